@@ -0,0 +1,235 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fs,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, SnakeAteEvent,
+    save_file::write_atomic,
+    snake::{BodySegment, SnakeTurnEvent},
+    strings::{MessageId, Strings},
+    test_mode::{self, TestMode},
+};
+
+// Ids of unlocked achievements, one per line
+const ACHIEVEMENTS_PATH: &str = "achievements.txt";
+const BANNER_DURATION: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy)]
+enum Goal {
+    // Foods eaten in a row without changing direction
+    StraightEats(usize),
+    // Seconds spent in a single run, pauses excluded
+    SurviveSecs(f32),
+    // Head plus body segments
+    Length(usize),
+}
+
+struct Achievement {
+    id: &'static str,
+    name: &'static str,
+    goal: Goal,
+}
+
+// New achievements only need an entry here
+const ACHIEVEMENTS: [Achievement; 3] = [
+    Achievement {
+        id: "straight_eats",
+        name: "Tunnel Vision",
+        goal: Goal::StraightEats(5),
+    },
+    Achievement {
+        id: "survive_2_minutes",
+        name: "Survivor",
+        goal: Goal::SurviveSecs(120.0),
+    },
+    Achievement {
+        id: "length_20",
+        name: "Growth Spurt",
+        goal: Goal::Length(20),
+    },
+];
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        if test_mode::uses_user_files(app) {
+            app.insert_resource(Achievements::load(ACHIEVEMENTS_PATH));
+        } else {
+            app.init_resource::<Achievements>();
+        }
+        app.init_resource::<RunProgress>();
+        app.init_resource::<Banner>();
+        app.add_systems(Startup, init_banner);
+        app.add_systems(OnEnter(GameState::StartGame), reset_progress);
+        app.add_systems(
+            Update,
+            (track_progress, unlock_achievements)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(Update, update_banner);
+        app.add_systems(
+            Last,
+            save_achievements_on_exit
+                .run_if(on_event::<AppExit>)
+                .run_if(test_mode::saves_user_files),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Achievements {
+    unlocked: HashSet<String>,
+}
+
+impl Achievements {
+    fn load(path: &str) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+
+        Self {
+            unlocked: contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    fn save(&self, path: &str) {
+        let mut ids: Vec<&str> = self.unlocked.iter().map(String::as_str).collect();
+        ids.sort();
+        let contents: String = ids.iter().map(|id| format!("{}\n", id)).collect();
+        if let Err(err) = write_atomic(path, &contents) {
+            println!("Could not save achievements to {}: {}", path, err);
+        }
+    }
+}
+
+// Counters for the current run
+#[derive(Resource, Default)]
+struct RunProgress {
+    eats_without_turning: usize,
+    survived: f32,
+    length: usize,
+}
+
+// Unlock notifications waiting to be shown, oldest first
+#[derive(Resource, Default)]
+struct Banner {
+    queue: VecDeque<&'static str>,
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct BannerText;
+
+fn init_banner(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.85, 0.2)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            top: Val::Px(8.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BannerText,
+    ));
+}
+
+fn reset_progress(mut progress: ResMut<RunProgress>) {
+    *progress = RunProgress::default();
+}
+
+fn track_progress(
+    time: Res<Time>,
+    mut turn_events: EventReader<SnakeTurnEvent>,
+    mut ate_events: EventReader<SnakeAteEvent>,
+    body_query: Query<(), With<BodySegment>>,
+    mut progress: ResMut<RunProgress>,
+) {
+    progress.survived += time.delta_secs();
+
+    // Turns come first, so food eaten right after a turn starts a new streak
+    if turn_events.read().count() > 0 {
+        progress.eats_without_turning = 0;
+    }
+    progress.eats_without_turning += ate_events.read().count();
+
+    progress.length = body_query.iter().count() + 1;
+}
+
+fn unlock_achievements(
+    progress: Res<RunProgress>,
+    test_mode: Res<TestMode>,
+    mut achievements: ResMut<Achievements>,
+    mut banner: ResMut<Banner>,
+) {
+    let mut unlocked_any = false;
+
+    for achievement in ACHIEVEMENTS.iter() {
+        if achievements.unlocked.contains(achievement.id) {
+            continue;
+        }
+
+        let reached = match achievement.goal {
+            Goal::StraightEats(count) => progress.eats_without_turning >= count,
+            Goal::SurviveSecs(secs) => progress.survived >= secs,
+            Goal::Length(length) => progress.length >= length,
+        };
+
+        if reached {
+            println!("Achievement unlocked: {}", achievement.name);
+            achievements.unlocked.insert(achievement.id.to_string());
+            banner.queue.push_back(achievement.name);
+            unlocked_any = true;
+        }
+    }
+
+    if unlocked_any && test_mode.uses_user_files() {
+        achievements.save(ACHIEVEMENTS_PATH);
+    }
+}
+
+fn save_achievements_on_exit(achievements: Res<Achievements>) {
+    achievements.save(ACHIEVEMENTS_PATH);
+}
+
+fn update_banner(
+    time: Res<Time>,
+    strings: Res<Strings>,
+    mut banner: ResMut<Banner>,
+    mut query: Query<(&mut Visibility, &mut Text), With<BannerText>>,
+) {
+    let Ok((mut visibility, mut text)) = query.single_mut() else {
+        return;
+    };
+
+    if banner.remaining > 0.0 {
+        banner.remaining -= time.delta_secs();
+        return;
+    }
+
+    match banner.queue.pop_front() {
+        Some(name) => {
+            text.0 = strings.format(MessageId::AchievementUnlocked, &[("name", &name)]);
+            *visibility = Visibility::Visible;
+            banner.remaining = BANNER_DURATION;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
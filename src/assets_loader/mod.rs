@@ -1,40 +1,318 @@
-use bevy::prelude::*;
+use std::path::Path;
+
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{
+    GameState, Tilemap,
+    controls::key_label,
+    game_config::GameConfig,
+    snake,
+    strings::{MessageId, Strings},
+};
+
+// Назви та шляхи спрайтових аркушів скінів. Усі мають однаковий макет.
+const SKINS: [(&str, &str); 2] = [("Green", "snake.png"), ("Blue", "snake_blue.png")];
+
+// Atlas cells used outside of the snake's own sprites
+pub const FLOOR_INDEX: usize = 11;
+pub const BORDER_INDEX: usize = 12;
+// Pieces of the wall around the field. The corner is rounded on its upper
+// left and mirrored for the other three.
+pub const BORDER_CORNER_INDEX: usize = 6;
+pub const BORDER_STRAIGHT_INDEX: usize = 13;
+pub const FOOD_INDEX: usize = 14;
+// Game over art, one per cause of death
+pub const GAME_OVER_WALL_INDEX: usize = 17;
+pub const GAME_OVER_BITE_INDEX: usize = 18;
+pub const GAME_OVER_CLOCK_INDEX: usize = 19;
+
+// Font for the HUD and message texts, relative to `assets/`
+pub const DEFAULT_UI_FONT: &str = "fonts/ui.ttf";
+// Looping background track, optional
+const MUSIC_PATH: &str = "music.ogg";
+
+const RETRY_KEY: KeyCode = KeyCode::KeyR;
+const QUIT_KEY: KeyCode = KeyCode::Escape;
 
-use crate::GameState;
 pub struct AssetsLoaderPlugin;
 
 impl Plugin for AssetsLoaderPlugin {
     fn build(&self, app: &mut App) {
         // Додаємо систему завантаження ресурсів під час запуску
         app.add_systems(Startup, load_game_assets);
+        app.add_systems(
+            Update,
+            wait_for_game_assets.run_if(in_state(GameState::AssetsLoading)),
+        );
+        app.add_systems(OnEnter(GameState::LoadError), show_load_error);
+        app.add_systems(
+            Update,
+            handle_load_error_inputs.run_if(in_state(GameState::LoadError)),
+        );
+        app.add_systems(OnExit(GameState::LoadError), hide_load_error);
+        app.add_systems(
+            Update,
+            apply_snake_skin.run_if(resource_changed::<SnakeSkin>),
+        );
+        app.add_systems(Update, apply_ui_font.run_if(resource_changed::<UiFont>));
     }
 }
 
 #[derive(Resource)]
 pub struct GameAssets {
     pub texture_atlas_layout: Handle<TextureAtlasLayout>,
+    // None when there's no track in the assets folder
+    pub music: Option<Handle<AudioSource>>,
+}
+
+// Font used by texts marked with `UsesUiFont`. The default handle is Bevy's
+// built-in font, used when the configured file doesn't exist.
+#[derive(Resource, Default)]
+pub struct UiFont(pub Handle<Font>);
+
+#[derive(Component)]
+pub struct UsesUiFont;
+
+pub struct Skin {
+    pub name: &'static str,
+    // Relative to `assets/`
+    pub path: &'static str,
     pub texture: Handle<Image>,
+    pub texture_atlas_layout: Handle<TextureAtlasLayout>,
+}
+
+// Available spritesheets and the one currently used by all sprites
+#[derive(Resource)]
+pub struct SnakeSkin {
+    pub skins: Vec<Skin>,
+    pub selected: usize,
+}
+
+impl SnakeSkin {
+    pub fn current(&self) -> &Skin {
+        &self.skins[self.selected]
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.skins.len();
+    }
+
+    // Sprite showing the given atlas cell of the current skin
+    pub fn sprite(&self, index: usize) -> Sprite {
+        let skin = self.current();
+        Sprite {
+            image: skin.texture.clone(),
+            texture_atlas: Some(TextureAtlas {
+                layout: skin.texture_atlas_layout.clone(),
+                index,
+            }),
+            ..default()
+        }
+    }
 }
 
 fn load_game_assets(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    game_config: Res<GameConfig>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    // Завантажуємо текстуру спрайтового аркуша.
-    let texture = asset_server.load("snake.png");
-
     // Визначаємо макет спрайтового аркуша: клітинки 8x8 пікселі, 4 стовпців, 5 рядків.
     let layout = TextureAtlasLayout::from_grid(UVec2::splat(8), 4, 5, None, None);
+    validate_atlas_indices(layout.textures.len());
     // Додаємо макет до сервера ресурсів та отримуємо його Handle.
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
 
-    // Вставляємо ресурс GameAssets у світ, щоб інші системи могли до нього отримати доступ.
+    // Завантажуємо текстури спрайтових аркушів для кожного скіна.
+    let skins = SKINS
+        .iter()
+        .map(|(name, path)| Skin {
+            name,
+            path,
+            texture: asset_server.load(*path),
+            texture_atlas_layout: texture_atlas_layout.clone(),
+        })
+        .collect();
+
+    // Вставляємо ресурси у світ, щоб інші системи могли до них отримати доступ.
     commands.insert_resource(GameAssets {
         texture_atlas_layout,
-        texture,
+        music: load_music(&asset_server),
     });
+    commands.insert_resource(SnakeSkin { skins, selected: 0 });
+    commands.insert_resource(load_ui_font(&asset_server, &game_config.ui_font));
+}
+
+// Spritesheets that failed to load, shown on the error screen
+#[derive(Resource)]
+struct FailedAssets(Vec<&'static str>);
+
+#[derive(Component)]
+struct LoadErrorText;
+
+// The menu only opens once every spritesheet is in, a missing or broken one
+// leads to the error screen instead of an empty playfield
+fn wait_for_game_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    snake_skin: Res<SnakeSkin>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let mut failed = Vec::new();
+    let mut loading = false;
+    for skin in snake_skin.skins.iter() {
+        match asset_server.load_state(&skin.texture) {
+            LoadState::Loaded => {}
+            LoadState::Failed(err) => {
+                error!("Could not load {}: {}", skin.path, err);
+                failed.push(skin.path);
+            }
+            _ => loading = true,
+        }
+    }
 
-    next_state.set(GameState::StartGame);
+    if !failed.is_empty() {
+        commands.insert_resource(FailedAssets(failed));
+        next_state.set(GameState::LoadError);
+    } else if !loading {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn show_load_error(
+    mut commands: Commands,
+    failed_assets: Res<FailedAssets>,
+    strings: Res<Strings>,
+    ui_font: Res<UiFont>,
+) {
+    commands.spawn((
+        Text::new(strings.format(
+            MessageId::LoadError,
+            &[
+                ("paths", &failed_assets.0.join("\n")),
+                ("retry", &key_label(RETRY_KEY)),
+                ("quit", &key_label(QUIT_KEY)),
+            ],
+        )),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font: ui_font.0.clone(),
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.3, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        LoadErrorText,
+    ));
+}
+
+// Asking the asset server for a failed path starts a fresh load of it
+fn handle_load_error_inputs(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    failed_assets: Res<FailedAssets>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if keyboard_input.just_pressed(RETRY_KEY) {
+        for path in failed_assets.0.iter() {
+            println!("Retrying {}", path);
+            let _: Handle<Image> = asset_server.load(*path);
+        }
+        next_state.set(GameState::AssetsLoading);
+    } else if keyboard_input.just_pressed(QUIT_KEY) {
+        exit.write(AppExit::Success);
+    }
+}
+
+fn hide_load_error(mut commands: Commands, text_query: Query<Entity, With<LoadErrorText>>) {
+    commands.remove_resource::<FailedAssets>();
+    for entity in text_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// The game plays fine without music, so a missing track isn't reported
+fn load_music(asset_server: &AssetServer) -> Option<Handle<AudioSource>> {
+    if !Path::new("assets").join(MUSIC_PATH).exists() {
+        debug!("No music at {}", MUSIC_PATH);
+        return None;
+    }
+    Some(asset_server.load(MUSIC_PATH))
+}
+
+fn load_ui_font(asset_server: &AssetServer, font_path: &str) -> UiFont {
+    if !Path::new("assets").join(font_path).exists() {
+        println!("UI font {} not found, using the default font", font_path);
+        return UiFont::default();
+    }
+    UiFont(asset_server.load(font_path.to_string()))
+}
+
+// Catches sprites that point past the end of the grid, e.g. after the
+// spritesheet layout changes
+fn validate_atlas_indices(cell_count: usize) {
+    let mut out_of_range: Vec<usize> = snake::atlas_indices()
+        .chain([
+            FLOOR_INDEX,
+            BORDER_INDEX,
+            BORDER_CORNER_INDEX,
+            BORDER_STRAIGHT_INDEX,
+            FOOD_INDEX,
+            GAME_OVER_WALL_INDEX,
+            GAME_OVER_BITE_INDEX,
+            GAME_OVER_CLOCK_INDEX,
+        ])
+        .filter(|index| *index >= cell_count)
+        .collect();
+    out_of_range.sort();
+    out_of_range.dedup();
+
+    if !out_of_range.is_empty() {
+        error!(
+            "Sprite atlas has {} cells but these indices are used: {:?}",
+            cell_count, out_of_range
+        );
+    }
+}
+
+// Rebinds already spawned sprites and the tilemap when the skin changes
+fn apply_snake_skin(
+    snake_skin: Res<SnakeSkin>,
+    mut sprite_query: Query<&mut Sprite>,
+    tilemap_query: Query<&MeshMaterial2d<ColorMaterial>, With<Tilemap>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let skin = snake_skin.current();
+
+    for mut sprite in sprite_query.iter_mut() {
+        let Sprite {
+            image,
+            texture_atlas,
+            ..
+        } = sprite.as_mut();
+        if let Some(atlas) = texture_atlas {
+            atlas.layout = skin.texture_atlas_layout.clone();
+            *image = skin.texture.clone();
+        }
+    }
+
+    for material_handle in tilemap_query.iter() {
+        if let Some(material) = materials.get_mut(&material_handle.0) {
+            material.texture = Some(skin.texture.clone());
+        }
+    }
+}
+
+fn apply_ui_font(ui_font: Res<UiFont>, mut text_query: Query<&mut TextFont, With<UsesUiFont>>) {
+    for mut text_font in text_query.iter_mut() {
+        text_font.font = ui_font.0.clone();
+    }
 }
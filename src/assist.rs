@@ -0,0 +1,595 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::prelude::*;
+
+use crate::{
+    BorderSegment, Food, GameMode, GameState, GridConfig, GridOrigin, Position, TILE_SIZE,
+    assets_loader::SnakeSkin,
+    effects::BorderFlash,
+    grid_to_screen_position,
+    obstacles::Obstacle,
+    snake::{BodySegment, Dir, Head, LastDirection, NextSegment, head_atlas_index},
+    strings::{MessageId, Strings},
+};
+
+const PULSE_SPEED: f32 = 6.0;
+const PULSE_AMPLITUDE: f32 = 0.25;
+// The flood fill only runs once the board is this crowded, so open boards cost nothing
+const TRAP_CHECK_FREE_CELLS: usize = 128;
+const TRAP_BLINK_SPEED: f32 = 8.0;
+// One screen pixel at the camera's 4x zoom
+const GRID_LINE_WIDTH: f32 = 0.25;
+// Above the floor tiles, below the snake and food
+const GRID_LINE_Z: f32 = -5.0;
+const PATH_COLOR: Color = Color::srgba(1.0, 1.0, 0.5, 0.2);
+// Above portals and speed tiles, below the snake and food
+const PATH_Z: f32 = -0.25;
+// Border cells closer to the head than this, in steps, turn red
+const WALL_WARNING_DISTANCE: i32 = 3;
+// Only the closest cells are tinted, a corner never needs more
+const WALL_WARNING_MAX_SEGMENTS: usize = 8;
+const WALL_WARNING_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+const HINT_ALPHA: f32 = 0.35;
+const NO_SAFE_MOVE_COLOR: Color = Color::srgba(1.0, 0.2, 0.2, 0.5);
+// Above the food path, below the snake and food
+const HINT_Z: f32 = -0.1;
+// Over the head it warns about
+const NO_SAFE_MOVE_Z: f32 = 0.5;
+
+pub struct AssistPlugin;
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HighVisibility>();
+        app.init_resource::<TrapWarning>();
+        app.init_resource::<Trapped>();
+        app.add_systems(Startup, init_trap_warning);
+        app.add_systems(OnEnter(GameState::StartGame), clear_trapped);
+        app.init_resource::<ShowGridLines>();
+        app.init_resource::<AssistMode>();
+        app.init_resource::<PracticeMode>();
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            (despawn_food_path, despawn_move_hint),
+        );
+        app.add_systems(
+            Update,
+            (
+                toggle_assist_mode,
+                update_food_path.run_if(in_state(GameState::InGame)),
+                tint_nearby_walls.run_if(in_state(GameState::InGame)),
+            )
+                .chain(),
+        );
+        app.add_systems(
+            Update,
+            (
+                toggle_practice_mode,
+                suggest_move.run_if(in_state(GameState::InGame)),
+            )
+                .chain(),
+        );
+        app.add_systems(Update, (toggle_high_visibility, food_pulse).chain());
+        app.add_systems(
+            Update,
+            (
+                toggle_grid_lines,
+                update_grid_lines.run_if(resource_changed::<ShowGridLines>),
+            )
+                .chain(),
+        );
+        app.add_systems(
+            Update,
+            (
+                toggle_trap_warning,
+                check_trap
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(resource_equals(TrapWarning(true))),
+                blink_trap_warning,
+            )
+                .chain(),
+        );
+    }
+}
+
+// Makes the food easier to spot for low-vision players
+#[derive(Resource, Default)]
+pub struct HighVisibility(pub bool);
+
+// Warns new players when the snake is about to wall itself into a pocket
+#[derive(Resource, Default, PartialEq)]
+pub struct TrapWarning(pub bool);
+
+#[derive(Resource, Default)]
+struct Trapped(bool);
+
+#[derive(Component)]
+struct TrapWarningText;
+
+// Thin lines between the playfield cells to make distances easier to judge
+#[derive(Resource, Default)]
+pub struct ShowGridLines(pub bool);
+
+#[derive(Component)]
+struct GridLine;
+
+// Tutorial aid: highlights the shortest way from the head to the closest food
+// and tints the walls the head is getting close to
+#[derive(Resource, Default)]
+pub struct AssistMode(pub bool);
+
+#[derive(Component)]
+struct PathHighlight;
+
+// Practice aid: shows the move that heads for the food while keeping a way
+// out, as a ghost head on the cell. The player still does the steering.
+#[derive(Resource, Default)]
+pub struct PracticeMode(pub bool);
+
+#[derive(Component)]
+struct MoveHint;
+
+fn blocked_cells(
+    body_query: &Query<&Position, With<BodySegment>>,
+    obstacle_query: &Query<&Position, With<Obstacle>>,
+) -> HashSet<(i8, i8)> {
+    body_query
+        .iter()
+        .chain(obstacle_query.iter())
+        .map(|pos| (pos.x, pos.y))
+        .collect()
+}
+
+fn toggle_high_visibility(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut high_visibility: ResMut<HighVisibility>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F2) {
+        high_visibility.0 = !high_visibility.0;
+    }
+}
+
+// Only the scale is animated, so the food's grid position and translation stay intact
+fn food_pulse(
+    time: Res<Time>,
+    high_visibility: Res<HighVisibility>,
+    mut food_query: Query<&mut Transform, With<Food>>,
+) {
+    if !high_visibility.0 && !high_visibility.is_changed() {
+        return;
+    }
+
+    let scale = if high_visibility.0 {
+        1.0 + PULSE_AMPLITUDE * (time.elapsed_secs() * PULSE_SPEED).sin()
+    } else {
+        1.0
+    };
+
+    for mut transform in food_query.iter_mut() {
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+}
+
+fn toggle_trap_warning(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut trap_warning: ResMut<TrapWarning>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        trap_warning.0 = !trap_warning.0;
+    }
+}
+
+fn init_trap_warning(mut commands: Commands, strings: Res<Strings>) {
+    commands.spawn((
+        Text::new(strings.get(MessageId::TrapWarning)),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.3, 0.3)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            top: Val::Px(40.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TrapWarningText,
+    ));
+}
+
+fn clear_trapped(mut trapped: ResMut<Trapped>) {
+    trapped.0 = false;
+}
+
+// Runs once per tick (when the head moves). The snake counts as trapped when the
+// free area reachable from its head is smaller than its body.
+fn check_trap(
+    head_query: Query<&Position, (With<Head>, Changed<Position>)>,
+    body_query: Query<&Position, With<BodySegment>>,
+    obstacle_query: Query<&Position, With<Obstacle>>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    mut trapped: ResMut<Trapped>,
+) {
+    let Ok(head_pos) = head_query.single() else {
+        return;
+    };
+
+    let blocked = blocked_cells(&body_query, &obstacle_query);
+
+    let (from, to) = game_mode.field_bounds(&grid);
+    let cell_count = (to.0 - from.0 + 1) as usize * (to.1 - from.1 + 1) as usize;
+    let free_cells = cell_count.saturating_sub(blocked.len() + 1);
+    if free_cells > TRAP_CHECK_FREE_CELLS {
+        trapped.0 = false;
+        return;
+    }
+
+    let snake_length = body_query.iter().count();
+    let in_bounds = |(x, y): (i8, i8)| x >= from.0 && x <= to.0 && y >= from.1 && y <= to.1;
+
+    // Flood fill from the head, stopping early once there is enough room
+    let head = (head_pos.x, head_pos.y);
+    let mut visited = HashSet::from([head]);
+    let mut queue = VecDeque::from([head]);
+    let mut reachable = 0;
+    while let Some((x, y)) = queue.pop_front() {
+        if reachable >= snake_length {
+            break;
+        }
+        for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if in_bounds(next) && !blocked.contains(&next) && visited.insert(next) {
+                reachable += 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    trapped.0 = reachable < snake_length;
+}
+
+fn blink_trap_warning(
+    time: Res<Time>,
+    trap_warning: Res<TrapWarning>,
+    trapped: Res<Trapped>,
+    game_state: Res<State<GameState>>,
+    mut query: Query<&mut Visibility, With<TrapWarningText>>,
+) {
+    let active = trap_warning.0 && trapped.0 && *game_state.get() == GameState::InGame;
+    let visible = active && (time.elapsed_secs() * TRAP_BLINK_SPEED).sin() > 0.0;
+
+    if let Ok(mut visibility) = query.single_mut() {
+        *visibility = if visible {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn toggle_grid_lines(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut show_grid_lines: ResMut<ShowGridLines>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        show_grid_lines.0 = !show_grid_lines.0;
+    }
+}
+
+// Lines are only spawned while shown, so a disabled grid leaves nothing behind
+fn update_grid_lines(
+    mut commands: Commands,
+    show_grid_lines: Res<ShowGridLines>,
+    grid: Res<GridConfig>,
+    line_query: Query<Entity, With<GridLine>>,
+) {
+    for entity in line_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !show_grid_lines.0 {
+        return;
+    }
+
+    let color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+    // Cell centres sit on multiples of TILE_SIZE, so the edges are half a tile off
+    let left = grid.from.0 as f32 * TILE_SIZE - TILE_SIZE / 2.0;
+    let right = grid.to.0 as f32 * TILE_SIZE + TILE_SIZE / 2.0;
+    let bottom = grid.from.1 as f32 * TILE_SIZE - TILE_SIZE / 2.0;
+    let top = grid.to.1 as f32 * TILE_SIZE + TILE_SIZE / 2.0;
+
+    for x in grid.from.0..=grid.to.0 + 1 {
+        let x_pos = x as f32 * TILE_SIZE - TILE_SIZE / 2.0;
+        commands.spawn((
+            GridLine,
+            Sprite::from_color(color, Vec2::new(GRID_LINE_WIDTH, top - bottom)),
+            Transform::from_xyz(x_pos, (top + bottom) / 2.0, GRID_LINE_Z),
+        ));
+    }
+
+    for y in grid.from.1..=grid.to.1 + 1 {
+        let y_pos = y as f32 * TILE_SIZE - TILE_SIZE / 2.0;
+        commands.spawn((
+            GridLine,
+            Sprite::from_color(color, Vec2::new(right - left, GRID_LINE_WIDTH)),
+            Transform::from_xyz((left + right) / 2.0, y_pos, GRID_LINE_Z),
+        ));
+    }
+}
+
+fn toggle_assist_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut assist_mode: ResMut<AssistMode>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F1) {
+        assist_mode.0 = !assist_mode.0;
+    }
+}
+
+fn despawn_food_path(mut commands: Commands, path_query: Query<Entity, With<PathHighlight>>) {
+    for entity in path_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// Only recomputed when the head or a food moves, or the mode is toggled.
+// A boxed in snake gets no path rather than a misleading one.
+fn update_food_path(
+    mut commands: Commands,
+    assist_mode: Res<AssistMode>,
+    head_query: Query<Ref<Position>, With<Head>>,
+    food_query: Query<Ref<Position>, With<Food>>,
+    body_query: Query<&Position, With<BodySegment>>,
+    obstacle_query: Query<&Position, With<Obstacle>>,
+    path_query: Query<Entity, With<PathHighlight>>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+) {
+    let Ok(head_pos) = head_query.single() else {
+        return;
+    };
+    let moved = head_pos.is_changed() || food_query.iter().any(|pos| pos.is_changed());
+    if !assist_mode.is_changed() && !moved {
+        return;
+    }
+
+    for entity in path_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !assist_mode.0 {
+        return;
+    }
+
+    let blocked = blocked_cells(&body_query, &obstacle_query);
+    let food_cells: HashSet<(i8, i8)> = food_query.iter().map(|pos| (pos.x, pos.y)).collect();
+    let (from, to) = game_mode.field_bounds(&grid);
+    let in_bounds = |(x, y): (i8, i8)| x >= from.0 && x <= to.0 && y >= from.1 && y <= to.1;
+
+    // Breadth first, so the first food reached is the closest one
+    let head = (head_pos.x, head_pos.y);
+    let mut came_from = HashMap::from([(head, head)]);
+    let mut queue = VecDeque::from([head]);
+    let mut target = None;
+    while let Some((x, y)) = queue.pop_front() {
+        if food_cells.contains(&(x, y)) {
+            target = Some((x, y));
+            break;
+        }
+        for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if in_bounds(next) && !blocked.contains(&next) && !came_from.contains_key(&next) {
+                came_from.insert(next, (x, y));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let Some(target) = target else {
+        return;
+    };
+
+    // Walk back from the food, highlighting the cells in between
+    let mut cell = came_from[&target];
+    while cell != head {
+        let mut translation = grid_to_screen_position(
+            &Position {
+                x: cell.0,
+                y: cell.1,
+            },
+            *grid_origin,
+        );
+        translation.z = PATH_Z;
+        commands.spawn((
+            PathHighlight,
+            Sprite::from_color(PATH_COLOR, Vec2::splat(TILE_SIZE)),
+            Transform::from_translation(translation),
+        ));
+        cell = came_from[&cell];
+    }
+}
+
+// The border flash owns the border colors while it runs
+fn tint_nearby_walls(
+    assist_mode: Res<AssistMode>,
+    border_flash: Res<BorderFlash>,
+    head_query: Query<Ref<Position>, With<Head>>,
+    mut border_query: Query<(Entity, &Position, &mut Sprite), With<BorderSegment>>,
+    mut tinted: Local<Vec<Entity>>,
+) {
+    if border_flash.is_active() {
+        tinted.clear();
+        return;
+    }
+    let Ok(head_pos) = head_query.single() else {
+        return;
+    };
+    if !assist_mode.is_changed() && !head_pos.is_changed() {
+        return;
+    }
+
+    for entity in tinted.drain(..) {
+        if let Ok((_, _, mut sprite)) = border_query.get_mut(entity) {
+            sprite.color = Color::WHITE;
+        }
+    }
+    if !assist_mode.0 {
+        return;
+    }
+
+    let mut nearby: Vec<(i32, Entity)> = border_query
+        .iter()
+        .map(|(entity, pos, _)| {
+            let distance =
+                (pos.x as i32 - head_pos.x as i32).abs() + (pos.y as i32 - head_pos.y as i32).abs();
+            (distance, entity)
+        })
+        .filter(|(distance, _)| *distance <= WALL_WARNING_DISTANCE)
+        .collect();
+    nearby.sort_by_key(|(distance, _)| *distance);
+    nearby.truncate(WALL_WARNING_MAX_SEGMENTS);
+
+    // Fully red right next to the head, fading out with the distance
+    for (distance, entity) in nearby {
+        if let Ok((_, _, mut sprite)) = border_query.get_mut(entity) {
+            let closeness = 1.0 - (distance - 1) as f32 / WALL_WARNING_DISTANCE as f32;
+            sprite.color = Color::WHITE.mix(&WALL_WARNING_COLOR, closeness.clamp(0.0, 1.0));
+            tinted.push(entity);
+        }
+    }
+}
+
+fn toggle_practice_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut practice_mode: ResMut<PracticeMode>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F12) {
+        practice_mode.0 = !practice_mode.0;
+    }
+}
+
+fn despawn_move_hint(mut commands: Commands, hint_query: Query<Entity, With<MoveHint>>) {
+    for entity in hint_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// Steps from `start` to the closest of `targets`, None when they're walled off
+fn distance_to(
+    start: (i8, i8),
+    targets: &HashSet<(i8, i8)>,
+    blocked: &HashSet<(i8, i8)>,
+    in_bounds: impl Fn((i8, i8)) -> bool,
+) -> Option<usize> {
+    let mut steps = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some((x, y)) = queue.pop_front() {
+        let step = steps[&(x, y)];
+        if targets.contains(&(x, y)) {
+            return Some(step);
+        }
+        for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if in_bounds(next) && !blocked.contains(&next) && !steps.contains_key(&next) {
+                steps.insert(next, step + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+// A move is safe when the next cell is free and the head can still reach the
+// tail from there. The tail keeps moving out of the way, so a snake that can
+// follow it is never boxed in. Of the safe moves the one closest to the food
+// is shown, and the head turns red when there's none.
+// Only recomputed when the head or a food moves, or the mode is toggled.
+fn suggest_move(
+    mut commands: Commands,
+    practice_mode: Res<PracticeMode>,
+    snake_skin: Res<SnakeSkin>,
+    head_query: Query<(Ref<Position>, &LastDirection), With<Head>>,
+    food_query: Query<Ref<Position>, With<Food>>,
+    body_query: Query<&Position, With<BodySegment>>,
+    tail_query: Query<&Position, (With<BodySegment>, Without<NextSegment>)>,
+    obstacle_query: Query<&Position, With<Obstacle>>,
+    hint_query: Query<Entity, With<MoveHint>>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+) {
+    let Ok((head_pos, last_direction)) = head_query.single() else {
+        return;
+    };
+    let moved = head_pos.is_changed() || food_query.iter().any(|pos| pos.is_changed());
+    if !practice_mode.is_changed() && !moved {
+        return;
+    }
+
+    for entity in hint_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !practice_mode.0 {
+        return;
+    }
+
+    let head = (head_pos.x, head_pos.y);
+    let tail = tail_query.single().ok().map(|pos| (pos.x, pos.y));
+    // After the move the tail's cell is free and the head's cell is body
+    let mut blocked = blocked_cells(&body_query, &obstacle_query);
+    if let Some(tail) = tail {
+        blocked.remove(&tail);
+    }
+    blocked.insert(head);
+
+    let food_cells: HashSet<(i8, i8)> = food_query.iter().map(|pos| (pos.x, pos.y)).collect();
+    let (from, to) = game_mode.field_bounds(&grid);
+    let in_bounds = |(x, y): (i8, i8)| x >= from.0 && x <= to.0 && y >= from.1 && y <= to.1;
+
+    let best = [Dir::Up, Dir::Right, Dir::Down, Dir::Left]
+        .into_iter()
+        .filter(|dir| *dir != last_direction.0.opposite())
+        .filter_map(|dir| {
+            let (dx, dy) = dir.offset();
+            let next = (head.0 + dx, head.1 + dy);
+            if !in_bounds(next) || blocked.contains(&next) {
+                return None;
+            }
+            if let Some(tail) = tail
+                && distance_to(next, &HashSet::from([tail]), &blocked, in_bounds).is_none()
+            {
+                return None;
+            }
+            let food_distance =
+                distance_to(next, &food_cells, &blocked, in_bounds).unwrap_or(usize::MAX);
+            Some((food_distance, dir, next))
+        })
+        .min_by_key(|(food_distance, _, _)| *food_distance);
+
+    let (sprite, cell) = match best {
+        Some((_, dir, next)) => {
+            let mut sprite = snake_skin.sprite(head_atlas_index(dir));
+            sprite.color.set_alpha(HINT_ALPHA);
+            (sprite, next)
+        }
+        None => (
+            Sprite::from_color(NO_SAFE_MOVE_COLOR, Vec2::splat(TILE_SIZE)),
+            head,
+        ),
+    };
+
+    let mut translation = grid_to_screen_position(
+        &Position {
+            x: cell.0,
+            y: cell.1,
+        },
+        *grid_origin,
+    );
+    translation.z = if best.is_some() {
+        HINT_Z
+    } else {
+        NO_SAFE_MOVE_Z
+    };
+    commands.spawn((MoveHint, sprite, Transform::from_translation(translation)));
+}
@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use bevy::input::mouse::MouseWheel;
+
+use crate::{
+    GameMode, GameState, GridOrigin, Position, grid_to_screen_position, settings::Settings,
+    snake::Head, test_mode::TestMode,
+};
+
+const DEFAULT_FOLLOW_SMOOTHING: f32 = 5.0;
+// Jumps longer than this (e.g. wrapping around the roam area) are not smoothed
+const SNAP_DISTANCE: f32 = 64.0;
+// Camera scale, 0.25 shows every pixel of the sprites as 4x4 screen pixels
+pub const DEFAULT_ZOOM: f32 = 0.25;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 0.5;
+// Scale change per key press or wheel notch
+const ZOOM_STEP: f32 = 1.1;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraFollow>();
+        app.init_resource::<CameraZoom>();
+        app.init_resource::<StableCamera>();
+        app.add_systems(Startup, load_camera_zoom);
+        app.add_systems(
+            Update,
+            (
+                apply_stable_camera.run_if(resource_changed::<Settings>),
+                handle_zoom_inputs.run_if(resource_equals(StableCamera(false))),
+                apply_camera_zoom
+                    .run_if(resource_changed::<CameraZoom>.or(resource_changed::<StableCamera>)),
+            )
+                .chain(),
+        );
+        app.add_systems(OnEnter(GameState::StartGame), reset_camera);
+        app.add_systems(OnEnter(GameState::MainMenu), reset_camera);
+        app.add_systems(
+            Update,
+            camera_follow_head
+                .run_if(in_state(GameState::InGame))
+                .run_if(resource_equals(GameMode::Endless))
+                .run_if(resource_equals(StableCamera(false))),
+        );
+        app.add_systems(
+            PostUpdate,
+            move_camera_anchored.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct CameraFollow {
+    // Higher values catch up with the head faster
+    pub smoothing: f32,
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        Self {
+            smoothing: DEFAULT_FOLLOW_SMOOTHING,
+        }
+    }
+}
+
+// Current camera scale, saved with the settings. Smaller values zoom in.
+#[derive(Resource)]
+pub struct CameraZoom(pub f32);
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self(DEFAULT_ZOOM)
+    }
+}
+
+// Meant for recording footage: the camera stays at its default position and
+// zoom, ignoring the head follow and the zoom keys until turned off again
+#[derive(Resource, Default, PartialEq)]
+pub struct StableCamera(pub bool);
+
+// Scale the camera is drawn at, a stable camera ignores the zoom
+pub fn view_scale(zoom: &CameraZoom, stable_camera: &StableCamera) -> f32 {
+    if stable_camera.0 {
+        DEFAULT_ZOOM
+    } else {
+        zoom.0
+    }
+}
+
+// World-space HUD elements that keep their offset from the camera when it moves
+#[derive(Component)]
+pub struct CameraAnchored(pub Vec3);
+
+fn camera_follow_head(
+    time: Res<Time>,
+    follow: Res<CameraFollow>,
+    head_query: Query<&Position, With<Head>>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    let (Ok(head_pos), Ok(mut camera_transform)) = (head_query.single(), camera_query.single_mut())
+    else {
+        return;
+    };
+
+    // The head keeps the board's offset from the middle of the window
+    let target = grid_to_screen_position(head_pos, GridOrigin::default()).truncate();
+    let current = camera_transform.translation.truncate();
+
+    let position = if current.distance(target) > SNAP_DISTANCE {
+        target
+    } else {
+        let step = 1.0 - (-follow.smoothing * time.delta_secs()).exp();
+        current.lerp(target, step)
+    };
+
+    camera_transform.translation.x = position.x;
+    camera_transform.translation.y = position.y;
+}
+
+fn reset_camera(mut camera_query: Query<&mut Transform, With<Camera2d>>) {
+    if let Ok(mut camera_transform) = camera_query.single_mut() {
+        camera_transform.translation.x = 0.0;
+        camera_transform.translation.y = 0.0;
+    }
+}
+
+fn move_camera_anchored(
+    camera_query: Query<&Transform, (With<Camera2d>, Changed<Transform>)>,
+    mut anchored_query: Query<(&mut Transform, &CameraAnchored), Without<Camera2d>>,
+) {
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+
+    let camera_offset = camera_transform.translation.truncate().extend(0.0);
+    for (mut transform, anchored) in anchored_query.iter_mut() {
+        transform.translation = anchored.0 + camera_offset;
+    }
+}
+
+fn apply_stable_camera(settings: Res<Settings>, mut stable_camera: ResMut<StableCamera>) {
+    if stable_camera.0 != settings.stable_camera {
+        stable_camera.0 = settings.stable_camera;
+    }
+}
+
+fn load_camera_zoom(settings: Res<Settings>, mut zoom: ResMut<CameraZoom>) {
+    zoom.0 = settings.camera_zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+}
+
+// `+`/`-` and the mouse wheel zoom, `0` goes back to the default
+fn handle_zoom_inputs(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    test_mode: Res<TestMode>,
+    mut zoom: ResMut<CameraZoom>,
+    mut settings: ResMut<Settings>,
+) {
+    let mut new_zoom = zoom.0;
+
+    if keyboard_input.just_pressed(KeyCode::Equal)
+        || keyboard_input.just_pressed(KeyCode::NumpadAdd)
+    {
+        new_zoom /= ZOOM_STEP;
+    }
+    if keyboard_input.just_pressed(KeyCode::Minus)
+        || keyboard_input.just_pressed(KeyCode::NumpadSubtract)
+    {
+        new_zoom *= ZOOM_STEP;
+    }
+    for event in wheel_events.read() {
+        new_zoom *= ZOOM_STEP.powf(-event.y.signum());
+    }
+    if keyboard_input.just_pressed(KeyCode::Digit0) {
+        new_zoom = DEFAULT_ZOOM;
+    }
+
+    let new_zoom = new_zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    if new_zoom != zoom.0 {
+        zoom.0 = new_zoom;
+        settings.camera_zoom = new_zoom;
+        settings.store(&test_mode);
+    }
+}
+
+// Only the scale changes, so the camera keeps looking at the same point.
+// Sprites stay crisp thanks to the nearest-neighbour image sampler.
+// A stable camera is put back to its default transform instead.
+fn apply_camera_zoom(
+    zoom: Res<CameraZoom>,
+    stable_camera: Res<StableCamera>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    let Ok(mut camera_transform) = camera_query.single_mut() else {
+        return;
+    };
+
+    if stable_camera.0 {
+        camera_transform.translation.x = 0.0;
+        camera_transform.translation.y = 0.0;
+    }
+    let scale = view_scale(&zoom, &stable_camera);
+    camera_transform.scale = Vec3::new(scale, scale, 1.0);
+}
@@ -0,0 +1,203 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use bevy::{
+    input::{
+        ButtonState, InputSystem,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{GameMode, GameState, spawn_food, test_mode::TestMode};
+
+// Shareable runs: every run draws its food and obstacle cells from a seed,
+// and the seed plus the game mode make up a short code shown on the game over
+// screen. Entering the code in the menu plays the same layout again.
+//
+// 8 bytes of seed and 1 of mode, base64 without padding
+const CODE_LENGTH: usize = 12;
+const CODE_BYTES: usize = 9;
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSeed>();
+        app.init_resource::<ChallengeInput>();
+        // Like the console, typed keys never reach the menu navigation
+        app.add_systems(
+            PreUpdate,
+            read_challenge_input
+                .after(InputSystem)
+                .run_if(in_state(GameState::MainMenu)),
+        );
+        app.add_systems(OnExit(GameState::MainMenu), cancel_challenge_input);
+        app.add_systems(OnEnter(GameState::StartGame), seed_run.before(spawn_food));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChallengeCode {
+    pub seed: u64,
+    pub mode: GameMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChallengeCodeError {
+    NotBase64,
+    WrongLength,
+    UnknownMode,
+}
+
+impl std::fmt::Display for ChallengeCodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ChallengeCodeError::NotBase64 => write!(f, "not a valid code"),
+            ChallengeCodeError::WrongLength => {
+                write!(f, "codes are {} characters long", CODE_LENGTH)
+            }
+            ChallengeCodeError::UnknownMode => write!(f, "unknown game mode"),
+        }
+    }
+}
+
+impl ChallengeCode {
+    pub fn encode(&self) -> String {
+        let mut bytes = [0; CODE_BYTES];
+        bytes[..8].copy_from_slice(&self.seed.to_le_bytes());
+        bytes[8] = match self.mode {
+            GameMode::Classic => 0,
+            GameMode::Timed => 1,
+            GameMode::Endless => 2,
+        };
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    pub fn decode(code: &str) -> Result<Self, ChallengeCodeError> {
+        let code = code.trim();
+        if code.len() != CODE_LENGTH {
+            return Err(ChallengeCodeError::WrongLength);
+        }
+        let bytes = URL_SAFE_NO_PAD
+            .decode(code)
+            .map_err(|_| ChallengeCodeError::NotBase64)?;
+        let bytes: [u8; CODE_BYTES] = bytes
+            .try_into()
+            .map_err(|_| ChallengeCodeError::WrongLength)?;
+
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&bytes[..8]);
+        let mode = match bytes[8] {
+            0 => GameMode::Classic,
+            1 => GameMode::Timed,
+            2 => GameMode::Endless,
+            _ => return Err(ChallengeCodeError::UnknownMode),
+        };
+        Ok(Self {
+            seed: u64::from_le_bytes(seed),
+            mode,
+        })
+    }
+}
+
+// Seed of the current run, None in test mode which keeps its own fixed seed
+#[derive(Resource, Default)]
+pub struct RunSeed(pub Option<ChallengeCode>);
+
+// Code typed in the menu. `pending` holds an accepted code until the run starts.
+#[derive(Resource, Default)]
+pub struct ChallengeInput {
+    pub active: bool,
+    pub text: String,
+    pub error: bool,
+    pending: Option<ChallengeCode>,
+}
+
+impl ChallengeInput {
+    pub fn start(&mut self) {
+        self.active = true;
+        self.text.clear();
+        self.error = false;
+    }
+}
+
+fn is_code_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+// Enter starts the challenge, Escape goes back to the menu
+fn read_challenge_input(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut key_events: EventReader<KeyboardInput>,
+    mut challenge_input: ResMut<ChallengeInput>,
+    mut game_mode: ResMut<GameMode>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if !challenge_input.active {
+        key_events.clear();
+        return;
+    }
+
+    keyboard_input.reset_all();
+
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => match ChallengeCode::decode(&challenge_input.text) {
+                Ok(code) => {
+                    info!("Starting challenge {}", code.encode());
+                    *game_mode = code.mode;
+                    challenge_input.pending = Some(code);
+                    challenge_input.active = false;
+                    game_state.set(GameState::StartGame);
+                }
+                Err(err) => {
+                    info!(
+                        "Rejected challenge code {:?}: {}",
+                        challenge_input.text, err
+                    );
+                    challenge_input.error = true;
+                }
+            },
+            Key::Backspace => {
+                challenge_input.text.pop();
+                challenge_input.error = false;
+            }
+            Key::Escape => challenge_input.active = false,
+            Key::Character(text) => {
+                for c in text.chars().filter(|c| is_code_char(*c)) {
+                    if challenge_input.text.len() < CODE_LENGTH {
+                        challenge_input.text.push(c);
+                        challenge_input.error = false;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn cancel_challenge_input(mut challenge_input: ResMut<ChallengeInput>) {
+    challenge_input.active = false;
+}
+
+// An entered code is used once, every other run gets a fresh seed
+fn seed_run(
+    game_mode: Res<GameMode>,
+    mut challenge_input: ResMut<ChallengeInput>,
+    mut run_seed: ResMut<RunSeed>,
+    mut test_mode: ResMut<TestMode>,
+) {
+    if test_mode.enabled {
+        run_seed.0 = None;
+        return;
+    }
+    let code = challenge_input.pending.take().unwrap_or(ChallengeCode {
+        seed: rand::rng().random(),
+        mode: *game_mode,
+    });
+    test_mode.seed_run(code.seed);
+    run_seed.0 = Some(code);
+}
@@ -0,0 +1,247 @@
+use bevy::{
+    input::{
+        ButtonState, InputSystem,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+
+use crate::{
+    Food, GameState, GridOrigin, Position,
+    assets_loader::UsesUiFont,
+    grid_to_screen_position,
+    snake::{Ate, Head, TurnDelay},
+};
+
+// Developer console for poking at a running game, opened with the backtick
+// key. Only available in builds with the `dev_console` feature:
+//
+//     cargo run --features dev_console
+//
+// Commands: `grow <segments>`, `food <x> <y>`, `speed <seconds per tick>`
+// and `goto <state>`, e.g. `goto gameover`.
+const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+const PROMPT: &str = "> ";
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        if !cfg!(feature = "dev_console") {
+            return;
+        }
+
+        app.init_resource::<Console>();
+        app.init_resource::<PendingGrowth>();
+        app.add_systems(Startup, init_console);
+        // Runs right after the keyboard state is updated, so while the console
+        // is open the rest of the game never sees the typed keys
+        app.add_systems(PreUpdate, read_console_input.after(InputSystem));
+        app.add_systems(
+            Update,
+            (
+                update_console_text.run_if(resource_changed::<Console>),
+                feed_pending_growth.run_if(in_state(GameState::InGame)),
+            ),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    // Reply to the last command
+    output: String,
+}
+
+// Segments still to be added by `grow`, one per tick like regular food
+#[derive(Resource, Default)]
+struct PendingGrowth(usize);
+
+#[derive(Component)]
+struct ConsoleText;
+
+enum ConsoleCommand {
+    Grow(usize),
+    Food(i8, i8),
+    Speed(f32),
+    Goto(GameState),
+}
+
+impl ConsoleCommand {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("Empty command")?;
+        let args: Vec<&str> = words.collect();
+
+        let arg = |index: usize| -> Result<&str, String> {
+            args.get(index)
+                .copied()
+                .ok_or_else(|| format!("{} needs {} argument(s)", name, index + 1))
+        };
+
+        match name {
+            "grow" => arg(0)?
+                .parse()
+                .map(ConsoleCommand::Grow)
+                .map_err(|_| "grow takes a number of segments".to_string()),
+            "food" => match (arg(0)?.parse(), arg(1)?.parse()) {
+                (Ok(x), Ok(y)) => Ok(ConsoleCommand::Food(x, y)),
+                _ => Err("food takes a cell, e.g. food 3 3".to_string()),
+            },
+            "speed" => match arg(0)?.parse::<f32>() {
+                Ok(delay) if delay > 0.0 => Ok(ConsoleCommand::Speed(delay)),
+                _ => Err("speed takes the seconds per tick, e.g. speed 0.5".to_string()),
+            },
+            "goto" => match arg(0)?.to_lowercase().as_str() {
+                "menu" | "mainmenu" => Ok(ConsoleCommand::Goto(GameState::MainMenu)),
+                "settings" => Ok(ConsoleCommand::Goto(GameState::Settings)),
+                "leaderboard" => Ok(ConsoleCommand::Goto(GameState::Leaderboard)),
+                "start" | "startgame" => Ok(ConsoleCommand::Goto(GameState::StartGame)),
+                "ingame" => Ok(ConsoleCommand::Goto(GameState::InGame)),
+                "paused" => Ok(ConsoleCommand::Goto(GameState::Paused)),
+                "gameover" => Ok(ConsoleCommand::Goto(GameState::GameOver)),
+                other => Err(format!("Unknown state {:?}", other)),
+            },
+            other => Err(format!("Unknown command {:?}", other)),
+        }
+    }
+}
+
+fn init_console(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(0.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        Visibility::Hidden,
+        ConsoleText,
+        UsesUiFont,
+    ));
+}
+
+fn read_console_input(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut key_events: EventReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+) {
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        console.open = !console.open;
+        keyboard_input.reset_all();
+        key_events.clear();
+        return;
+    }
+    if !console.open {
+        key_events.clear();
+        return;
+    }
+
+    keyboard_input.reset_all();
+
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                commands.queue(move |world: &mut World| run_command(world, &line));
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Escape => console.open = false,
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => console.input.push_str(text.as_str()),
+            _ => {}
+        }
+    }
+}
+
+// Commands touch all sorts of resources and entities, so they get the whole world
+fn run_command(world: &mut World, line: &str) {
+    let reply = match ConsoleCommand::parse(line) {
+        Ok(command) => apply_command(world, command),
+        Err(err) => err,
+    };
+    println!("{}{}: {}", PROMPT, line, reply);
+    world.resource_mut::<Console>().output = reply;
+}
+
+fn apply_command(world: &mut World, command: ConsoleCommand) -> String {
+    match command {
+        ConsoleCommand::Grow(segments) => {
+            world.resource_mut::<PendingGrowth>().0 += segments;
+            format!("Growing by {} segment(s)", segments)
+        }
+        ConsoleCommand::Food(x, y) => {
+            let position = Position { x, y };
+            let grid_origin = *world.resource::<GridOrigin>();
+            let mut food_query =
+                world.query_filtered::<(&mut Position, &mut Transform), With<Food>>();
+            let Ok((mut food_pos, mut food_transform)) = food_query.single_mut(world) else {
+                return "No food on the board".to_string();
+            };
+            *food_pos = position;
+            food_transform.translation = grid_to_screen_position(&position, grid_origin);
+            format!("Food moved to {}, {}", x, y)
+        }
+        ConsoleCommand::Speed(delay) => {
+            world.resource_mut::<TurnDelay>().0 = delay;
+            format!("Turn delay set to {}s", delay)
+        }
+        ConsoleCommand::Goto(state) => {
+            let reply = format!("Going to {:?}", state);
+            world.resource_mut::<NextState<GameState>>().set(state);
+            reply
+        }
+    }
+}
+
+// Hands out the pending segments one at a time through the head's Ate flag,
+// which the next tick turns into a new tail segment
+fn feed_pending_growth(
+    mut pending_growth: ResMut<PendingGrowth>,
+    mut head_query: Query<&mut Ate, With<Head>>,
+) {
+    if pending_growth.0 == 0 {
+        return;
+    }
+    if let Ok(mut ate) = head_query.single_mut()
+        && !ate.0
+    {
+        ate.0 = true;
+        pending_growth.0 -= 1;
+    }
+}
+
+fn update_console_text(
+    console: Res<Console>,
+    mut query: Query<(&mut Text, &mut Visibility), With<ConsoleText>>,
+) {
+    for (mut text, mut visibility) in query.iter_mut() {
+        *visibility = if console.open {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        text.0 = if console.output.is_empty() {
+            format!("{}{}", PROMPT, console.input)
+        } else {
+            format!("{}\n{}{}", console.output, PROMPT, console.input)
+        };
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>();
+        app.init_resource::<InvertVertical>();
+        app.init_resource::<TurnMode>();
+        app.init_resource::<PlayerBindings>();
+        app.add_systems(
+            PreUpdate,
+            (
+                apply_control_layout.run_if(resource_changed::<Settings>),
+                warn_overlapping_bindings.run_if(resource_changed::<PlayerBindings>),
+            )
+                .chain(),
+        );
+    }
+}
+
+// Key clusters the snake can be steered with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlLayout {
+    #[default]
+    Wasd,
+    Arrows,
+    Ijkl,
+    Numpad,
+}
+
+impl ControlLayout {
+    const ALL: [ControlLayout; 4] = [
+        ControlLayout::Wasd,
+        ControlLayout::Arrows,
+        ControlLayout::Ijkl,
+        ControlLayout::Numpad,
+    ];
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL
+            .iter()
+            .position(|layout| *layout == self)
+            .unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    // Identifier used in the settings file
+    pub fn key(self) -> &'static str {
+        match self {
+            ControlLayout::Wasd => "wasd",
+            ControlLayout::Arrows => "arrows",
+            ControlLayout::Ijkl => "ijkl",
+            ControlLayout::Numpad => "numpad",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|layout| layout.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ControlLayout::Wasd => "WASD",
+            ControlLayout::Arrows => "Arrows",
+            ControlLayout::Ijkl => "IJKL",
+            ControlLayout::Numpad => "Numpad 8456",
+        }
+    }
+
+    pub fn bindings(self) -> KeyBindings {
+        match self {
+            ControlLayout::Wasd => DEFAULT_BINDINGS,
+            ControlLayout::Arrows => KeyBindings {
+                up: KeyCode::ArrowUp,
+                down: KeyCode::ArrowDown,
+                left: KeyCode::ArrowLeft,
+                right: KeyCode::ArrowRight,
+                ..DEFAULT_BINDINGS
+            },
+            ControlLayout::Ijkl => KeyBindings {
+                up: KeyCode::KeyI,
+                down: KeyCode::KeyK,
+                left: KeyCode::KeyJ,
+                right: KeyCode::KeyL,
+                ..DEFAULT_BINDINGS
+            },
+            ControlLayout::Numpad => KeyBindings {
+                up: KeyCode::Numpad8,
+                down: KeyCode::Numpad5,
+                left: KeyCode::Numpad4,
+                right: KeyCode::Numpad6,
+                ..DEFAULT_BINDINGS
+            },
+        }
+    }
+}
+
+// Keys currently used to steer the snake and control the game
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct KeyBindings {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub pause: KeyCode,
+    pub restart: KeyCode,
+    // Starts over right away during a run, skipping the game over screen
+    pub quick_restart: KeyCode,
+    // Leaves a peaceful run for the main menu
+    pub end_game: KeyCode,
+    // Back to the main menu from the game over screen
+    pub menu: KeyCode,
+}
+
+pub const DEFAULT_BINDINGS: KeyBindings = KeyBindings {
+    up: KeyCode::KeyW,
+    down: KeyCode::KeyS,
+    left: KeyCode::KeyA,
+    right: KeyCode::KeyD,
+    pause: KeyCode::Escape,
+    restart: KeyCode::KeyR,
+    quick_restart: KeyCode::Backspace,
+    end_game: KeyCode::KeyQ,
+    menu: KeyCode::KeyM,
+};
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        DEFAULT_BINDINGS
+    }
+}
+
+// Which player a snake head belongs to. Everything is single player for now,
+// the second player is there for the planned two-player mode.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlayerId {
+    One,
+    Two,
+}
+
+impl PlayerId {
+    const ALL: [PlayerId; 2] = [PlayerId::One, PlayerId::Two];
+}
+
+// Steering keys per player, so both can be rebound independently.
+// Player one follows the layout and keys picked in the settings.
+#[derive(Resource)]
+pub struct PlayerBindings(HashMap<PlayerId, KeyBindings>);
+
+impl Default for PlayerBindings {
+    fn default() -> Self {
+        Self(HashMap::from([
+            (PlayerId::One, ControlLayout::Wasd.bindings()),
+            (PlayerId::Two, ControlLayout::Arrows.bindings()),
+        ]))
+    }
+}
+
+impl PlayerBindings {
+    pub fn get(&self, player: PlayerId) -> &KeyBindings {
+        self.0.get(&player).unwrap_or(&DEFAULT_BINDINGS)
+    }
+
+    pub fn set(&mut self, player: PlayerId, key_bindings: KeyBindings) {
+        self.0.insert(player, key_bindings);
+    }
+
+    // Steering keys bound for more than one player
+    fn overlapping_keys(&self) -> Vec<KeyCode> {
+        let mut seen = Vec::new();
+        let mut overlapping = Vec::new();
+        for player in PlayerId::ALL {
+            for key in self.get(player).steering() {
+                if seen.contains(&key) && !overlapping.contains(&key) {
+                    overlapping.push(key);
+                }
+            }
+            seen.extend(self.get(player).steering());
+        }
+        overlapping
+    }
+}
+
+// Swaps the up and down keys for players who expect the up key to move the snake down
+#[derive(Resource, Default)]
+pub struct InvertVertical(pub bool);
+
+impl InvertVertical {
+    // Keys that steer the snake up and down, in that order
+    pub fn vertical_keys(&self, key_bindings: &KeyBindings) -> (KeyCode, KeyCode) {
+        if self.0 {
+            (key_bindings.down, key_bindings.up)
+        } else {
+            (key_bindings.up, key_bindings.down)
+        }
+    }
+}
+
+// How steering keys are read. Held keeps applying a pressed key every frame,
+// Tap reacts to each press once and queues presses that come within one tick.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TurnMode {
+    #[default]
+    Held,
+    Tap,
+}
+
+impl TurnMode {
+    pub fn next(self) -> Self {
+        match self {
+            TurnMode::Held => TurnMode::Tap,
+            TurnMode::Tap => TurnMode::Held,
+        }
+    }
+
+    // Identifier used in the settings file
+    pub fn key(self) -> &'static str {
+        match self {
+            TurnMode::Held => "held",
+            TurnMode::Tap => "tap",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [TurnMode::Held, TurnMode::Tap]
+            .into_iter()
+            .find(|mode| mode.key() == key)
+    }
+
+    pub fn is_active(self, keyboard_input: &ButtonInput<KeyCode>, key: KeyCode) -> bool {
+        match self {
+            TurnMode::Held => keyboard_input.pressed(key),
+            TurnMode::Tap => keyboard_input.just_pressed(key),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn steering(&self) -> [KeyCode; 4] {
+        [self.up, self.down, self.left, self.right]
+    }
+}
+
+// Keys that can be assigned to the action keys in the settings. Anything else is ignored
+// while rebinding, so every binding can be written to and read from the settings file.
+const REBINDABLE_KEYS: [KeyCode; 47] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Escape,
+    KeyCode::Space,
+    KeyCode::Backspace,
+    KeyCode::Tab,
+    KeyCode::Enter,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+];
+
+pub fn is_rebindable(key: KeyCode) -> bool {
+    REBINDABLE_KEYS.contains(&key)
+}
+
+// Identifier used in the settings file, e.g. `KeyR` or `Escape`
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    REBINDABLE_KEYS
+        .iter()
+        .copied()
+        .find(|key| key_name(*key) == name)
+}
+
+// Short name shown to the player, e.g. `R` instead of `KeyR`
+pub fn key_label(key: KeyCode) -> String {
+    let name = key_name(key);
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+fn apply_control_layout(
+    settings: Res<Settings>,
+    mut key_bindings: ResMut<KeyBindings>,
+    mut player_bindings: ResMut<PlayerBindings>,
+    mut invert_vertical: ResMut<InvertVertical>,
+    mut turn_mode: ResMut<TurnMode>,
+) {
+    *key_bindings = KeyBindings {
+        pause: settings.pause_key,
+        restart: settings.restart_key,
+        quick_restart: settings.quick_restart_key,
+        end_game: settings.end_game_key,
+        ..settings.control_layout.bindings()
+    };
+    player_bindings.set(PlayerId::One, *key_bindings);
+    invert_vertical.0 = settings.invert_vertical;
+    *turn_mode = settings.turn_mode;
+}
+
+fn warn_overlapping_bindings(player_bindings: Res<PlayerBindings>) {
+    let overlapping = player_bindings.overlapping_keys();
+    if !overlapping.is_empty() {
+        println!(
+            "Players share steering keys: {}",
+            overlapping
+                .iter()
+                .map(|key| key_name(*key))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use crate::{
+    controls::{InvertVertical, KeyBindings, key_label},
+    settings::Settings,
+    strings::{MessageId, Strings},
+    test_mode::TestMode,
+};
+
+pub struct ControlsOverlayPlugin;
+
+impl Plugin for ControlsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowControls>();
+        app.add_systems(Startup, (init_controls_overlay, show_on_first_launch));
+        app.add_systems(
+            Update,
+            (toggle_controls_overlay, update_controls_overlay).chain(),
+        );
+    }
+}
+
+// Works in every state, so it can be opened from the menu, in game or while paused
+#[derive(Resource, Default)]
+pub struct ShowControls(pub bool);
+
+#[derive(Component)]
+struct ControlsOverlayText;
+
+fn controls_text(
+    key_bindings: &KeyBindings,
+    invert_vertical: &InvertVertical,
+    strings: &Strings,
+) -> String {
+    let (up, down) = invert_vertical.vertical_keys(key_bindings);
+    strings.format(
+        MessageId::ControlsOverlay,
+        &[
+            ("up", &key_label(up)),
+            ("down", &key_label(down)),
+            ("left", &key_label(key_bindings.left)),
+            ("right", &key_label(key_bindings.right)),
+            ("pause", &key_label(key_bindings.pause)),
+            ("restart", &key_label(key_bindings.restart)),
+            ("quick_restart", &key_label(key_bindings.quick_restart)),
+        ],
+    )
+}
+
+fn init_controls_overlay(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        Visibility::Hidden,
+        // Above the menu, pause and game over texts
+        GlobalZIndex(10),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ControlsOverlayText,
+    ));
+}
+
+fn show_on_first_launch(
+    test_mode: Res<TestMode>,
+    mut settings: ResMut<Settings>,
+    mut show_controls: ResMut<ShowControls>,
+) {
+    if settings.seen_controls {
+        return;
+    }
+    show_controls.0 = true;
+    settings.seen_controls = true;
+    settings.store(&test_mode);
+}
+
+fn toggle_controls_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut show_controls: ResMut<ShowControls>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyH) {
+        show_controls.0 = !show_controls.0;
+    }
+}
+
+// Rebuilt from KeyBindings, so rebound keys show up right away
+fn update_controls_overlay(
+    show_controls: Res<ShowControls>,
+    key_bindings: Res<KeyBindings>,
+    invert_vertical: Res<InvertVertical>,
+    strings: Res<Strings>,
+    mut query: Query<(&mut Visibility, &mut Text), With<ControlsOverlayText>>,
+) {
+    if !show_controls.is_changed() && !key_bindings.is_changed() {
+        return;
+    }
+    if let Ok((mut visibility, mut text)) = query.single_mut() {
+        text.0 = controls_text(&key_bindings, &invert_vertical, &strings);
+        *visibility = if show_controls.0 {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, GameplaySet, Position,
+    lives::SnakeRespawnEvent,
+    settings::Settings,
+    snake::{BodySegment, Head, TurnDelay},
+};
+
+// Seconds of movement kept for the replay, at the starting speed
+const RECORDED_SECONDS: f32 = 1.0;
+// Seconds each recorded tick is shown while rewinding and while replaying
+const REWIND_STEP: f32 = 0.08;
+const SLOW_STEP: f32 = 0.4;
+
+pub struct DeathReplayPlugin;
+
+impl Plugin for DeathReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnakeHistory>();
+        app.init_resource::<DeathReplay>();
+        app.add_systems(OnEnter(GameState::StartGame), clear_history);
+        // Frames from before a lost life show a snake that no longer exists
+        app.add_systems(Update, clear_history.run_if(on_event::<SnakeRespawnEvent>));
+        app.add_systems(
+            Update,
+            record_snake_frame
+                .after(GameplaySet::Collision)
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(OnEnter(GameState::GameOver), start_death_replay);
+        app.add_systems(
+            Update,
+            play_death_replay.run_if(in_state(GameState::GameOver)),
+        );
+        app.add_systems(OnExit(GameState::GameOver), finish_death_replay);
+    }
+}
+
+type SnakePartFilter = Or<(With<Head>, With<BodySegment>)>;
+
+// How every part of the snake was drawn after one tick
+#[derive(Clone)]
+struct SnakeFrame(Vec<(Entity, Transform, Sprite)>);
+
+#[derive(Resource, Default)]
+struct SnakeHistory(VecDeque<SnakeFrame>);
+
+// Shows the last ticks before the crash again: a quick rewind, then forward in
+// slow motion. The snake is put back exactly as it died once it's done.
+#[derive(Resource, Default)]
+pub struct DeathReplay {
+    frames: Vec<SnakeFrame>,
+    // Frame index and how long to show it, in playback order
+    steps: VecDeque<(usize, f32)>,
+    remaining: f32,
+    final_frame: Option<SnakeFrame>,
+}
+
+impl DeathReplay {
+    pub fn is_playing(&self) -> bool {
+        self.final_frame.is_some()
+    }
+}
+
+fn snapshot(snake_query: &Query<(Entity, &Transform, &Sprite), SnakePartFilter>) -> SnakeFrame {
+    SnakeFrame(
+        snake_query
+            .iter()
+            .map(|(entity, transform, sprite)| (entity, *transform, sprite.clone()))
+            .collect(),
+    )
+}
+
+// Parts that didn't exist yet in the frame, e.g. segments grown later, are hidden
+fn apply_frame(
+    frame: &SnakeFrame,
+    snake_query: &mut Query<
+        (Entity, &mut Transform, &mut Sprite, &mut Visibility),
+        SnakePartFilter,
+    >,
+) {
+    for (entity, mut transform, mut sprite, mut visibility) in snake_query.iter_mut() {
+        match frame.0.iter().find(|(recorded, _, _)| *recorded == entity) {
+            Some((_, recorded_transform, recorded_sprite)) => {
+                *transform = *recorded_transform;
+                *sprite = recorded_sprite.clone();
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+// Frames covering `RECORDED_SECONDS` plus the crash itself, always at least
+// one to rewind to
+fn recorded_ticks(turn_delay: f32) -> usize {
+    (RECORDED_SECONDS / turn_delay).ceil() as usize + 1
+}
+
+fn clear_history(mut history: ResMut<SnakeHistory>) {
+    history.0.clear();
+}
+
+fn record_snake_frame(
+    head_query: Query<Ref<Position>, With<Head>>,
+    snake_query: Query<(Entity, &Transform, &Sprite), SnakePartFilter>,
+    turn_delay: Res<TurnDelay>,
+    mut history: ResMut<SnakeHistory>,
+) {
+    let Ok(head_pos) = head_query.single() else {
+        return;
+    };
+    if !head_pos.is_changed() {
+        return;
+    }
+
+    history.0.push_back(snapshot(&snake_query));
+    while history.0.len() > recorded_ticks(turn_delay.0) {
+        history.0.pop_front();
+    }
+}
+
+fn start_death_replay(
+    settings: Res<Settings>,
+    history: Res<SnakeHistory>,
+    snake_query: Query<(Entity, &Transform, &Sprite), SnakePartFilter>,
+    mut death_replay: ResMut<DeathReplay>,
+) {
+    // The last recorded frame is the crash itself, there is nothing before a
+    // single one to replay
+    if !settings.death_replay || history.0.len() < 2 {
+        return;
+    }
+
+    let last = history.0.len() - 1;
+    let rewind = (0..last).rev().map(|idx| (idx, REWIND_STEP));
+    let forward = (1..=last).map(|idx| (idx, SLOW_STEP));
+
+    *death_replay = DeathReplay {
+        frames: history.0.iter().cloned().collect(),
+        steps: rewind.chain(forward).collect(),
+        remaining: 0.0,
+        final_frame: Some(snapshot(&snake_query)),
+    };
+}
+
+fn play_death_replay(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut death_replay: ResMut<DeathReplay>,
+    mut snake_query: Query<(Entity, &mut Transform, &mut Sprite, &mut Visibility), SnakePartFilter>,
+) {
+    if !death_replay.is_playing() {
+        return;
+    }
+
+    // Any key skips straight to the overlay
+    if keyboard_input.get_just_pressed().next().is_some() {
+        death_replay.steps.clear();
+        death_replay.remaining = 0.0;
+    }
+
+    death_replay.remaining -= time.delta_secs();
+    if death_replay.remaining > 0.0 {
+        return;
+    }
+
+    match death_replay.steps.pop_front() {
+        Some((idx, duration)) => {
+            apply_frame(&death_replay.frames[idx], &mut snake_query);
+            death_replay.remaining = duration;
+        }
+        None => {
+            if let Some(final_frame) = death_replay.final_frame.take() {
+                apply_frame(&final_frame, &mut snake_query);
+            }
+            *death_replay = DeathReplay::default();
+        }
+    }
+}
+
+// Leaving the game over screen early still restores the true final state
+fn finish_death_replay(
+    mut death_replay: ResMut<DeathReplay>,
+    mut snake_query: Query<(Entity, &mut Transform, &mut Sprite, &mut Visibility), SnakePartFilter>,
+) {
+    if let Some(final_frame) = death_replay.final_frame.take() {
+        apply_frame(&final_frame, &mut snake_query);
+    }
+    *death_replay = DeathReplay::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_ticks_cover_the_last_second() {
+        // 0.8 s per tick: two ticks reach back past a second, plus the crash
+        assert_eq!(recorded_ticks(0.8), 3);
+        assert_eq!(recorded_ticks(0.25), 5);
+        assert_eq!(recorded_ticks(0.1), 11);
+        // Slower than a second per tick still leaves one tick to rewind to
+        assert_eq!(recorded_ticks(2.0), 2);
+    }
+}
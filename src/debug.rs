@@ -0,0 +1,218 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    Food, GridOrigin, Position, TILE_SIZE, assets_loader::UsesUiFont, grid_to_screen_position,
+};
+
+const FRAME_SAMPLES: usize = 60;
+const GRAPH_BAR_WIDTH: f32 = 2.0;
+const GRAPH_HEIGHT: f32 = 50.0;
+// Frame time (ms) that fills the whole graph height
+const GRAPH_MAX_FRAME_MS: f32 = 50.0;
+const DEFAULT_FPS_WARNING: f32 = 50.0;
+const DEFAULT_FPS_CRITICAL: f32 = 30.0;
+// Above the other sprites, so the label isn't hidden by the snake
+const FOOD_LABEL_Z: f32 = 5.0;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FpsSamples>();
+        app.init_resource::<FpsWarning>();
+        app.init_resource::<DebugOverlay>();
+        app.add_systems(Startup, (init_frame_graph, init_food_label));
+        app.add_systems(
+            Update,
+            (
+                record_frame_time,
+                toggle_debug_overlay,
+                update_frame_graph,
+                update_food_label,
+            )
+                .chain(),
+        );
+    }
+}
+
+// Ring buffer of the most recent frame times, in seconds
+#[derive(Resource, Default)]
+pub struct FpsSamples(pub VecDeque<f32>);
+
+impl FpsSamples {
+    // Frame rate over the whole buffer, smoother than a single frame's
+    pub fn average_fps(&self) -> Option<f32> {
+        let total: f32 = self.0.iter().sum();
+        (total > 0.0).then(|| self.0.len() as f32 / total)
+    }
+}
+
+// The FPS text turns yellow below `warning` and red below `critical`
+#[derive(Resource)]
+pub struct FpsWarning {
+    pub warning: f32,
+    pub critical: f32,
+}
+
+impl Default for FpsWarning {
+    fn default() -> Self {
+        Self {
+            warning: DEFAULT_FPS_WARNING,
+            critical: DEFAULT_FPS_CRITICAL,
+        }
+    }
+}
+
+impl FpsWarning {
+    pub fn color(&self, fps: f32) -> Color {
+        if fps < self.critical {
+            Color::srgb(1.0, 0.2, 0.2)
+        } else if fps < self.warning {
+            Color::srgb(1.0, 0.9, 0.2)
+        } else {
+            Color::WHITE
+        }
+    }
+}
+
+// Toggled with F3: the frame time graph and the food's cell
+#[derive(Resource, Default)]
+pub struct DebugOverlay(pub bool);
+
+#[derive(Component)]
+struct FrameGraph;
+
+#[derive(Component)]
+struct FrameGraphBar(usize);
+
+#[derive(Component)]
+struct FoodLabel;
+
+fn record_frame_time(time: Res<Time>, mut samples: ResMut<FpsSamples>) {
+    if samples.0.len() == FRAME_SAMPLES {
+        samples.0.pop_front();
+    }
+    samples.0.push_back(time.delta_secs());
+}
+
+fn toggle_debug_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut graph_query: Query<&mut Visibility, With<FrameGraph>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        overlay.0 = !overlay.0;
+        if let Ok(mut visibility) = graph_query.single_mut() {
+            *visibility = if overlay.0 {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+// The bars are spawned once and resized every frame
+fn init_frame_graph(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(8.0),
+                right: Val::Px(8.0),
+                height: Val::Px(GRAPH_HEIGHT),
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Visibility::Hidden,
+            FrameGraph,
+        ))
+        .with_children(|parent| {
+            for idx in 0..FRAME_SAMPLES {
+                parent.spawn((
+                    Node {
+                        width: Val::Px(GRAPH_BAR_WIDTH),
+                        height: Val::Px(0.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.0, 1.0, 0.0)),
+                    FrameGraphBar(idx),
+                ));
+            }
+        });
+}
+
+fn update_frame_graph(
+    overlay: Res<DebugOverlay>,
+    samples: Res<FpsSamples>,
+    mut bar_query: Query<(&FrameGraphBar, &mut Node, &mut BackgroundColor)>,
+) {
+    if !overlay.0 {
+        return;
+    }
+
+    // Right-align the samples so the newest frame is always the rightmost bar
+    let offset = FRAME_SAMPLES - samples.0.len();
+    for (bar, mut node, mut color) in bar_query.iter_mut() {
+        let frame_ms = bar
+            .0
+            .checked_sub(offset)
+            .and_then(|idx| samples.0.get(idx))
+            .map_or(0.0, |frame_time| frame_time * 1000.0);
+
+        node.height = Val::Px((frame_ms / GRAPH_MAX_FRAME_MS).min(1.0) * GRAPH_HEIGHT);
+        color.0 = if frame_ms > 33.3 {
+            Color::srgb(1.0, 0.0, 0.0)
+        } else if frame_ms > 16.7 {
+            Color::srgb(1.0, 1.0, 0.0)
+        } else {
+            Color::srgb(0.0, 1.0, 0.0)
+        };
+    }
+}
+
+fn init_food_label(mut commands: Commands) {
+    commands.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 5.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 0.4)),
+        Transform::from_xyz(0.0, 0.0, FOOD_LABEL_Z),
+        Visibility::Hidden,
+        FoodLabel,
+        UsesUiFont,
+    ));
+}
+
+// Only touches the label when the food moves to another cell or the overlay
+// is toggled, the bobbing animation leaves the food's Position alone
+fn update_food_label(
+    overlay: Res<DebugOverlay>,
+    food_query: Query<Ref<Position>, With<Food>>,
+    grid_origin: Res<GridOrigin>,
+    mut label_query: Query<(&mut Text2d, &mut Transform, &mut Visibility), With<FoodLabel>>,
+) {
+    let food_pos = food_query.single().ok();
+    let food_moved = food_pos.as_ref().is_some_and(|pos| pos.is_changed());
+    if !overlay.is_changed() && !food_moved {
+        return;
+    }
+    let Ok((mut text, mut transform, mut visibility)) = label_query.single_mut() else {
+        return;
+    };
+
+    let Some(food_pos) = food_pos.filter(|_| overlay.0) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    text.0 = format!("{}, {}", food_pos.x, food_pos.y);
+    transform.translation = (grid_to_screen_position(&food_pos, *grid_origin)
+        + Vec3::Y * TILE_SIZE)
+        .with_z(FOOD_LABEL_Z);
+    *visibility = Visibility::Visible;
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate::{BorderSegment, GameOverEvent, GameState};
+
+const BORDER_FLASH_DURATION: f32 = 0.6;
+const BORDER_FLASH_BLINK: f32 = 0.1;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BorderFlash>();
+        app.add_systems(OnEnter(GameState::StartGame), stop_border_flash);
+        // Started in PostUpdate so the flash is already running when the
+        // game-over overlay checks it on the next frame
+        app.add_systems(PostUpdate, start_border_flash);
+        app.add_systems(Update, update_border_flash);
+    }
+}
+
+// Blinks the border red after the snake crashed into it
+#[derive(Resource, Default)]
+pub struct BorderFlash(Option<Timer>);
+
+impl BorderFlash {
+    pub fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+fn start_border_flash(
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut border_flash: ResMut<BorderFlash>,
+) {
+    if game_over_events
+        .read()
+        .any(|event| *event == GameOverEvent::BorderCollision)
+    {
+        border_flash.0 = Some(Timer::from_seconds(BORDER_FLASH_DURATION, TimerMode::Once));
+    }
+}
+
+fn update_border_flash(
+    time: Res<Time>,
+    mut border_flash: ResMut<BorderFlash>,
+    mut border_query: Query<&mut Sprite, With<BorderSegment>>,
+) {
+    let Some(timer) = border_flash.0.as_mut() else {
+        return;
+    };
+
+    timer.tick(time.delta());
+
+    let color = if timer.finished() {
+        border_flash.0 = None;
+        Color::WHITE
+    } else if ((timer.elapsed_secs() / BORDER_FLASH_BLINK) as u32).is_multiple_of(2) {
+        Color::srgb(1.0, 0.2, 0.2)
+    } else {
+        Color::WHITE
+    };
+
+    for mut sprite in border_query.iter_mut() {
+        sprite.color = color;
+    }
+}
+
+fn stop_border_flash(
+    mut border_flash: ResMut<BorderFlash>,
+    mut border_query: Query<&mut Sprite, With<BorderSegment>>,
+) {
+    if border_flash.0.take().is_some() {
+        for mut sprite in border_query.iter_mut() {
+            sprite.color = Color::WHITE;
+        }
+    }
+}
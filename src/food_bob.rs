@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::{Food, GameplaySet, GridOrigin, Position, grid_to_screen_position, settings::Settings};
+
+const DEFAULT_AMPLITUDE: f32 = 1.0;
+const DEFAULT_SPEED: f32 = 3.0;
+const DEFAULT_ROTATION_SPEED: f32 = 0.0;
+
+pub struct FoodBobPlugin;
+
+impl Plugin for FoodBobPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FoodBob>();
+        app.add_systems(
+            Update,
+            (
+                apply_food_bob_setting.run_if(resource_changed::<Settings>),
+                bob_food.after(GameplaySet::Collision),
+            )
+                .chain(),
+        );
+    }
+}
+
+// Gentle idle animation that makes the food easier to notice
+#[derive(Resource)]
+pub struct FoodBob {
+    pub enabled: bool,
+    // Height of the bob in world units, a tile is 8
+    pub amplitude: f32,
+    // Bobs per second times 2π, like the argument of a sine
+    pub speed: f32,
+    // Radians per second, 0 keeps the food upright
+    pub rotation_speed: f32,
+}
+
+impl Default for FoodBob {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            amplitude: DEFAULT_AMPLITUDE,
+            speed: DEFAULT_SPEED,
+            rotation_speed: DEFAULT_ROTATION_SPEED,
+        }
+    }
+}
+
+fn apply_food_bob_setting(settings: Res<Settings>, mut food_bob: ResMut<FoodBob>) {
+    if food_bob.enabled != settings.food_bob {
+        food_bob.enabled = settings.food_bob;
+    }
+}
+
+// The translation is rebuilt from the grid position every frame, so the bob
+// follows the food wherever it's moved to. Collisions only ever look at the
+// Position, never at the drawn offset.
+fn bob_food(
+    time: Res<Time>,
+    food_bob: Res<FoodBob>,
+    grid_origin: Res<GridOrigin>,
+    mut food_query: Query<(&Position, &mut Transform), With<Food>>,
+) {
+    if !food_bob.enabled && !food_bob.is_changed() {
+        return;
+    }
+
+    let elapsed = time.elapsed_secs();
+    let (offset, angle) = if food_bob.enabled {
+        (
+            food_bob.amplitude * (elapsed * food_bob.speed).sin(),
+            (elapsed * food_bob.rotation_speed) % std::f32::consts::TAU,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    for (position, mut transform) in food_query.iter_mut() {
+        transform.translation = grid_to_screen_position(position, *grid_origin) + Vec3::Y * offset;
+        transform.rotation = Quat::from_rotation_z(angle);
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::{
+    Food, FoodKind, FoodSpawnRegion, FoodValues, GameMode, GameState, GameplaySet, GridConfig,
+    GridOrigin, Position, RunStats, Score, SnakeAteEvent,
+    assets_loader::{FOOD_INDEX, SnakeSkin, UsesUiFont},
+    grid_to_screen_transform,
+    obstacles::Obstacle,
+    portals::Portal,
+    random_free_cell,
+    snake::{Ate, BodySegment, Head, SnakeErrors},
+    test_mode::TestMode,
+};
+
+const DEFAULT_CHAIN_LENGTH: u8 = 3;
+const DEFAULT_CHAIN_BONUS: usize = 10;
+// The item to eat next stands out, the rest are dimmed
+const TARGET_COLOR: Color = Color::WHITE;
+const WAITING_COLOR: Color = Color::srgba(0.6, 0.6, 0.6, 0.6);
+const TARGET_NUMBER_COLOR: Color = Color::srgb(1.0, 0.9, 0.2);
+const WAITING_NUMBER_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+
+pub struct FoodChainPlugin;
+
+impl Plugin for FoodChainPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FoodChainConfig>();
+        app.init_resource::<FoodChain>();
+        app.add_systems(OnEnter(GameState::StartGame), reset_food_chain);
+        app.add_systems(
+            Update,
+            (
+                check_chain_collision,
+                spawn_food_chain,
+                highlight_chain_target.run_if(resource_changed::<FoodChain>),
+            )
+                .chain()
+                .in_set(GameplaySet::Collision)
+                .run_if(in_state(GameState::InGame))
+                .run_if(|config: Res<FoodChainConfig>| config.enabled),
+        );
+    }
+}
+
+// Numbered food that has to be eaten in order, off unless enabled in config.ron
+#[derive(Resource)]
+pub struct FoodChainConfig {
+    pub enabled: bool,
+    // Items in one chain
+    pub length: u8,
+    // Points for finishing a chain, on top of the points for each item
+    pub bonus: usize,
+}
+
+impl Default for FoodChainConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            length: DEFAULT_CHAIN_LENGTH,
+            bonus: DEFAULT_CHAIN_BONUS,
+        }
+    }
+}
+
+// Progress through the chain on the board, `current` items were eaten in order so far
+#[derive(Resource, Default)]
+pub struct FoodChain {
+    pub current: u8,
+    pub total: u8,
+}
+
+impl FoodChain {
+    fn target(&self) -> u8 {
+        self.current + 1
+    }
+}
+
+// One numbered item of the chain, counting from 1
+#[derive(Component)]
+pub struct ChainFood(u8);
+
+#[derive(Component)]
+struct ChainNumber;
+
+fn reset_food_chain(
+    mut commands: Commands,
+    mut food_chain: ResMut<FoodChain>,
+    chain_query: Query<Entity, With<ChainFood>>,
+) {
+    for entity in chain_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    *food_chain = FoodChain::default();
+}
+
+// Eating the target moves the chain on, anything else breaks it and a new
+// chain is laid out. Every item counts as food either way.
+fn check_chain_collision(
+    mut commands: Commands,
+    config: Res<FoodChainConfig>,
+    food_values: Res<FoodValues>,
+    mut food_chain: ResMut<FoodChain>,
+    mut score: ResMut<Score>,
+    mut run_stats: ResMut<RunStats>,
+    mut head_query: Query<(&Position, &mut Ate), With<Head>>,
+    chain_query: Query<(Entity, &ChainFood, &Position), Without<Head>>,
+    mut ate_events: EventWriter<SnakeAteEvent>,
+    mut snake_errors: ResMut<SnakeErrors>,
+) {
+    let (head_pos, mut snake_ate) = match head_query.single_mut() {
+        Ok(head) => head,
+        Err(err) => {
+            snake_errors.report("check_chain_collision", err);
+            return;
+        }
+    };
+
+    let Some((entity, item, _)) = chain_query
+        .iter()
+        .find(|(_, _, pos)| pos.x == head_pos.x && pos.y == head_pos.y)
+    else {
+        return;
+    };
+
+    snake_ate.0 = true;
+    score.0 += food_values.points(FoodKind::Normal);
+    ate_events.write(SnakeAteEvent);
+    run_stats.foods_eaten += 1;
+    commands.entity(entity).despawn();
+
+    if item.0 != food_chain.target() {
+        debug!(
+            "Food chain broken at {}/{}",
+            food_chain.current, food_chain.total
+        );
+        for (entity, _, _) in chain_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        food_chain.current = 0;
+        return;
+    }
+
+    food_chain.current += 1;
+    if food_chain.current == food_chain.total {
+        score.0 += config.bonus;
+        info!("Food chain complete, bonus: {}", config.bonus);
+        food_chain.current = 0;
+    }
+}
+
+// Lays out a new chain whenever the board has none, at the start of a run and
+// after a chain was finished or broken
+fn spawn_food_chain(
+    mut commands: Commands,
+    config: Res<FoodChainConfig>,
+    snake_skin: Res<SnakeSkin>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    spawn_region: Res<FoodSpawnRegion>,
+    mut food_chain: ResMut<FoodChain>,
+    chain_query: Query<&ChainFood>,
+    taken_query: Query<
+        &Position,
+        Or<(
+            With<Head>,
+            With<BodySegment>,
+            With<Food>,
+            With<Obstacle>,
+            With<Portal>,
+        )>,
+    >,
+    mut test_mode: ResMut<TestMode>,
+) {
+    if !chain_query.is_empty() {
+        return;
+    }
+
+    let mut occupied: HashSet<(i8, i8)> = taken_query.iter().map(|pos| (pos.x, pos.y)).collect();
+    let bounds = spawn_region.bounds(*game_mode, &grid);
+
+    food_chain.current = 0;
+    food_chain.total = 0;
+    for number in 1..=config.length {
+        let Some((x, y)) = random_free_cell(&mut test_mode, bounds, &occupied) else {
+            debug!("No free cell left for the food chain");
+            break;
+        };
+        occupied.insert((x, y));
+        food_chain.total = number;
+
+        let position = Position { x, y };
+        let mut sprite = snake_skin.sprite(FOOD_INDEX);
+        sprite.color = WAITING_COLOR;
+        commands
+            .spawn((
+                ChainFood(number),
+                sprite,
+                position,
+                grid_to_screen_transform(&position, *grid_origin),
+            ))
+            .with_child((
+                Text2d::new(number.to_string()),
+                TextFont {
+                    font_size: 6.0,
+                    ..default()
+                },
+                TextColor(WAITING_NUMBER_COLOR),
+                Transform::from_xyz(0.0, 0.0, 0.5),
+                ChainNumber,
+                UsesUiFont,
+            ));
+    }
+}
+
+fn highlight_chain_target(
+    food_chain: Res<FoodChain>,
+    mut chain_query: Query<(&ChainFood, &mut Sprite, &Children)>,
+    mut number_query: Query<&mut TextColor, With<ChainNumber>>,
+) {
+    for (item, mut sprite, children) in chain_query.iter_mut() {
+        let is_target = item.0 == food_chain.target();
+        sprite.color = if is_target {
+            TARGET_COLOR
+        } else {
+            WAITING_COLOR
+        };
+
+        for child in children.iter() {
+            if let Ok(mut color) = number_query.get_mut(child) {
+                color.0 = if is_target {
+                    TARGET_NUMBER_COLOR
+                } else {
+                    WAITING_NUMBER_COLOR
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    // A head on the origin and chain items on the given cells, `current`
+    // items of a three item chain eaten so far
+    fn chain_world(current: u8, items: &[(u8, Position)]) -> World {
+        let mut world = World::new();
+        world.init_resource::<FoodChainConfig>();
+        world.init_resource::<FoodValues>();
+        world.insert_resource(FoodChain { current, total: 3 });
+        world.insert_resource(Score(0));
+        world.init_resource::<RunStats>();
+        world.init_resource::<SnakeErrors>();
+        world.init_resource::<Events<SnakeAteEvent>>();
+        world.spawn((Head, Position { x: 0, y: 0 }, Ate(false)));
+        for (number, position) in items {
+            world.spawn((ChainFood(*number), *position));
+        }
+        world
+    }
+
+    fn chain_items(world: &mut World) -> usize {
+        world.query::<&ChainFood>().iter(world).count()
+    }
+
+    #[test]
+    fn finishing_the_chain_pays_the_bonus() {
+        let mut world = chain_world(2, &[(3, Position { x: 0, y: 0 })]);
+        world.run_system_once(check_chain_collision).unwrap();
+
+        assert_eq!(world.resource::<Score>().0, 1 + DEFAULT_CHAIN_BONUS);
+        assert_eq!(world.resource::<FoodChain>().current, 0);
+        assert_eq!(world.resource::<RunStats>().foods_eaten, 1);
+        assert_eq!(chain_items(&mut world), 0);
+    }
+
+    #[test]
+    fn eating_out_of_order_breaks_the_chain() {
+        let mut world = chain_world(
+            0,
+            &[
+                (1, Position { x: 3, y: 3 }),
+                (2, Position { x: 0, y: 0 }),
+                (3, Position { x: -3, y: 3 }),
+            ],
+        );
+        world.run_system_once(check_chain_collision).unwrap();
+
+        // The item still counts as food, just without the chain
+        assert_eq!(world.resource::<Score>().0, 1);
+        assert_eq!(world.resource::<FoodChain>().current, 0);
+        assert_eq!(chain_items(&mut world), 0);
+    }
+}
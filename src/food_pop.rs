@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::{Food, GameplaySet, Position};
+
+const DEFAULT_POP_DURATION: f32 = 0.2;
+
+pub struct FoodPopPlugin;
+
+impl Plugin for FoodPopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FoodPopConfig>();
+        app.add_systems(
+            Update,
+            (start_food_pop, animate_food_pop)
+                .chain()
+                .after(GameplaySet::Collision),
+        );
+    }
+}
+
+// Food scales up from nothing wherever it (re)appears
+#[derive(Resource)]
+pub struct FoodPopConfig {
+    pub enabled: bool,
+    // Seconds the food takes to reach full size
+    pub duration: f32,
+}
+
+impl Default for FoodPopConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duration: DEFAULT_POP_DURATION,
+        }
+    }
+}
+
+// Seconds since the food appeared. Only the scale is animated, the food's
+// Position can be eaten right away.
+#[derive(Component)]
+struct FoodSpawnAnim(f32);
+
+// The food entity is reused, so a new cell counts as a new spawn
+fn start_food_pop(
+    mut commands: Commands,
+    pop_config: Res<FoodPopConfig>,
+    mut food_query: Query<(Entity, &mut Transform), (With<Food>, Changed<Position>)>,
+) {
+    if !pop_config.enabled || pop_config.duration <= 0.0 {
+        return;
+    }
+    for (entity, mut transform) in food_query.iter_mut() {
+        transform.scale = Vec3::ZERO;
+        commands.entity(entity).insert(FoodSpawnAnim(0.0));
+    }
+}
+
+fn animate_food_pop(
+    mut commands: Commands,
+    time: Res<Time>,
+    pop_config: Res<FoodPopConfig>,
+    mut query: Query<(Entity, &mut FoodSpawnAnim, &mut Transform)>,
+) {
+    for (entity, mut pop_anim, mut transform) in query.iter_mut() {
+        pop_anim.0 += time.delta_secs();
+
+        let progress = if pop_config.enabled && pop_config.duration > 0.0 {
+            (pop_anim.0 / pop_config.duration).min(1.0)
+        } else {
+            1.0
+        };
+        transform.scale = Vec3::new(progress, progress, 1.0);
+
+        if progress >= 1.0 {
+            commands.entity(entity).remove::<FoodSpawnAnim>();
+        }
+    }
+}
@@ -0,0 +1,1075 @@
+use std::{fs, path::Path, time::SystemTime};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use ron::extensions::Extensions;
+use serde::Deserialize;
+
+use crate::{
+    BorderThickness, FoodKind, FoodSpawnRegion, FoodValues, GridConfig, GridOrigin, Position,
+    TILE_SIZE,
+    assets_loader::DEFAULT_UI_FONT,
+    camera::{CameraFollow, CameraZoom, StableCamera, view_scale},
+    controls::{ControlLayout, is_rebindable, key_from_name},
+    debug::FpsWarning,
+    food_bob::FoodBob,
+    food_chain::FoodChainConfig,
+    food_pop::FoodPopConfig,
+    game_over::DeathDelayConfig,
+    lives::LivesConfig,
+    menu_nav::KeyRepeat,
+    music::MusicConfig,
+    obstacles::ObstacleConfig,
+    pause::{AfkPause, AutoPauseOnBlur},
+    settings::Settings,
+    snake::{
+        AttractConfig, BodyGradient, BoostConfig, DashConfig, GrowConfig, MaxLength, NeckGrace,
+        RampConfig, ReversalPolicy, RewindConfig, ShowTrail, SwipeConfig, TimingMode, TurnDelay,
+        WaitForFirstInput,
+    },
+    survival::SurvivalConfig,
+    test_mode::{self, TestMode},
+    timed_mode::TimedModeConfig,
+};
+
+// All gameplay tunables in one place. Every field is optional, see config.example.ron.
+const CONFIG_PATH: &str = "config.ron";
+const MIN_GRID_SIZE: i8 = 8;
+// Smallest playfield thick walls may leave, room for the starting snake
+const MIN_PLAYFIELD_SIZE: i8 = 4;
+// Chain numbers are a single digit so they fit on a tile
+const MAX_FOOD_CHAIN_LENGTH: u8 = 9;
+const MAX_GRID_SIZE: i8 = 100;
+// Head plus one segment, what every snake starts with
+const MIN_MAX_LENGTH: usize = 2;
+// How often the file's modification time is checked, in seconds
+const WATCH_INTERVAL: f32 = 1.0;
+
+pub struct GameConfigPlugin;
+
+impl Plugin for GameConfigPlugin {
+    fn build(&self, app: &mut App) {
+        let user_files = test_mode::uses_user_files(app);
+        let config = if user_files {
+            GameConfig::read(CONFIG_PATH).unwrap_or_else(|err| {
+                // Running without a config file is the normal case
+                if Path::new(CONFIG_PATH).exists() {
+                    warn!("{}, using default config", err);
+                } else {
+                    debug!("{}, using default config", err);
+                }
+                GameConfig::default()
+            })
+        } else {
+            GameConfig::default()
+        };
+        // The board is built once at startup, so its size isn't hot-reloaded
+        app.insert_resource(GridConfig::walled(
+            config.grid_width,
+            config.grid_height,
+            config.border_thickness,
+        ));
+        app.insert_resource(BorderThickness(config.border_thickness));
+        app.insert_resource(config);
+        app.insert_resource(ConfigWatcher {
+            modified: modified_time(CONFIG_PATH),
+            timer: WATCH_INTERVAL,
+        });
+        if user_files {
+            app.add_systems(Update, (reload_on_hotkey, watch_config_file));
+        }
+        app.add_systems(
+            PreUpdate,
+            apply_game_config.run_if(resource_changed::<GameConfig>),
+        );
+    }
+}
+
+// Config as written in the file, missing fields fall back to the defaults
+#[derive(Deserialize, Default)]
+struct RawGameConfig {
+    grid_width: Option<i8>,
+    border_thickness: Option<u8>,
+    grid_height: Option<i8>,
+    timed_duration: Option<f32>,
+    turn_delay: Option<f32>,
+    control_layout: Option<String>,
+    pause_key: Option<String>,
+    restart_key: Option<String>,
+    quick_restart_key: Option<String>,
+    dash_enabled: Option<bool>,
+    dash_factor: Option<f32>,
+    dash_drain_rate: Option<f32>,
+    dash_regen_rate: Option<f32>,
+    boost_enabled: Option<bool>,
+    boost_factor: Option<f32>,
+    boost_ticks_per_segment: Option<u32>,
+    survival_interval: Option<f32>,
+    survival_points: Option<usize>,
+    points_per_obstacle: Option<usize>,
+    max_obstacles: Option<usize>,
+    food_points: Option<usize>,
+    swipe_min_distance: Option<f32>,
+    menu_repeat_delay: Option<f32>,
+    menu_repeat_interval: Option<f32>,
+    attract_idle_timeout: Option<f32>,
+    wait_for_first_input: Option<bool>,
+    reversal_policy: Option<ReversalPolicy>,
+    neck_grace: Option<bool>,
+    timing_mode: Option<TimingMode>,
+    auto_pause_on_blur: Option<bool>,
+    camera_smoothing: Option<f32>,
+    music_volume: Option<f32>,
+    show_trail: Option<bool>,
+    ramp_duration: Option<f32>,
+    ramp_start_delay: Option<f32>,
+    death_delay: Option<f32>,
+    grow_duration: Option<f32>,
+    ui_font: Option<String>,
+    lives: Option<u8>,
+    invulnerability: Option<f32>,
+    fps_warning: Option<f32>,
+    fps_critical: Option<f32>,
+    rewind_ticks: Option<usize>,
+    rewind_penalty: Option<usize>,
+    food_bob_amplitude: Option<f32>,
+    food_bob_speed: Option<f32>,
+    food_bob_rotation_speed: Option<f32>,
+    food_pop: Option<bool>,
+    food_pop_duration: Option<f32>,
+    food_region: Option<((i8, i8), (i8, i8))>,
+    afk_pause: Option<bool>,
+    afk_timeout: Option<f32>,
+    food_chain: Option<bool>,
+    food_chain_length: Option<u8>,
+    food_chain_bonus: Option<usize>,
+    max_length: Option<usize>,
+    body_gradient: Option<bool>,
+    body_gradient_head: Option<(f32, f32, f32)>,
+    body_gradient_tail: Option<(f32, f32, f32)>,
+    grid_origin: Option<(f32, f32)>,
+}
+
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct GameConfig {
+    pub grid_width: i8,
+    // Loaded once at startup, like the grid size
+    pub border_thickness: u8,
+    pub grid_height: i8,
+    pub timed_duration: f32,
+    pub turn_delay: f32,
+    // Player one's keys. Left out, they stay as picked in the settings menu.
+    pub control_layout: Option<ControlLayout>,
+    pub pause_key: Option<KeyCode>,
+    pub restart_key: Option<KeyCode>,
+    pub quick_restart_key: Option<KeyCode>,
+    pub dash_enabled: bool,
+    pub dash_factor: f32,
+    pub dash_drain_rate: f32,
+    pub dash_regen_rate: f32,
+    pub boost_enabled: bool,
+    pub boost_factor: f32,
+    pub boost_ticks_per_segment: u32,
+    pub survival_interval: f32,
+    pub survival_points: usize,
+    pub points_per_obstacle: usize,
+    pub max_obstacles: usize,
+    pub food_points: usize,
+    pub swipe_min_distance: f32,
+    pub menu_repeat_delay: f32,
+    pub menu_repeat_interval: f32,
+    pub attract_idle_timeout: f32,
+    pub wait_for_first_input: bool,
+    pub reversal_policy: ReversalPolicy,
+    pub neck_grace: bool,
+    pub timing_mode: TimingMode,
+    pub auto_pause_on_blur: bool,
+    pub camera_smoothing: f32,
+    pub music_volume: f32,
+    pub show_trail: bool,
+    pub ramp_duration: f32,
+    pub ramp_start_delay: f32,
+    pub death_delay: f32,
+    pub grow_duration: f32,
+    // Loaded once at startup, like the grid size
+    pub ui_font: String,
+    pub lives: u8,
+    pub invulnerability: f32,
+    pub fps_warning: f32,
+    pub fps_critical: f32,
+    pub rewind_ticks: usize,
+    pub rewind_penalty: usize,
+    pub food_bob_amplitude: f32,
+    pub food_bob_speed: f32,
+    pub food_bob_rotation_speed: f32,
+    pub food_pop: bool,
+    pub food_pop_duration: f32,
+    // Inclusive cell range, None for the whole playable area
+    pub food_region: Option<((i8, i8), (i8, i8))>,
+    pub afk_pause: bool,
+    pub afk_timeout: f32,
+    pub food_chain: bool,
+    pub food_chain_length: u8,
+    pub food_chain_bonus: usize,
+    pub max_length: Option<usize>,
+    pub body_gradient: bool,
+    pub body_gradient_head: (f32, f32, f32),
+    pub body_gradient_tail: (f32, f32, f32),
+    pub grid_origin: (f32, f32),
+}
+
+// Defaults come from the resources themselves so they are defined only once
+impl Default for GameConfig {
+    fn default() -> Self {
+        let dash = DashConfig::default();
+        let boost = BoostConfig::default();
+        let survival = SurvivalConfig::default();
+        let obstacles = ObstacleConfig::default();
+        let grid = GridConfig::default();
+        let ramp = RampConfig::default();
+        let rewind = RewindConfig::default();
+        let food_bob = FoodBob::default();
+        let food_pop = FoodPopConfig::default();
+        let food_chain = FoodChainConfig::default();
+        let gradient = BodyGradient::default();
+        let key_repeat = KeyRepeat::default();
+
+        Self {
+            grid_width: grid.width(),
+            border_thickness: BorderThickness::default().0,
+            grid_height: grid.height(),
+            timed_duration: TimedModeConfig::default().duration,
+            turn_delay: TurnDelay::default().0,
+            control_layout: None,
+            pause_key: None,
+            restart_key: None,
+            quick_restart_key: None,
+            dash_enabled: dash.enabled,
+            dash_factor: dash.factor,
+            dash_drain_rate: dash.drain_rate,
+            dash_regen_rate: dash.regen_rate,
+            boost_enabled: boost.enabled,
+            boost_factor: boost.factor,
+            boost_ticks_per_segment: boost.ticks_per_segment,
+            survival_interval: survival.interval,
+            survival_points: survival.points,
+            points_per_obstacle: obstacles.points_per_obstacle,
+            max_obstacles: obstacles.max_obstacles,
+            food_points: FoodValues::default().points(FoodKind::Normal),
+            swipe_min_distance: SwipeConfig::default().min_distance,
+            menu_repeat_delay: key_repeat.delay,
+            menu_repeat_interval: key_repeat.interval,
+            attract_idle_timeout: AttractConfig::default().idle_timeout,
+            wait_for_first_input: WaitForFirstInput::default().0,
+            reversal_policy: ReversalPolicy::default(),
+            neck_grace: NeckGrace::default().0,
+            timing_mode: TimingMode::default(),
+            auto_pause_on_blur: AutoPauseOnBlur::default().0,
+            camera_smoothing: CameraFollow::default().smoothing,
+            music_volume: MusicConfig::default().volume,
+            show_trail: ShowTrail::default().0,
+            ramp_duration: ramp.duration,
+            ramp_start_delay: ramp.start_delay,
+            death_delay: DeathDelayConfig::default().duration,
+            grow_duration: GrowConfig::default().duration,
+            ui_font: DEFAULT_UI_FONT.to_string(),
+            lives: LivesConfig::default().lives,
+            invulnerability: LivesConfig::default().invulnerability,
+            fps_warning: FpsWarning::default().warning,
+            fps_critical: FpsWarning::default().critical,
+            rewind_ticks: rewind.ticks,
+            rewind_penalty: rewind.penalty,
+            food_bob_amplitude: food_bob.amplitude,
+            food_bob_speed: food_bob.speed,
+            food_bob_rotation_speed: food_bob.rotation_speed,
+            food_pop: food_pop.enabled,
+            food_pop_duration: food_pop.duration,
+            food_region: FoodSpawnRegion::default().0,
+            afk_pause: AfkPause::default().enabled,
+            afk_timeout: AfkPause::default().timeout,
+            food_chain: food_chain.enabled,
+            food_chain_length: food_chain.length,
+            food_chain_bonus: food_chain.bonus,
+            max_length: MaxLength::default().0,
+            body_gradient: gradient.enabled,
+            body_gradient_head: gradient.head_color,
+            body_gradient_tail: gradient.tail_color,
+            grid_origin: GridOrigin::default().0.into(),
+        }
+    }
+}
+
+// Last seen modification time of the config file
+#[derive(Resource)]
+struct ConfigWatcher {
+    modified: Option<SystemTime>,
+    timer: f32,
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+// Takes the value from the file when it is set and valid. Invalid values are
+// warned about right away, the fields left out are listed once at the end.
+#[derive(Default)]
+struct FieldResolver {
+    defaulted: Vec<&'static str>,
+}
+
+impl FieldResolver {
+    fn resolve<T: Copy + std::fmt::Debug>(
+        &mut self,
+        name: &'static str,
+        value: Option<T>,
+        default: T,
+        is_valid: impl Fn(T) -> bool,
+    ) -> T {
+        match value {
+            Some(value) if is_valid(value) => value,
+            Some(value) => {
+                warn!(
+                    "Invalid {} in {}: {:?}, using default {:?}",
+                    name, CONFIG_PATH, value, default
+                );
+                default
+            }
+            None => {
+                self.defaulted.push(name);
+                default
+            }
+        }
+    }
+
+    fn log_defaulted(&self) {
+        if !self.defaulted.is_empty() {
+            debug!(
+                "{} fields not set in {}, using defaults: {}",
+                self.defaulted.len(),
+                CONFIG_PATH,
+                self.defaulted.join(", ")
+            );
+        }
+    }
+}
+
+fn any<T>(_: T) -> bool {
+    true
+}
+
+impl GameConfig {
+    fn read(path: &str) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path, err))?;
+        // Plain values instead of `Some(..)` for the optional fields
+        let raw: RawGameConfig = ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str(&contents)
+            .map_err(|err| format!("Could not parse {}: {}", path, err))?;
+        Ok(Self::resolve(raw))
+    }
+
+    fn resolve(raw: RawGameConfig) -> Self {
+        let default = Self::default();
+        let mut fields = FieldResolver::default();
+        let positive = |value: f32| value > 0.0;
+        let non_negative = |value: f32| value >= 0.0;
+        // RGB with every channel from 0 to 1
+        let color = |(r, g, b): (f32, f32, f32)| [r, g, b].iter().all(|c| (0.0..=1.0).contains(c));
+        // Room for the starting snake and food, and for the border inside the i8 grid
+        let grid_size = |size: i8| (MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&size);
+        let grid_width =
+            fields.resolve("grid_width", raw.grid_width, default.grid_width, grid_size);
+        let grid_height = fields.resolve(
+            "grid_height",
+            raw.grid_height,
+            default.grid_height,
+            grid_size,
+        );
+
+        // Thick walls must leave some room to play in
+        let border_thickness = fields.resolve(
+            "border_thickness",
+            raw.border_thickness,
+            default.border_thickness,
+            |thickness| {
+                let inset = 2 * (thickness as i16 - 1);
+                thickness >= 1
+                    && grid_width.min(grid_height) as i16 - inset >= MIN_PLAYFIELD_SIZE as i16
+            },
+        );
+        let playfield = GridConfig::walled(grid_width, grid_height, border_thickness);
+
+        let config = Self {
+            grid_width,
+            grid_height,
+            border_thickness,
+            timed_duration: fields.resolve(
+                "timed_duration",
+                raw.timed_duration,
+                default.timed_duration,
+                positive,
+            ),
+            turn_delay: fields.resolve("turn_delay", raw.turn_delay, default.turn_delay, positive),
+            control_layout: raw.control_layout.and_then(|layout| {
+                let resolved = ControlLayout::from_key(&layout);
+                if resolved.is_none() {
+                    warn!(
+                        "Invalid control_layout in {}: {:?}, keeping the layout from the settings",
+                        CONFIG_PATH, layout
+                    );
+                }
+                resolved
+            }),
+            pause_key: resolve_key("pause_key", raw.pause_key),
+            restart_key: resolve_key("restart_key", raw.restart_key),
+            quick_restart_key: resolve_key("quick_restart_key", raw.quick_restart_key),
+            dash_enabled: fields.resolve(
+                "dash_enabled",
+                raw.dash_enabled,
+                default.dash_enabled,
+                any,
+            ),
+            dash_factor: fields.resolve("dash_factor", raw.dash_factor, default.dash_factor, |f| {
+                f > 0.0 && f <= 1.0
+            }),
+            dash_drain_rate: fields.resolve(
+                "dash_drain_rate",
+                raw.dash_drain_rate,
+                default.dash_drain_rate,
+                non_negative,
+            ),
+            dash_regen_rate: fields.resolve(
+                "dash_regen_rate",
+                raw.dash_regen_rate,
+                default.dash_regen_rate,
+                non_negative,
+            ),
+            boost_enabled: fields.resolve(
+                "boost_enabled",
+                raw.boost_enabled,
+                default.boost_enabled,
+                any,
+            ),
+            // A factor of 1 or more would make boosting pointless
+            boost_factor: fields.resolve(
+                "boost_factor",
+                raw.boost_factor,
+                default.boost_factor,
+                |f| f > 0.0 && f < 1.0,
+            ),
+            boost_ticks_per_segment: fields.resolve(
+                "boost_ticks_per_segment",
+                raw.boost_ticks_per_segment,
+                default.boost_ticks_per_segment,
+                |ticks| ticks >= 1,
+            ),
+            survival_interval: fields.resolve(
+                "survival_interval",
+                raw.survival_interval,
+                default.survival_interval,
+                positive,
+            ),
+            survival_points: fields.resolve(
+                "survival_points",
+                raw.survival_points,
+                default.survival_points,
+                any,
+            ),
+            points_per_obstacle: fields.resolve(
+                "points_per_obstacle",
+                raw.points_per_obstacle,
+                default.points_per_obstacle,
+                any,
+            ),
+            max_obstacles: fields.resolve(
+                "max_obstacles",
+                raw.max_obstacles,
+                default.max_obstacles,
+                |count| count <= 64,
+            ),
+            food_points: fields.resolve("food_points", raw.food_points, default.food_points, any),
+            swipe_min_distance: fields.resolve(
+                "swipe_min_distance",
+                raw.swipe_min_distance,
+                default.swipe_min_distance,
+                non_negative,
+            ),
+            menu_repeat_delay: fields.resolve(
+                "menu_repeat_delay",
+                raw.menu_repeat_delay,
+                default.menu_repeat_delay,
+                non_negative,
+            ),
+            menu_repeat_interval: fields.resolve(
+                "menu_repeat_interval",
+                raw.menu_repeat_interval,
+                default.menu_repeat_interval,
+                positive,
+            ),
+            attract_idle_timeout: fields.resolve(
+                "attract_idle_timeout",
+                raw.attract_idle_timeout,
+                default.attract_idle_timeout,
+                non_negative,
+            ),
+            wait_for_first_input: fields.resolve(
+                "wait_for_first_input",
+                raw.wait_for_first_input,
+                default.wait_for_first_input,
+                any,
+            ),
+            reversal_policy: fields.resolve(
+                "reversal_policy",
+                raw.reversal_policy,
+                default.reversal_policy,
+                any,
+            ),
+            neck_grace: fields.resolve("neck_grace", raw.neck_grace, default.neck_grace, any),
+            timing_mode: fields.resolve("timing_mode", raw.timing_mode, default.timing_mode, any),
+            auto_pause_on_blur: fields.resolve(
+                "auto_pause_on_blur",
+                raw.auto_pause_on_blur,
+                default.auto_pause_on_blur,
+                any,
+            ),
+            camera_smoothing: fields.resolve(
+                "camera_smoothing",
+                raw.camera_smoothing,
+                default.camera_smoothing,
+                positive,
+            ),
+            // Linear, above 1 would play the track louder than it was mixed
+            music_volume: fields.resolve(
+                "music_volume",
+                raw.music_volume,
+                default.music_volume,
+                |v| (0.0..=1.0).contains(&v),
+            ),
+            show_trail: fields.resolve("show_trail", raw.show_trail, default.show_trail, any),
+            ramp_duration: fields.resolve(
+                "ramp_duration",
+                raw.ramp_duration,
+                default.ramp_duration,
+                non_negative,
+            ),
+            ramp_start_delay: fields.resolve(
+                "ramp_start_delay",
+                raw.ramp_start_delay,
+                default.ramp_start_delay,
+                positive,
+            ),
+            death_delay: fields.resolve(
+                "death_delay",
+                raw.death_delay,
+                default.death_delay,
+                non_negative,
+            ),
+            grow_duration: fields.resolve(
+                "grow_duration",
+                raw.grow_duration,
+                default.grow_duration,
+                non_negative,
+            ),
+            ui_font: raw.ui_font.unwrap_or_else(|| {
+                fields.defaulted.push("ui_font");
+                default.ui_font
+            }),
+            lives: fields.resolve("lives", raw.lives, default.lives, |lives| lives >= 1),
+            invulnerability: fields.resolve(
+                "invulnerability",
+                raw.invulnerability,
+                default.invulnerability,
+                non_negative,
+            ),
+            fps_warning: fields.resolve(
+                "fps_warning",
+                raw.fps_warning,
+                default.fps_warning,
+                non_negative,
+            ),
+            fps_critical: fields.resolve(
+                "fps_critical",
+                raw.fps_critical,
+                default.fps_critical,
+                non_negative,
+            ),
+            rewind_ticks: fields.resolve(
+                "rewind_ticks",
+                raw.rewind_ticks,
+                default.rewind_ticks,
+                |ticks| ticks >= 1,
+            ),
+            rewind_penalty: fields.resolve(
+                "rewind_penalty",
+                raw.rewind_penalty,
+                default.rewind_penalty,
+                any,
+            ),
+            food_bob_amplitude: fields.resolve(
+                "food_bob_amplitude",
+                raw.food_bob_amplitude,
+                default.food_bob_amplitude,
+                non_negative,
+            ),
+            food_bob_speed: fields.resolve(
+                "food_bob_speed",
+                raw.food_bob_speed,
+                default.food_bob_speed,
+                non_negative,
+            ),
+            food_bob_rotation_speed: fields.resolve(
+                "food_bob_rotation_speed",
+                raw.food_bob_rotation_speed,
+                default.food_bob_rotation_speed,
+                f32::is_finite,
+            ),
+            food_pop: fields.resolve("food_pop", raw.food_pop, default.food_pop, any),
+            food_pop_duration: fields.resolve(
+                "food_pop_duration",
+                raw.food_pop_duration,
+                default.food_pop_duration,
+                non_negative,
+            ),
+            food_region: resolve_food_region(raw.food_region, &playfield),
+            afk_pause: fields.resolve("afk_pause", raw.afk_pause, default.afk_pause, any),
+            afk_timeout: fields.resolve(
+                "afk_timeout",
+                raw.afk_timeout,
+                default.afk_timeout,
+                positive,
+            ),
+            food_chain: fields.resolve("food_chain", raw.food_chain, default.food_chain, any),
+            food_chain_length: fields.resolve(
+                "food_chain_length",
+                raw.food_chain_length,
+                default.food_chain_length,
+                |length| (2..=MAX_FOOD_CHAIN_LENGTH).contains(&length),
+            ),
+            food_chain_bonus: fields.resolve(
+                "food_chain_bonus",
+                raw.food_chain_bonus,
+                default.food_chain_bonus,
+                any,
+            ),
+            max_length: resolve_max_length(raw.max_length),
+            // Whether the board still fits the window is checked when a game starts
+            grid_origin: fields.resolve(
+                "grid_origin",
+                raw.grid_origin,
+                default.grid_origin,
+                |(x, y): (f32, f32)| x.is_finite() && y.is_finite(),
+            ),
+            body_gradient: fields.resolve(
+                "body_gradient",
+                raw.body_gradient,
+                default.body_gradient,
+                any,
+            ),
+            body_gradient_head: fields.resolve(
+                "body_gradient_head",
+                raw.body_gradient_head,
+                default.body_gradient_head,
+                color,
+            ),
+            body_gradient_tail: fields.resolve(
+                "body_gradient_tail",
+                raw.body_gradient_tail,
+                default.body_gradient_tail,
+                color,
+            ),
+        };
+        fields.log_defaulted();
+        config
+    }
+}
+
+// Unlike the other fields, leaving the region out is the normal case
+fn resolve_food_region(
+    region: Option<((i8, i8), (i8, i8))>,
+    grid: &GridConfig,
+) -> Option<((i8, i8), (i8, i8))> {
+    let (from, to) = region?;
+    let inside = |(x, y): (i8, i8)| grid.contains(&Position { x, y });
+    if from.0 > to.0 || from.1 > to.1 || !inside(from) || !inside(to) {
+        warn!(
+            "Invalid food_region in {}: {:?}, it must be a non-empty range inside the field {:?}..={:?}, using the whole field",
+            CONFIG_PATH, region, grid.from, grid.to
+        );
+        return None;
+    }
+    Some((from, to))
+}
+
+// Keys are named like in the settings file, e.g. `KeyR` or `Escape`. Leaving
+// them out keeps the keys picked in the settings menu.
+fn resolve_key(name: &str, key: Option<String>) -> Option<KeyCode> {
+    let key = key?;
+    let resolved = key_from_name(&key).filter(|key| is_rebindable(*key));
+    if resolved.is_none() {
+        warn!(
+            "Invalid {} in {}: {:?}, keeping the key from the settings",
+            name, CONFIG_PATH, key
+        );
+    }
+    resolved
+}
+
+// Like the food region, no cap is the normal case
+fn resolve_max_length(max_length: Option<usize>) -> Option<usize> {
+    let max = max_length?;
+    if max < MIN_MAX_LENGTH {
+        warn!(
+            "Invalid max_length in {}: {}, it must be at least {}, growing without limit",
+            CONFIG_PATH, max, MIN_MAX_LENGTH
+        );
+        return None;
+    }
+    Some(max)
+}
+
+// Checked as each game starts, right before the board is built, since the
+// window and the zoom can both change between runs. A board that doesn't fit
+// is centered instead.
+pub fn fit_grid_origin(
+    config: Res<GameConfig>,
+    grid: Res<GridConfig>,
+    border_thickness: Res<BorderThickness>,
+    (zoom, stable_camera): (Res<CameraZoom>, Res<StableCamera>),
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut grid_origin: ResMut<GridOrigin>,
+) {
+    let origin = Vec2::from(config.grid_origin);
+    // Headless runs have no window to fit the board to
+    let Ok(window) = window_query.single() else {
+        grid_origin.set_if_neq(GridOrigin(origin));
+        return;
+    };
+
+    let scale = view_scale(&zoom, &stable_camera);
+    // A centered board is the fallback anyway, so it isn't warned about
+    if origin != Vec2::ZERO
+        && !board_fits(
+            &grid,
+            *border_thickness,
+            origin,
+            window.size() * scale / 2.0,
+        )
+    {
+        warn!(
+            "Invalid grid_origin in {}: {:?}, the board doesn't fit a {}x{} window at zoom {}, using no offset",
+            CONFIG_PATH,
+            config.grid_origin,
+            window.width(),
+            window.height(),
+            scale
+        );
+        grid_origin.set_if_neq(GridOrigin::default());
+        return;
+    }
+    grid_origin.set_if_neq(GridOrigin(origin));
+}
+
+// Whether the board moved by `origin`, walls included, stays within
+// `half_view` of the middle of the window
+fn board_fits(
+    grid: &GridConfig,
+    thickness: BorderThickness,
+    origin: Vec2,
+    half_view: Vec2,
+) -> bool {
+    let ring = thickness.0 as i8;
+    // Outer edges of the outermost wall ring
+    let low = Vec2::new((grid.from.0 - ring) as f32, (grid.from.1 - ring) as f32) * TILE_SIZE
+        - TILE_SIZE / 2.0
+        + origin;
+    let high = Vec2::new((grid.to.0 + ring) as f32, (grid.to.1 + ring) as f32) * TILE_SIZE
+        + TILE_SIZE / 2.0
+        + origin;
+    low.cmpge(-half_view).all() && high.cmple(half_view).all()
+}
+
+// A broken file keeps the config that is already running. Only the tunables
+// change, so the score and the rest of the current run are left alone.
+fn reload_config(config: &mut ResMut<GameConfig>) {
+    match GameConfig::read(CONFIG_PATH) {
+        Ok(new_config) => {
+            info!("Reloaded {}", CONFIG_PATH);
+            config.set_if_neq(new_config);
+        }
+        Err(err) => warn!("{}, keeping the current config", err),
+    }
+}
+
+fn reload_on_hotkey(keyboard_input: Res<ButtonInput<KeyCode>>, mut config: ResMut<GameConfig>) {
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        reload_config(&mut config);
+    }
+}
+
+// Polls the modification time instead of using a file watcher, config.ron lives
+// outside of the assets folder
+fn watch_config_file(
+    time: Res<Time>,
+    mut watcher: ResMut<ConfigWatcher>,
+    mut config: ResMut<GameConfig>,
+) {
+    watcher.timer -= time.delta_secs();
+    if watcher.timer > 0.0 {
+        return;
+    }
+    watcher.timer = WATCH_INTERVAL;
+
+    let modified = modified_time(CONFIG_PATH);
+    if modified.is_some() && modified != watcher.modified {
+        watcher.modified = modified;
+        reload_config(&mut config);
+    }
+}
+
+fn apply_game_config(
+    config: Res<GameConfig>,
+    mut applied: Local<Option<GameConfig>>,
+    (mut timed_mode_config, mut turn_delay): (ResMut<TimedModeConfig>, ResMut<TurnDelay>),
+    (mut dash_config, mut boost_config): (ResMut<DashConfig>, ResMut<BoostConfig>),
+    (mut survival_config, mut obstacle_config): (ResMut<SurvivalConfig>, ResMut<ObstacleConfig>),
+    mut food_values: ResMut<FoodValues>,
+    (mut swipe_config, mut key_repeat): (ResMut<SwipeConfig>, ResMut<KeyRepeat>),
+    mut attract_config: ResMut<AttractConfig>,
+    (mut wait_for_first_input, mut reversal_policy, mut neck_grace, mut timing_mode): (
+        ResMut<WaitForFirstInput>,
+        ResMut<ReversalPolicy>,
+        ResMut<NeckGrace>,
+        ResMut<TimingMode>,
+    ),
+    (mut auto_pause, mut afk_pause): (ResMut<AutoPauseOnBlur>, ResMut<AfkPause>),
+    (mut camera_follow, mut music_config): (ResMut<CameraFollow>, ResMut<MusicConfig>),
+    (mut show_trail, mut body_gradient): (ResMut<ShowTrail>, ResMut<BodyGradient>),
+    (mut ramp_config, mut settings): (ResMut<RampConfig>, ResMut<Settings>),
+    // Grouped to stay within Bevy's system parameter limit
+    (mut death_delay_config, mut grow_config, mut max_length, mut food_region, mut food_chain): (
+        ResMut<DeathDelayConfig>,
+        ResMut<GrowConfig>,
+        ResMut<MaxLength>,
+        ResMut<FoodSpawnRegion>,
+        ResMut<FoodChainConfig>,
+    ),
+    (mut lives_config, mut fps_warning, mut rewind_config, mut food_bob, mut food_pop): (
+        ResMut<LivesConfig>,
+        ResMut<FpsWarning>,
+        ResMut<RewindConfig>,
+        ResMut<FoodBob>,
+        ResMut<FoodPopConfig>,
+    ),
+    test_mode: Res<TestMode>,
+) {
+    // Only what the file changed since the last load is applied, so values
+    // changed in game since then, in the menus or the console, are kept
+    let previous = applied.replace(config.clone());
+    let changes = ConfigChanges {
+        previous: previous.as_ref(),
+        config: &config,
+    };
+
+    changes.apply(&mut turn_delay.0, |c| c.turn_delay);
+    changes.apply(&mut timed_mode_config.duration, |c| c.timed_duration);
+    changes.apply(&mut dash_config.enabled, |c| c.dash_enabled);
+    changes.apply(&mut dash_config.factor, |c| c.dash_factor);
+    changes.apply(&mut dash_config.drain_rate, |c| c.dash_drain_rate);
+    changes.apply(&mut dash_config.regen_rate, |c| c.dash_regen_rate);
+    changes.apply(&mut boost_config.enabled, |c| c.boost_enabled);
+    changes.apply(&mut boost_config.factor, |c| c.boost_factor);
+    changes.apply(&mut boost_config.ticks_per_segment, |c| {
+        c.boost_ticks_per_segment
+    });
+    changes.apply(&mut survival_config.interval, |c| c.survival_interval);
+    changes.apply(&mut survival_config.points, |c| c.survival_points);
+    changes.apply(&mut obstacle_config.points_per_obstacle, |c| {
+        c.points_per_obstacle
+    });
+    changes.apply(&mut obstacle_config.max_obstacles, |c| c.max_obstacles);
+    if changes.changed(|c| c.food_points) {
+        food_values.0.insert(FoodKind::Normal, config.food_points);
+    }
+    changes.apply(&mut swipe_config.min_distance, |c| c.swipe_min_distance);
+    changes.apply(&mut key_repeat.delay, |c| c.menu_repeat_delay);
+    changes.apply(&mut key_repeat.interval, |c| c.menu_repeat_interval);
+    changes.apply(&mut attract_config.idle_timeout, |c| c.attract_idle_timeout);
+    changes.apply(&mut wait_for_first_input.0, |c| c.wait_for_first_input);
+    changes.apply(&mut *reversal_policy, |c| c.reversal_policy);
+    changes.apply(&mut neck_grace.0, |c| c.neck_grace);
+    changes.apply(&mut *timing_mode, |c| c.timing_mode);
+    changes.apply(&mut auto_pause.0, |c| c.auto_pause_on_blur);
+    changes.apply(&mut afk_pause.enabled, |c| c.afk_pause);
+    changes.apply(&mut afk_pause.timeout, |c| c.afk_timeout);
+    changes.apply(&mut camera_follow.smoothing, |c| c.camera_smoothing);
+    changes.apply(&mut music_config.volume, |c| c.music_volume);
+    changes.apply(&mut show_trail.0, |c| c.show_trail);
+    changes.apply(&mut ramp_config.duration, |c| c.ramp_duration);
+    changes.apply(&mut ramp_config.start_delay, |c| c.ramp_start_delay);
+    changes.apply(&mut death_delay_config.duration, |c| c.death_delay);
+    changes.apply(&mut grow_config.duration, |c| c.grow_duration);
+    changes.apply(&mut lives_config.lives, |c| c.lives);
+    changes.apply(&mut lives_config.invulnerability, |c| c.invulnerability);
+    changes.apply(&mut fps_warning.warning, |c| c.fps_warning);
+    changes.apply(&mut fps_warning.critical, |c| c.fps_critical);
+    changes.apply(&mut rewind_config.ticks, |c| c.rewind_ticks);
+    changes.apply(&mut rewind_config.penalty, |c| c.rewind_penalty);
+    changes.apply(&mut food_bob.amplitude, |c| c.food_bob_amplitude);
+    changes.apply(&mut food_bob.speed, |c| c.food_bob_speed);
+    changes.apply(&mut food_bob.rotation_speed, |c| c.food_bob_rotation_speed);
+    changes.apply(&mut food_pop.enabled, |c| c.food_pop);
+    changes.apply(&mut food_pop.duration, |c| c.food_pop_duration);
+    changes.apply(&mut food_chain.enabled, |c| c.food_chain);
+    changes.apply(&mut food_chain.length, |c| c.food_chain_length);
+    changes.apply(&mut food_chain.bonus, |c| c.food_chain_bonus);
+    changes.apply(&mut food_region.0, |c| c.food_region);
+    changes.apply(&mut body_gradient.enabled, |c| c.body_gradient);
+    changes.apply(&mut body_gradient.head_color, |c| c.body_gradient_head);
+    changes.apply(&mut body_gradient.tail_color, |c| c.body_gradient_tail);
+    changes.apply(&mut max_length.0, |c| c.max_length);
+    // Written to the settings so the menu shows them, checked first so an
+    // unchanged file doesn't touch the settings at all
+    if let Some(layout) = config.control_layout
+        && changes.changed(|c| c.control_layout)
+    {
+        settings.control_layout = layout;
+    }
+    if let Some(key) = config.pause_key
+        && changes.changed(|c| c.pause_key)
+    {
+        settings.pause_key = key;
+    }
+    if let Some(key) = config.restart_key
+        && changes.changed(|c| c.restart_key)
+    {
+        settings.restart_key = key;
+    }
+    if let Some(key) = config.quick_restart_key
+        && changes.changed(|c| c.quick_restart_key)
+    {
+        settings.quick_restart_key = key;
+    }
+
+    // Scripted runs check the board right away, without waiting on animations
+    if test_mode.enabled {
+        death_delay_config.duration = 0.0;
+        grow_config.duration = 0.0;
+    }
+}
+
+// The config being applied and the one applied before it, None on the first load
+struct ConfigChanges<'a> {
+    previous: Option<&'a GameConfig>,
+    config: &'a GameConfig,
+}
+
+impl ConfigChanges<'_> {
+    fn changed<T: PartialEq>(&self, field: impl Fn(&GameConfig) -> T) -> bool {
+        self.previous
+            .is_none_or(|previous| field(previous) != field(self.config))
+    }
+
+    // Overwrites `target` only when the file's value differs from the last load
+    fn apply<T: PartialEq>(&self, target: &mut T, field: impl Fn(&GameConfig) -> T) {
+        if self.changed(&field) {
+            *target = field(self.config);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn missing_fields_are_collected_for_one_summary() {
+        let mut fields = FieldResolver::default();
+        assert_eq!(fields.resolve("set", Some(2.0), 1.0, f32::is_finite), 2.0);
+        assert_eq!(fields.resolve("invalid", Some(-2.0), 1.0, |v| v > 0.0), 1.0);
+        assert_eq!(fields.resolve("missing", None, 1.0, f32::is_finite), 1.0);
+        assert_eq!(fields.defaulted, ["missing"]);
+    }
+
+    #[test]
+    fn empty_file_resolves_to_the_defaults() {
+        assert!(GameConfig::resolve(RawGameConfig::default()) == GameConfig::default());
+    }
+
+    #[test]
+    fn key_names_resolve_like_the_settings_file() {
+        assert_eq!(
+            resolve_key("pause_key", Some("KeyP".into())),
+            Some(KeyCode::KeyP)
+        );
+        assert_eq!(resolve_key("pause_key", Some("Nope".into())), None);
+        assert_eq!(resolve_key("pause_key", None), None);
+    }
+
+    #[test]
+    fn reload_applies_only_the_changed_fields() {
+        let previous = GameConfig::default();
+        let config = GameConfig {
+            turn_delay: 0.5,
+            ..GameConfig::default()
+        };
+        let changes = ConfigChanges {
+            previous: Some(&previous),
+            config: &config,
+        };
+
+        // Changed in game since the last load, the file still has the old value
+        let mut dash_factor = 0.9;
+        changes.apply(&mut dash_factor, |c| c.dash_factor);
+        assert_eq!(dash_factor, 0.9);
+
+        let mut turn_delay = previous.turn_delay;
+        changes.apply(&mut turn_delay, |c| c.turn_delay);
+        assert_eq!(turn_delay, 0.5);
+    }
+
+    #[test]
+    fn first_load_applies_every_field() {
+        let config = GameConfig::default();
+        let changes = ConfigChanges {
+            previous: None,
+            config: &config,
+        };
+        let mut dash_factor = 0.9;
+        changes.apply(&mut dash_factor, |c| c.dash_factor);
+        assert_eq!(dash_factor, config.dash_factor);
+    }
+    #[test]
+    fn grid_origin_has_to_fit_the_window_at_the_current_zoom() {
+        let mut world = World::new();
+        world.insert_resource(GameConfig {
+            grid_origin: (-20.0, 0.0),
+            ..GameConfig::default()
+        });
+        world.init_resource::<GridConfig>();
+        world.insert_resource(BorderThickness(1));
+        world.init_resource::<StableCamera>();
+        world.init_resource::<GridOrigin>();
+        world.spawn((
+            Window {
+                resolution: (800.0, 800.0).into(),
+                ..default()
+            },
+            PrimaryWindow,
+        ));
+
+        // A 14 tile wide board with its walls, 112 units, and 200 in view
+        world.insert_resource(CameraZoom(0.25));
+        world.run_system_once(fit_grid_origin).unwrap();
+        assert_eq!(
+            *world.resource::<GridOrigin>(),
+            GridOrigin(Vec2::new(-20.0, 0.0))
+        );
+
+        // Zoomed in to 120 units the centered board still fits, the moved one doesn't
+        world.insert_resource(CameraZoom(0.15));
+        world.run_system_once(fit_grid_origin).unwrap();
+        assert_eq!(*world.resource::<GridOrigin>(), GridOrigin::default());
+    }
+}
@@ -1,14 +1,59 @@
 use bevy::prelude::*;
 
-use crate::{GameState, Score};
+use crate::{
+    GameOverEvent, GameState, RunStats, Score,
+    assets_loader::{
+        GAME_OVER_BITE_INDEX, GAME_OVER_CLOCK_INDEX, GAME_OVER_WALL_INDEX, SnakeSkin, UsesUiFont,
+    },
+    challenge::RunSeed,
+    controls::{KeyBindings, key_label},
+    death_replay::DeathReplay,
+    effects::BorderFlash,
+    settings::Settings,
+    strings::{MessageId, Strings},
+};
+
+const DEFAULT_DEATH_DELAY: f32 = 0.5;
+// The 8x8 art scaled up for the overlay
+const ART_SIZE: f32 = 64.0;
+// Seconds after the overlay shows up before any key or gamepad button
+// restarts, so a key hit right as the snake died doesn't skip the screen
+const ANY_KEY_LOCKOUT: f32 = 0.4;
 
 pub struct GameOverPlugin;
 
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init_game_over);
-        app.add_systems(OnEnter(GameState::GameOver), show_game_over);
-        app.add_systems(Update, handle_inputs.run_if(in_state(GameState::GameOver)));
+        app.init_resource::<DeathDelayConfig>();
+        app.init_resource::<DeathDelay>();
+        app.init_resource::<GameOverLayout>();
+        app.init_resource::<RestartOnAnyKey>();
+        app.init_resource::<AnyKeyLockout>();
+        app.add_systems(
+            Update,
+            apply_restart_on_any_key.run_if(resource_changed::<Settings>),
+        );
+        app.add_systems(Startup, (init_game_over, init_game_over_art));
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            (
+                update_game_over_text,
+                select_game_over_layout,
+                start_death_delay,
+                start_any_key_lockout,
+            ),
+        );
+        app.add_systems(
+            Update,
+            (
+                tick_death_delay,
+                (tick_any_key_lockout, handle_inputs, show_game_over)
+                    .chain()
+                    .run_if(death_delay_finished),
+            )
+                .chain()
+                .run_if(in_state(GameState::GameOver)),
+        );
         app.add_systems(OnExit(GameState::GameOver), hide_game_over);
     }
 }
@@ -16,18 +61,135 @@ impl Plugin for GameOverPlugin {
 #[derive(Component)]
 struct GameOverText;
 
+// Art and caption above the text, picked by what ended the run
+#[derive(Component)]
+struct GameOverArt;
+
+#[derive(Component)]
+struct GameOverIcon;
+
+#[derive(Component)]
+struct GameOverCaption;
+
+// Layout of the current game over screen, None for the plain text
+#[derive(Resource, Default)]
+struct GameOverLayout(Option<(usize, MessageId)>);
+
+impl GameOverLayout {
+    // Anything without its own art, like obstacles or leaving a peaceful
+    // game, keeps the plain text
+    fn for_cause(cause: Option<GameOverEvent>) -> Self {
+        Self(cause.and_then(|cause| match cause {
+            GameOverEvent::BorderCollision => Some((GAME_OVER_WALL_INDEX, MessageId::GameOverWall)),
+            GameOverEvent::SelfCollision => Some((GAME_OVER_BITE_INDEX, MessageId::GameOverSelf)),
+            GameOverEvent::TimeUp => Some((GAME_OVER_CLOCK_INDEX, MessageId::GameOverTime)),
+            GameOverEvent::ObstacleCollision => None,
+        }))
+    }
+}
+
+#[derive(Resource)]
+pub struct DeathDelayConfig {
+    // Seconds the snake stays frozen where it died before the overlay shows up
+    pub duration: f32,
+}
+
+impl Default for DeathDelayConfig {
+    fn default() -> Self {
+        Self {
+            duration: DEFAULT_DEATH_DELAY,
+        }
+    }
+}
+
+// Counts down the freeze. The game is already in GameOver, so the snake
+// doesn't move, and the restart and menu keys wait for it to finish.
+// A death replay plays first, the freeze starts once it's over.
+#[derive(Resource, Default)]
+struct DeathDelay(Timer);
+
+fn start_death_delay(config: Res<DeathDelayConfig>, mut death_delay: ResMut<DeathDelay>) {
+    death_delay.0 = Timer::from_seconds(config.duration, TimerMode::Once);
+}
+
+fn tick_death_delay(
+    time: Res<Time>,
+    death_replay: Res<DeathReplay>,
+    mut death_delay: ResMut<DeathDelay>,
+) {
+    if death_replay.is_playing() {
+        return;
+    }
+    death_delay.0.tick(time.delta());
+}
+
+fn death_delay_finished(death_delay: Res<DeathDelay>) -> bool {
+    death_delay.0.finished()
+}
+
+// "Press any key to restart" for casual players, off by default
+#[derive(Resource, Default, PartialEq)]
+pub struct RestartOnAnyKey(pub bool);
+
+fn apply_restart_on_any_key(settings: Res<Settings>, mut any_key: ResMut<RestartOnAnyKey>) {
+    any_key.set_if_neq(RestartOnAnyKey(settings.restart_on_any_key));
+}
+
+// Counts from when the overlay shows up, after the death delay and the
+// border flash
+#[derive(Resource, Default)]
+struct AnyKeyLockout(Timer);
+
+fn start_any_key_lockout(mut lockout: ResMut<AnyKeyLockout>) {
+    lockout.0 = Timer::from_seconds(ANY_KEY_LOCKOUT, TimerMode::Once);
+}
+
+fn tick_any_key_lockout(
+    time: Res<Time>,
+    overlay_query: Query<&Visibility, With<GameOverText>>,
+    mut lockout: ResMut<AnyKeyLockout>,
+) {
+    if overlay_visible(&overlay_query) {
+        lockout.0.tick(time.delta());
+    }
+}
+
+fn overlay_visible(overlay_query: &Query<&Visibility, With<GameOverText>>) -> bool {
+    overlay_query
+        .single()
+        .is_ok_and(|visibility| *visibility == Visibility::Visible)
+}
+
 fn handle_inputs(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    key_bindings: Res<KeyBindings>,
+    any_key: Res<RestartOnAnyKey>,
+    lockout: Res<AnyKeyLockout>,
+    overlay_query: Query<&Visibility, With<GameOverText>>,
     mut game_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard_input.pressed(KeyCode::KeyR) {
+    if keyboard_input.just_pressed(key_bindings.menu) {
+        game_state.set(GameState::MainMenu);
+    } else if any_key.0 {
+        // The menu and pause keys keep their meaning, so they never restart
+        let any_pressed = keyboard_input
+            .get_just_pressed()
+            .any(|key| ![key_bindings.menu, key_bindings.pause, KeyCode::Escape].contains(key))
+            || gamepads
+                .iter()
+                .any(|gamepad| gamepad.digital().get_just_pressed().next().is_some());
+        if any_pressed && lockout.0.finished() && overlay_visible(&overlay_query) {
+            game_state.set(GameState::StartGame);
+        }
+    } else if keyboard_input.pressed(key_bindings.restart) {
         game_state.set(GameState::StartGame);
     }
 }
 
 fn init_game_over(mut commands: Commands) {
     commands.spawn((
-        Text::new(format!("GAME OVER\nScore: {}\nPress R to restart", 0)),
+        Text::new(""),
         TextLayout::new_with_justify(JustifyText::Center),
         TextFont {
             font_size: 32.0,
@@ -39,27 +201,288 @@ fn init_game_over(mut commands: Commands) {
             position_type: PositionType::Absolute,
             width: Val::Percent(100.0),
             height: Val::Percent(100.0),
-            top: Val::Percent(50.0),
+            // Raised above the middle to make room for the run stats
+            top: Val::Percent(25.0),
             justify_content: JustifyContent::Center,
             align_items: AlignItems::Center,
             ..default()
         },
         GameOverText,
+        UsesUiFont,
     ));
 }
 
-fn show_game_over(
+fn init_game_over_art(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Percent(6.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            Visibility::Hidden,
+            GameOverArt,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageNode::default(),
+                Node {
+                    width: Val::Px(ART_SIZE),
+                    height: Val::Px(ART_SIZE),
+                    ..default()
+                },
+                GameOverIcon,
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.0, 0.0)),
+                GameOverCaption,
+                UsesUiFont,
+            ));
+        });
+}
+
+// The event is sent on the frame the run ends, so it's still there when the
+// state changes. Every game over picks its layout anew, a run that ends
+// without an event gets the plain text rather than the last run's art.
+fn select_game_over_layout(
+    mut game_over_events: EventReader<GameOverEvent>,
+    snake_skin: Res<SnakeSkin>,
+    strings: Res<Strings>,
+    mut layout: ResMut<GameOverLayout>,
+    mut icon_query: Query<&mut ImageNode, With<GameOverIcon>>,
+    mut caption_query: Query<&mut Text, (With<GameOverCaption>, Without<GameOverText>)>,
+) {
+    *layout = GameOverLayout::for_cause(game_over_events.read().last().copied());
+    let Some((index, caption)) = layout.0 else {
+        return;
+    };
+
+    if let Ok(mut icon) = icon_query.single_mut() {
+        let sprite = snake_skin.sprite(index);
+        *icon = ImageNode {
+            image: sprite.image,
+            texture_atlas: sprite.texture_atlas,
+            ..default()
+        };
+    }
+    if let Ok(mut text) = caption_query.single_mut() {
+        text.0 = strings.get(caption).to_string();
+    }
+}
+
+// `numerator / denominator * scale` with one decimal, a dash while there's
+// nothing to divide by yet
+fn ratio(numerator: usize, denominator: usize, scale: f32) -> String {
+    if denominator == 0 {
+        return "-".to_string();
+    }
+    format!("{:.1}", numerator as f32 / denominator as f32 * scale)
+}
+
+// One line per stat, new stats only need an entry here. Derived stats are
+// computed from the counters with `ratio`.
+fn stats_text(score: usize, run_stats: &RunStats, strings: &Strings) -> String {
+    let stats: [(MessageId, String); 6] = [
+        (MessageId::StatLength, run_stats.length.to_string()),
+        (
+            MessageId::StatTime,
+            format!("{:.0}", run_stats.time_survived),
+        ),
+        (MessageId::StatFoods, run_stats.foods_eaten.to_string()),
+        (
+            MessageId::StatTopSpeed,
+            format!("{:.1}", run_stats.top_speed),
+        ),
+        (
+            MessageId::StatPointsPerFood,
+            ratio(score, run_stats.foods_eaten, 1.0),
+        ),
+        (
+            MessageId::StatEfficiency,
+            ratio(run_stats.foods_eaten, run_stats.moves, 100.0),
+        ),
+    ];
+
+    stats
+        .iter()
+        .map(|(id, value)| strings.format(*id, &[("value", value)]))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn update_game_over_text(
     score: Res<Score>,
-    mut query: Query<(&mut Visibility, &mut Text), With<GameOverText>>,
+    run_stats: Res<RunStats>,
+    key_bindings: Res<KeyBindings>,
+    any_key: Res<RestartOnAnyKey>,
+    run_seed: Res<RunSeed>,
+    strings: Res<Strings>,
+    mut query: Query<&mut Text, With<GameOverText>>,
+) {
+    let restart = if any_key.0 {
+        strings.get(MessageId::AnyKey).to_string()
+    } else {
+        key_label(key_bindings.restart)
+    };
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = strings.format(
+            MessageId::GameOver,
+            &[
+                ("score", &score.0),
+                ("stats", &stats_text(score.0, &run_stats, &strings)),
+                ("restart", &restart),
+                ("menu", &key_label(key_bindings.menu)),
+            ],
+        );
+        // Logged as well, so it can be copied from the terminal
+        if let Some(code) = run_seed.0 {
+            let code = code.encode();
+            info!("Challenge code: {}", code);
+            text.0.push('\n');
+            text.0
+                .push_str(&strings.format(MessageId::ChallengeCode, &[("code", &code)]));
+        }
+    }
+}
+
+// The overlay waits for the border flash so the cause of death stays visible
+fn show_game_over(
+    border_flash: Res<BorderFlash>,
+    layout: Res<GameOverLayout>,
+    mut query: Query<&mut Visibility, With<GameOverText>>,
+    mut art_query: Query<&mut Visibility, (With<GameOverArt>, Without<GameOverText>)>,
 ) {
-    if let Ok((mut visibility, mut text)) = query.single_mut() {
+    if border_flash.is_active() {
+        return;
+    }
+    if let Ok(mut visibility) = query.single_mut() {
+        *visibility = Visibility::Visible;
+    }
+    if layout.0.is_some()
+        && let Ok(mut visibility) = art_query.single_mut()
+    {
         *visibility = Visibility::Visible;
-        text.0 = format!("GAME OVER\nScore: {}\nPress R to restart", score.0);
     }
 }
 
-fn hide_game_over(mut game_over_query: Query<&mut Visibility, With<GameOverText>>) {
-    if let Ok(mut visibility) = game_over_query.single_mut() {
+fn hide_game_over(
+    mut game_over_query: Query<&mut Visibility, Or<(With<GameOverText>, With<GameOverArt>)>>,
+) {
+    for mut visibility in game_over_query.iter_mut() {
         *visibility = Visibility::Hidden;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    // Game over with any key restarting, the overlay still hidden
+    fn any_key_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.init_resource::<Time>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<KeyBindings>();
+        app.insert_resource(RestartOnAnyKey(true));
+        app.init_resource::<AnyKeyLockout>();
+        app.init_resource::<NextState<GameState>>();
+        app.add_systems(Update, (tick_any_key_lockout, handle_inputs).chain());
+        let overlay = app
+            .world_mut()
+            .spawn((GameOverText, Visibility::Hidden))
+            .id();
+        app.world_mut()
+            .run_system_cached(start_any_key_lockout)
+            .unwrap();
+        (app, overlay)
+    }
+
+    // One frame of `seconds` with a gamepad button pressed on it
+    fn press_button(app: &mut App, seconds: f32) {
+        let mut gamepad = Gamepad::default();
+        gamepad.digital_mut().press(GamepadButton::South);
+        let entity = app.world_mut().spawn(gamepad).id();
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+        app.world_mut().despawn(entity);
+    }
+
+    fn restarting(app: &App) -> bool {
+        matches!(
+            app.world().resource::<NextState<GameState>>(),
+            NextState::Pending(GameState::StartGame)
+        )
+    }
+
+    #[test]
+    fn gamepad_restarts_only_once_the_overlay_is_up() {
+        let (mut app, overlay) = any_key_app();
+
+        // The border flash is still on, however long it takes
+        press_button(&mut app, 1.0);
+        assert!(!restarting(&app));
+
+        app.world_mut()
+            .entity_mut(overlay)
+            .insert(Visibility::Visible);
+        press_button(&mut app, ANY_KEY_LOCKOUT / 2.0);
+        assert!(!restarting(&app), "still locked out");
+
+        press_button(&mut app, ANY_KEY_LOCKOUT);
+        assert!(restarting(&app));
+    }
+
+    #[test]
+    fn any_key_restarts_once_the_overlay_is_up() {
+        let (mut app, overlay) = any_key_app();
+        app.world_mut()
+            .entity_mut(overlay)
+            .insert(Visibility::Visible);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(ANY_KEY_LOCKOUT));
+        app.update();
+        assert!(!restarting(&app), "nothing pressed yet");
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        app.update();
+        assert!(restarting(&app));
+    }
+
+    #[test]
+    fn pause_and_escape_do_not_restart() {
+        let (mut app, overlay) = any_key_app();
+        app.world_mut().resource_mut::<KeyBindings>().pause = KeyCode::KeyP;
+        app.world_mut()
+            .entity_mut(overlay)
+            .insert(Visibility::Visible);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(ANY_KEY_LOCKOUT));
+        app.update();
+
+        for key in [KeyCode::KeyP, KeyCode::Escape] {
+            let mut keyboard_input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keyboard_input.release_all();
+            keyboard_input.clear();
+            keyboard_input.press(key);
+            app.update();
+            assert!(!restarting(&app), "{:?}", key);
+        }
+    }
+}
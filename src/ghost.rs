@@ -0,0 +1,233 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameMode, GameState, GameplaySet, GridOrigin, Position, RunStats, Score, TILE_SIZE,
+    assets_loader::SnakeSkin,
+    grid_to_screen_position,
+    save_file::write_atomic,
+    settings::Settings,
+    snake::{Dir, Head, head_atlas_index},
+    test_mode::{self, TestMode},
+};
+
+// Best timed run, replayed as a ghost to race against
+const GHOST_PATH: &str = "ghost.ron";
+// Drawn under the live snake, translucent and tinted so the two never mix up
+const GHOST_Z: f32 = -0.05;
+const GHOST_COLOR: Color = Color::srgba(0.6, 0.8, 1.0, 0.4);
+const GHOST_BODY_SIZE: f32 = TILE_SIZE * 0.6;
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        if test_mode::uses_user_files(app) {
+            app.insert_resource(BestRun::load(GHOST_PATH));
+        } else {
+            app.init_resource::<BestRun>();
+        }
+        app.init_resource::<RunRecording>();
+        app.add_systems(
+            OnEnter(GameState::StartGame),
+            (reset_recording, despawn_ghost),
+        );
+        app.add_systems(
+            Update,
+            (
+                record_head,
+                move_ghost
+                    .run_if(|settings: Res<Settings>| settings.ghost)
+                    .run_if(|best_run: Res<BestRun>| !best_run.samples.is_empty()),
+            )
+                .chain()
+                .after(GameplaySet::Collision)
+                .run_if(in_state(GameState::InGame))
+                .run_if(resource_equals(GameMode::Timed)),
+        );
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            (
+                despawn_ghost,
+                keep_best_run.run_if(resource_equals(GameMode::Timed)),
+            ),
+        );
+        app.add_systems(OnEnter(GameState::MainMenu), despawn_ghost);
+    }
+}
+
+// Where the head was after each tick, and how long the snake was then
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Sample {
+    // Seconds into the run, pauses excluded
+    time: f32,
+    head: Position,
+    length: usize,
+}
+
+// The highest scoring timed run so far
+#[derive(Resource, Serialize, Deserialize, Default)]
+struct BestRun {
+    score: usize,
+    samples: Vec<Sample>,
+}
+
+impl BestRun {
+    fn load(path: &str) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            println!(
+                "Could not parse {}: {}, starting without a ghost",
+                path, err
+            );
+            Self::default()
+        })
+    }
+
+    fn save(&self, path: &str) {
+        match ron::to_string(self) {
+            Ok(contents) => {
+                if let Err(err) = write_atomic(path, &contents) {
+                    println!("Could not save the ghost to {}: {}", path, err);
+                }
+            }
+            Err(err) => println!("Could not serialize the ghost: {}", err),
+        }
+    }
+
+    // Last sample reached at `time`, the ghost stays there once its run is over
+    fn sample_at(&self, time: f32) -> Option<usize> {
+        let reached = self.samples.partition_point(|sample| sample.time <= time);
+        reached.checked_sub(1)
+    }
+}
+
+// Timeline of the run in progress
+#[derive(Resource, Default)]
+struct RunRecording(Vec<Sample>);
+
+// Index along the ghost, 0 is the head
+#[derive(Component)]
+struct GhostPart(usize);
+
+fn reset_recording(mut recording: ResMut<RunRecording>) {
+    recording.0.clear();
+}
+
+fn despawn_ghost(mut commands: Commands, query: Query<Entity, With<GhostPart>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn record_head(
+    run_stats: Res<RunStats>,
+    head_query: Query<Ref<Position>, With<Head>>,
+    mut recording: ResMut<RunRecording>,
+) {
+    let Ok(head_pos) = head_query.single() else {
+        return;
+    };
+    if !head_pos.is_changed() {
+        return;
+    }
+
+    recording.0.push(Sample {
+        time: run_stats.time_survived,
+        head: *head_pos,
+        length: run_stats.length,
+    });
+}
+
+fn keep_best_run(
+    score: Res<Score>,
+    test_mode: Res<TestMode>,
+    mut recording: ResMut<RunRecording>,
+    mut best_run: ResMut<BestRun>,
+) {
+    if score.0 == 0 || score.0 <= best_run.score || recording.0.is_empty() {
+        return;
+    }
+
+    println!("New best timed run, {} is the ghost now", score.0);
+    best_run.score = score.0;
+    best_run.samples = std::mem::take(&mut recording.0);
+    if test_mode.uses_user_files() {
+        best_run.save(GHOST_PATH);
+    }
+}
+
+// The ghost's body trails its head through the cells the head recorded last
+fn move_ghost(
+    mut commands: Commands,
+    run_stats: Res<RunStats>,
+    best_run: Res<BestRun>,
+    snake_skin: Res<SnakeSkin>,
+    grid_origin: Res<GridOrigin>,
+    mut part_query: Query<(&GhostPart, &mut Sprite, &mut Transform, &mut Visibility)>,
+) {
+    let Some(index) = best_run.sample_at(run_stats.time_survived) else {
+        return;
+    };
+    let length = best_run.samples[index].length.clamp(1, index + 1);
+    let cells: Vec<Position> = (0..length)
+        .map(|offset| best_run.samples[index - offset].head)
+        .collect();
+    let head_index = head_atlas_index(ghost_direction(&best_run.samples[..=index]));
+
+    let mut existing = 0;
+    for (part, mut sprite, mut transform, mut visibility) in part_query.iter_mut() {
+        existing = existing.max(part.0 + 1);
+        let Some(cell) = cells.get(part.0) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        transform.translation = grid_to_screen_position(cell, *grid_origin).with_z(GHOST_Z);
+        *visibility = Visibility::Inherited;
+        if part.0 == 0
+            && let Some(atlas) = &mut sprite.texture_atlas
+            && atlas.index != head_index
+        {
+            atlas.index = head_index;
+        }
+    }
+
+    for (idx, cell) in cells.iter().enumerate().skip(existing) {
+        let sprite = if idx == 0 {
+            let mut sprite = snake_skin.sprite(head_index);
+            sprite.color = GHOST_COLOR;
+            sprite
+        } else {
+            Sprite::from_color(GHOST_COLOR, Vec2::splat(GHOST_BODY_SIZE))
+        };
+        commands.spawn((
+            GhostPart(idx),
+            sprite,
+            Transform::from_translation(
+                grid_to_screen_position(cell, *grid_origin).with_z(GHOST_Z),
+            ),
+        ));
+    }
+}
+
+// Way the ghost's head faces, from its last step between neighbouring cells.
+// Jumps through walls and portals keep the direction from before.
+fn ghost_direction(samples: &[Sample]) -> Dir {
+    samples
+        .windows(2)
+        .rev()
+        .find_map(|pair| {
+            let step = (
+                pair[1].head.x - pair[0].head.x,
+                pair[1].head.y - pair[0].head.y,
+            );
+            [Dir::Up, Dir::Right, Dir::Down, Dir::Left]
+                .into_iter()
+                .find(|dir| dir.offset() == step)
+        })
+        .unwrap_or(Dir::Right)
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, GameplaySet, GridOrigin, Position, TILE_SIZE, grid_to_screen_position,
+    settings::Settings, snake::Head,
+};
+
+// Above the floor and the snake, below the HUD texts
+const HEATMAP_Z: f32 = 0.8;
+const HEATMAP_ALPHA: f32 = 0.55;
+// Colors for the least and the most visited cells
+const COLD: Color = Color::srgb(0.1, 0.3, 1.0);
+const HOT: Color = Color::srgb(1.0, 0.1, 0.1);
+
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VisitCounts>();
+        app.add_systems(OnEnter(GameState::StartGame), reset_visit_counts);
+        app.add_systems(
+            Update,
+            count_visits
+                .after(GameplaySet::Movement)
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            show_heatmap.run_if(|settings: Res<Settings>| settings.heatmap),
+        );
+        app.add_systems(OnExit(GameState::GameOver), hide_heatmap);
+    }
+}
+
+// Ticks the head spent on each cell during the current run
+#[derive(Resource, Default)]
+pub struct VisitCounts(pub HashMap<(i8, i8), u32>);
+
+#[derive(Component)]
+struct HeatmapCell;
+
+fn reset_visit_counts(mut visit_counts: ResMut<VisitCounts>) {
+    visit_counts.0.clear();
+}
+
+// The head's Position only changes when it steps, so every change is one tick
+fn count_visits(
+    head_query: Query<&Position, (With<Head>, Changed<Position>)>,
+    mut visit_counts: ResMut<VisitCounts>,
+) {
+    for pos in head_query.iter() {
+        *visit_counts.0.entry((pos.x, pos.y)).or_default() += 1;
+    }
+}
+
+// Colors run from cold to hot relative to the most visited cell of the run
+fn show_heatmap(
+    mut commands: Commands,
+    visit_counts: Res<VisitCounts>,
+    grid_origin: Res<GridOrigin>,
+) {
+    let Some(max) = visit_counts.0.values().copied().max() else {
+        return;
+    };
+
+    for (&(x, y), &count) in visit_counts.0.iter() {
+        let heat = count as f32 / max as f32;
+        let color = COLD.mix(&HOT, heat).with_alpha(HEATMAP_ALPHA);
+        let mut translation = grid_to_screen_position(&Position { x, y }, *grid_origin);
+        translation.z = HEATMAP_Z;
+        commands.spawn((
+            HeatmapCell,
+            Sprite::from_color(color, Vec2::splat(TILE_SIZE)),
+            Transform::from_translation(translation),
+        ));
+    }
+}
+
+fn hide_heatmap(mut commands: Commands, query: Query<Entity, With<HeatmapCell>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
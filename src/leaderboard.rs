@@ -0,0 +1,253 @@
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, Score,
+    save_file::write_atomic,
+    strings::{MessageId, Strings},
+    test_mode::{self, TestMode},
+};
+
+// Best scores, one `score timestamp` line per run, timestamps in Unix seconds
+const LEADERBOARD_PATH: &str = "leaderboard.txt";
+const MAX_ENTRIES: usize = 10;
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        if test_mode::uses_user_files(app) {
+            app.insert_resource(Leaderboard::load(LEADERBOARD_PATH));
+        } else {
+            app.init_resource::<Leaderboard>();
+        }
+        app.add_systems(Startup, init_leaderboard_screen);
+        app.add_systems(OnEnter(GameState::GameOver), record_score);
+        app.add_systems(
+            Last,
+            save_leaderboard_on_exit
+                .run_if(on_event::<AppExit>)
+                .run_if(test_mode::saves_user_files),
+        );
+        app.add_systems(OnEnter(GameState::Leaderboard), show_leaderboard);
+        app.add_systems(OnExit(GameState::Leaderboard), hide_leaderboard);
+        app.add_systems(
+            Update,
+            handle_inputs.run_if(in_state(GameState::Leaderboard)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    score: usize,
+    timestamp: u64,
+}
+
+// Highest score first, never longer than MAX_ENTRIES
+#[derive(Resource, Default)]
+pub struct Leaderboard {
+    entries: Vec<Entry>,
+    // A made-up high score was added for a showcase, so nothing gets written
+    // over the player's real leaderboard
+    preloaded: bool,
+}
+
+impl Leaderboard {
+    fn load(path: &str) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+
+        let parsed: Option<Vec<Entry>> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (score, timestamp) = line.split_once(' ')?;
+                Some(Entry {
+                    score: score.parse().ok()?,
+                    timestamp: timestamp.trim().parse().ok()?,
+                })
+            })
+            .collect();
+
+        // A damaged file is thrown away rather than half trusted
+        let Some(entries) = parsed else {
+            println!("Could not parse {}, starting a new leaderboard", path);
+            return Self::default();
+        };
+
+        let mut leaderboard = Self {
+            entries,
+            preloaded: false,
+        };
+        leaderboard.sort_and_truncate();
+        leaderboard
+    }
+
+    fn save(&self, path: &str) {
+        if self.preloaded {
+            return;
+        }
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|entry| format!("{} {}\n", entry.score, entry.timestamp))
+            .collect();
+        if let Err(err) = write_atomic(path, &contents) {
+            println!("Could not save leaderboard to {}: {}", path, err);
+        }
+    }
+
+    // Returns whether the score made it onto the board
+    fn insert(&mut self, score: usize, timestamp: u64) -> bool {
+        if self.entries.len() >= MAX_ENTRIES
+            && self.entries.last().is_some_and(|last| last.score >= score)
+        {
+            return false;
+        }
+        self.entries.push(Entry { score, timestamp });
+        self.sort_and_truncate();
+        true
+    }
+
+    // Puts `score` on top for this session only, see `preloaded`
+    pub fn preload_high_score(&mut self, score: usize) {
+        self.entries.retain(|entry| entry.score < score);
+        self.entries.insert(
+            0,
+            Entry {
+                score,
+                timestamp: now_secs(),
+            },
+        );
+        self.sort_and_truncate();
+        self.preloaded = true;
+    }
+
+    // Stable sort, so older runs stay ahead of later runs with the same score
+    fn sort_and_truncate(&mut self) {
+        self.entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+#[derive(Component)]
+struct LeaderboardText;
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+// Unix seconds to a UTC `YYYY-MM-DD`, using the days-to-civil algorithm
+// (http://howardhinnant.github.io/date_algorithms.html)
+fn format_date(timestamp: u64) -> String {
+    let z = (timestamp / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn leaderboard_text(leaderboard: &Leaderboard, strings: &Strings) -> String {
+    let mut text = format!("{}\n\n", strings.get(MessageId::LeaderboardTitle));
+    if leaderboard.entries.is_empty() {
+        text.push_str(&format!("{}\n", strings.get(MessageId::LeaderboardEmpty)));
+    }
+    for (idx, entry) in leaderboard.entries.iter().enumerate() {
+        text.push_str(&strings.format(
+            MessageId::LeaderboardEntry,
+            &[
+                ("rank", &(idx + 1).to_string()),
+                ("score", &entry.score.to_string()),
+                ("date", &format_date(entry.timestamp)),
+            ],
+        ));
+        text.push('\n');
+    }
+    text.push_str(&format!("\n{}", strings.get(MessageId::LeaderboardHint)));
+    text
+}
+
+fn record_score(score: Res<Score>, test_mode: Res<TestMode>, mut leaderboard: ResMut<Leaderboard>) {
+    if score.0 == 0 {
+        return;
+    }
+    if leaderboard.insert(score.0, now_secs()) && test_mode.uses_user_files() {
+        leaderboard.save(LEADERBOARD_PATH);
+    }
+}
+
+// Closing the window mid-run still records the score reached so far
+fn save_leaderboard_on_exit(
+    score: Res<Score>,
+    game_state: Res<State<GameState>>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    if matches!(game_state.get(), GameState::InGame | GameState::Paused) && score.0 > 0 {
+        leaderboard.insert(score.0, now_secs());
+    }
+    leaderboard.save(LEADERBOARD_PATH);
+}
+
+fn handle_inputs(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) || keyboard_input.just_pressed(KeyCode::Enter) {
+        game_state.set(GameState::MainMenu);
+    }
+}
+
+fn init_leaderboard_screen(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        LeaderboardText,
+    ));
+}
+
+fn show_leaderboard(
+    leaderboard: Res<Leaderboard>,
+    strings: Res<Strings>,
+    mut query: Query<(&mut Visibility, &mut Text), With<LeaderboardText>>,
+) {
+    if let Ok((mut visibility, mut text)) = query.single_mut() {
+        *visibility = Visibility::Visible;
+        text.0 = leaderboard_text(&leaderboard, &strings);
+    }
+}
+
+fn hide_leaderboard(mut query: Query<&mut Visibility, With<LeaderboardText>>) {
+    if let Ok(mut visibility) = query.single_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
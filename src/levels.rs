@@ -0,0 +1,106 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::snake::{Dir, StartDirection};
+
+// Level list shown in the main menu, see the file for the format
+const LEVELS_PATH: &str = "assets/levels.ron";
+const FALLBACK_LEVEL_NAME: &str = "Open field";
+
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Levels::load(LEVELS_PATH));
+        app.add_systems(
+            Update,
+            apply_start_direction.run_if(resource_changed::<Levels>),
+        );
+    }
+}
+
+// Features placed on top of the regular field. Every list may be left out.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Level {
+    pub name: String,
+    // Pairs of linked cells, entering either one exits from the other
+    #[serde(default)]
+    pub portals: Vec<((i8, i8), (i8, i8))>,
+    // Cells that change the turn delay while the head is on them, see SpeedTile
+    #[serde(default)]
+    pub speed_tiles: Vec<((i8, i8), f32)>,
+    // Way the snake faces at the start, Right when left out
+    #[serde(default)]
+    pub start_direction: Option<Dir>,
+}
+
+impl Level {
+    fn fallback() -> Self {
+        Self {
+            name: FALLBACK_LEVEL_NAME.to_string(),
+            portals: Vec::new(),
+            speed_tiles: Vec::new(),
+            start_direction: None,
+        }
+    }
+}
+
+// Available levels and the one the next game is played on
+#[derive(Resource)]
+pub struct Levels {
+    levels: Vec<Level>,
+    selected: usize,
+}
+
+impl Levels {
+    fn load(path: &str) -> Self {
+        let levels = match fs::read_to_string(path) {
+            Ok(contents) => match ron::from_str::<Vec<Level>>(&contents) {
+                Ok(levels) if !levels.is_empty() => levels,
+                Ok(_) => {
+                    println!("No levels in {}, using an open field", path);
+                    vec![Level::fallback()]
+                }
+                Err(err) => {
+                    println!("Could not parse {}: {}, using an open field", path, err);
+                    vec![Level::fallback()]
+                }
+            },
+            Err(err) => {
+                println!("Could not read {}: {}, using an open field", path, err);
+                vec![Level::fallback()]
+            }
+        };
+
+        Self {
+            levels,
+            selected: 0,
+        }
+    }
+
+    pub fn current(&self) -> &Level {
+        &self.levels[self.selected]
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.levels.len();
+    }
+
+    // Returns whether a level with that name exists
+    pub fn select_named(&mut self, name: &str) -> bool {
+        let Some(index) = self.levels.iter().position(|level| level.name == name) else {
+            return false;
+        };
+        self.selected = index;
+        true
+    }
+}
+
+fn apply_start_direction(levels: Res<Levels>, mut start_direction: ResMut<StartDirection>) {
+    let direction = StartDirection(levels.current().start_direction.unwrap_or(Dir::Right));
+    if *start_direction != direction {
+        *start_direction = direction;
+    }
+}
@@ -0,0 +1,1261 @@
+use bevy::text::JustifyText;
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+}; // Correct import for SpatialBundle
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    assets_loader::{
+        BORDER_CORNER_INDEX, BORDER_STRAIGHT_INDEX, FLOOR_INDEX, FOOD_INDEX, GameAssets, SnakeSkin,
+        UsesUiFont,
+    },
+    camera::{CameraAnchored, DEFAULT_ZOOM},
+    controls::{KeyBindings, key_label},
+    debug::{FpsSamples, FpsWarning},
+    food_chain::ChainFood,
+    levels::Levels,
+    obstacles::Obstacle,
+    portals::Portal,
+    snake::{Ate, BodySegment, Head, SnakeErrors, StartDirection},
+    strings::{MessageId, Strings},
+    test_mode::TestMode,
+};
+
+// Default playfield, see GridConfig
+const FIELD_FROM: (i8, i8) = (-5, -5);
+const FIELD_TO: (i8, i8) = (6, 6);
+// Area the snake can roam in endless mode before wrapping around
+const ROAM_FROM: (i8, i8) = (-60, -60);
+const ROAM_TO: (i8, i8) = (60, 60);
+const TILE_SIZE: f32 = 8.0;
+// Cell the first food starts on, the snake's cells come from StartDirection
+const FIRST_FOOD_CELL: (i8, i8) = (3, 3);
+// Random cells tried for a new food before falling back to a scan of the region
+const FOOD_PLACEMENT_ATTEMPTS: usize = 64;
+// How fast the displayed score catches up with the real one (per second)
+const SCORE_TWEEN_RATE: f32 = 10.0;
+
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+enum GameState {
+    #[default]
+    AssetsLoading,
+    MainMenu,
+    Settings,
+    Leaderboard,
+    StartGame,
+    InGame,
+    Paused,
+    GameOver,
+    // A spritesheet couldn't be loaded, the game can't be drawn
+    LoadError,
+}
+
+// Order of the per-tick gameplay systems: collisions see the head's new cell,
+// and crashes are settled once every collision has been checked
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+enum GameplaySet {
+    Movement,
+    Collision,
+    Crash,
+}
+
+// The head hit something. Costs a life, the last one ends the run.
+#[derive(Event, Debug, Clone, Copy)]
+struct CrashEvent(GameOverEvent);
+
+// Why the run ended
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameOverEvent {
+    BorderCollision,
+    SelfCollision,
+    ObstacleCollision,
+    TimeUp,
+}
+
+// The head reached a food
+#[derive(Event)]
+struct SnakeAteEvent;
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum GameMode {
+    #[default]
+    Classic,
+    Timed,
+    Endless,
+}
+
+impl GameMode {
+    // Inclusive range of cells the snake may occupy in this mode
+    fn field_bounds(self, grid: &GridConfig) -> ((i8, i8), (i8, i8)) {
+        match self {
+            GameMode::Classic | GameMode::Timed => (grid.from, grid.to),
+            GameMode::Endless => (ROAM_FROM, ROAM_TO),
+        }
+    }
+}
+
+// Rings of wall around the field. The walls grow inwards, so every ring past
+// the first takes a cell off each side of the playfield, see GameConfig.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+struct BorderThickness(u8);
+
+impl Default for BorderThickness {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl BorderThickness {
+    // How far the walls reach past a single ring, in world units
+    fn outset(&self) -> f32 {
+        self.0.saturating_sub(1) as f32 * TILE_SIZE
+    }
+}
+
+// Where the board sits in the window, in world units, e.g. to leave room for
+// a HUD panel on one side. Only changes between runs, so a board is never
+// moved under the snake.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+struct GridOrigin(Vec2);
+
+// No game over: the head wraps at the borders and passes through the body and obstacles
+#[derive(Resource, Default, PartialEq)]
+struct Peaceful(bool);
+
+// Walled playfield as an inclusive range of cells. Any rectangle works, the
+// snake and the first food just need to fit in.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+struct GridConfig {
+    from: (i8, i8),
+    to: (i8, i8),
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            from: FIELD_FROM,
+            to: FIELD_TO,
+        }
+    }
+}
+
+impl GridConfig {
+    // A width x height field around the origin, one cell further right/up when even
+    fn centered(width: i8, height: i8) -> Self {
+        let from = (-(width - 1) / 2, -(height - 1) / 2);
+        Self {
+            from,
+            to: (from.0 + width - 1, from.1 + height - 1),
+        }
+    }
+
+    // Playfield inside walls of the given thickness. The first ring sits just
+    // outside the width x height cells, every further one takes their outermost cells.
+    fn walled(width: i8, height: i8, thickness: u8) -> Self {
+        let inset = thickness.saturating_sub(1) as i8;
+        let field = Self::centered(width, height);
+        Self {
+            from: (field.from.0 + inset, field.from.1 + inset),
+            to: (field.to.0 - inset, field.to.1 - inset),
+        }
+    }
+
+    fn width(&self) -> i8 {
+        self.to.0 - self.from.0 + 1
+    }
+
+    fn height(&self) -> i8 {
+        self.to.1 - self.from.1 + 1
+    }
+
+    fn contains(&self, position: &Position) -> bool {
+        position.x >= self.from.0
+            && position.x <= self.to.0
+            && position.y >= self.from.1
+            && position.y <= self.to.1
+    }
+}
+
+mod achievements;
+mod assets_loader;
+mod assist;
+mod camera;
+mod challenge;
+mod console;
+mod controls;
+mod controls_overlay;
+mod death_replay;
+mod debug;
+mod effects;
+mod food_bob;
+mod food_chain;
+mod food_pop;
+mod game_config;
+mod game_over;
+mod ghost;
+mod heatmap;
+mod leaderboard;
+mod levels;
+mod lives;
+mod menu;
+mod menu_nav;
+mod metrics;
+mod music;
+mod obstacles;
+mod pause;
+mod portals;
+mod save_file;
+mod saved_game;
+mod score_reporter;
+mod settings;
+mod showcase;
+mod snake;
+mod speed_tiles;
+mod strings;
+mod survival;
+mod test_mode;
+#[cfg(any(test, feature = "test_mode"))]
+pub mod test_support;
+mod timed_mode;
+mod window_config;
+
+pub use snake::{BENCH_FLAG, run_movement_bench};
+pub use window_config::WindowConfig;
+
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    x: i8,
+    y: i8,
+}
+
+#[derive(Component)]
+struct BorderSegment;
+
+#[derive(Resource, Default)]
+struct Score(usize);
+
+// Numbers shown on the results screen, collected during the run
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub foods_eaten: usize,
+    pub time_survived: f32,
+    pub length: usize,
+    // Cells per second, dashing included
+    pub top_speed: f32,
+    // Cells the head moved, one per tick
+    pub moves: usize,
+}
+
+// Score shown in the HUD, counting up towards `Score`
+#[derive(Resource, Default)]
+struct DisplayedScore(f32);
+
+#[derive(Component)]
+struct Food(FoodKind);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum FoodKind {
+    Normal,
+}
+
+// Points awarded for eating each kind of food. Balance changes go here.
+#[derive(Resource)]
+struct FoodValues(HashMap<FoodKind, usize>);
+
+impl Default for FoodValues {
+    fn default() -> Self {
+        Self(HashMap::from([(FoodKind::Normal, 1)]))
+    }
+}
+
+impl FoodValues {
+    fn points(&self, kind: FoodKind) -> usize {
+        self.0.get(&kind).copied().unwrap_or(1)
+    }
+}
+
+// Inclusive range of cells new food may appear on, e.g. to keep it away from
+// the edges. None uses the whole playable area.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Default)]
+struct FoodSpawnRegion(Option<((i8, i8), (i8, i8))>);
+
+impl FoodSpawnRegion {
+    // Clipped to the playable area, a region that misses it entirely falls
+    // back to the whole area
+    fn bounds(&self, game_mode: GameMode, grid: &GridConfig) -> ((i8, i8), (i8, i8)) {
+        let field = game_mode.field_bounds(grid);
+        let Some((from, to)) = self.0 else {
+            return field;
+        };
+
+        let from = (from.0.max(field.0.0), from.1.max(field.0.1));
+        let to = (to.0.min(field.1.0), to.1.min(field.1.1));
+        if from.0 > to.0 || from.1 > to.1 {
+            return field;
+        }
+        (from, to)
+    }
+}
+
+// Random cell in the bounds that's not taken, scanning the bounds in order
+// once random picks keep missing. None when every cell is taken.
+fn random_free_cell(
+    test_mode: &mut TestMode,
+    (from, to): ((i8, i8), (i8, i8)),
+    occupied: &HashSet<(i8, i8)>,
+) -> Option<(i8, i8)> {
+    (0..FOOD_PLACEMENT_ATTEMPTS)
+        .map(|_| test_mode.random_cell(from, to))
+        .find(|cell| !occupied.contains(cell))
+        .or_else(|| {
+            (from.1..=to.1)
+                .flat_map(|y| (from.0..=to.0).map(move |x| (x, y)))
+                .find(|cell| !occupied.contains(cell))
+        })
+}
+
+#[derive(Component)]
+struct FpsText;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct PeacefulText;
+
+#[derive(Component)]
+struct Tilemap;
+
+// Above the top wall, moved along with the board
+fn score_text_position(grid: &GridConfig, thickness: BorderThickness, origin: GridOrigin) -> Vec3 {
+    Vec3::new(
+        grid.from.0 as f32 * TILE_SIZE + TILE_SIZE + origin.0.x,
+        grid.to.1 as f32 * TILE_SIZE + TILE_SIZE * 2.0 + thickness.outset() + origin.0.y,
+        1.0, // A z-value to ensure the text is rendered on top of other sprites.
+    )
+}
+
+fn place_score_text(
+    grid: Res<GridConfig>,
+    border_thickness: Res<BorderThickness>,
+    grid_origin: Res<GridOrigin>,
+    mut text_query: Query<&mut CameraAnchored, With<ScoreText>>,
+) {
+    for mut anchored in text_query.iter_mut() {
+        anchored.0 = score_text_position(&grid, *border_thickness, *grid_origin);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    grid: Res<GridConfig>,
+    border_thickness: Res<BorderThickness>,
+    strings: Res<Strings>,
+) {
+    // Camera with 4x pixel scaling
+    commands.spawn((Camera2d, Transform::from_scale(Vec3::splat(DEFAULT_ZOOM))));
+
+    // FPS Text
+    commands.spawn((
+        Text::new(strings.format(MessageId::Fps, &[("fps", &0)])),
+        TextLayout::new_with_justify(JustifyText::Left),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        },
+        FpsText,
+        UsesUiFont,
+    ));
+
+    // Reminds peaceful runs how to end, since nothing else will
+    commands.spawn((
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Left),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        },
+        PeacefulText,
+        UsesUiFont,
+    ));
+
+    let world_pos = score_text_position(&grid, *border_thickness, GridOrigin::default());
+
+    // Score Text (positioned in world space using Text2d)
+    commands.spawn((
+        Text2d::new(strings.format(MessageId::Score, &[("score", &0)])),
+        TextFont {
+            font_size: 8.0, // Larger font size to compensate for camera scaling
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        Transform::from_translation(world_pos),
+        CameraAnchored(world_pos),
+        ScoreText,
+        UsesUiFont,
+    ));
+}
+
+fn grid_to_screen_position(position: &Position, origin: GridOrigin) -> Vec3 {
+    grid_to_screen_transform(position, origin).translation
+}
+
+// Everything on the board is placed through here, so the origin moves the
+// tilemap, the walls and every sprite on them together
+fn grid_to_screen_transform(position: &Position, origin: GridOrigin) -> Transform {
+    Transform::from_xyz(
+        position.x as f32 * 8.0 + origin.0.x,
+        position.y as f32 * 8.0 + origin.0.y,
+        0.0,
+    )
+}
+
+// The color follows the averaged frame rate, so a single slow frame doesn't flash it
+fn update_fps(
+    time: Res<Time>,
+    strings: Res<Strings>,
+    samples: Res<FpsSamples>,
+    fps_warning: Res<FpsWarning>,
+    mut fps_query: Query<(&mut Text, &mut TextColor), With<FpsText>>,
+) {
+    if let Ok((mut fps_text, mut fps_color)) = fps_query.single_mut() {
+        let fps = 1.0 / time.delta_secs();
+        fps_text.0 = strings.format(MessageId::Fps, &[("fps", &format!("{:.0}", fps))]);
+
+        if let Some(average) = samples.average_fps() {
+            fps_color.set_if_neq(TextColor(fps_warning.color(average)));
+        }
+    }
+}
+
+fn check_border_collision(
+    head_query: Query<&Position, With<Head>>,
+    grid: Res<GridConfig>,
+    mut crash_events: EventWriter<CrashEvent>,
+    mut snake_errors: ResMut<SnakeErrors>,
+) {
+    let head_pos = match head_query.single() {
+        Ok(head_pos) => head_pos,
+        Err(err) => {
+            snake_errors.report("check_border_collision", err);
+            return;
+        }
+    };
+
+    if head_pos.x < grid.from.0
+        || head_pos.x > grid.to.0
+        || head_pos.y < grid.from.1
+        || head_pos.y > grid.to.1
+    {
+        debug!("Head left the field at {:?}", head_pos);
+        crash_events.write(CrashEvent(GameOverEvent::BorderCollision));
+    }
+}
+
+fn check_food_collision(
+    mut food_query: Query<(&Food, &mut Position, &mut Transform)>,
+    mut head_query: Query<(&Position, &mut Ate), (With<Head>, Without<Food>)>,
+    obstacle_query: Query<&Position, (With<Obstacle>, Without<Food>)>,
+    portal_query: Query<&Position, (With<Portal>, Without<Food>)>,
+    body_query: Query<&Position, (With<BodySegment>, Without<Food>)>,
+    chain_query: Query<&Position, (With<ChainFood>, Without<Food>)>,
+    mut score: ResMut<Score>,
+    mut run_stats: ResMut<RunStats>,
+    food_values: Res<FoodValues>,
+    game_mode: Res<GameMode>,
+    // Grouped to stay within Bevy's system parameter limit
+    (grid, grid_origin): (Res<GridConfig>, Res<GridOrigin>),
+    spawn_region: Res<FoodSpawnRegion>,
+    mut ate_events: EventWriter<SnakeAteEvent>,
+    mut snake_errors: ResMut<SnakeErrors>,
+    mut test_mode: ResMut<TestMode>,
+) {
+    let (head_pos, mut snake_ate) = match head_query.single_mut() {
+        Ok(head) => head,
+        Err(err) => {
+            snake_errors.report("check_food_collision", err);
+            return;
+        }
+    };
+
+    if let Ok((food, mut food_pos, mut food_transform)) = food_query.single_mut()
+        && head_pos.x == food_pos.x
+        && head_pos.y == food_pos.y
+    {
+        // The food lands on a cell of the region that's not taken by the
+        // snake, an obstacle, a portal or the food chain
+        let occupied: HashSet<(i8, i8)> = obstacle_query
+            .iter()
+            .chain(portal_query.iter())
+            .chain(body_query.iter())
+            .chain(chain_query.iter())
+            .chain(std::iter::once(head_pos))
+            .map(|pos| (pos.x, pos.y))
+            .collect();
+        let bounds = spawn_region.bounds(*game_mode, &grid);
+        match random_free_cell(&mut test_mode, bounds, &occupied) {
+            Some((x, y)) => (food_pos.x, food_pos.y) = (x, y),
+            None => warn!("No free cell left for the food"),
+        }
+
+        food_transform.translation = grid_to_screen_position(&food_pos, *grid_origin);
+
+        snake_ate.0 = true;
+
+        score.0 += food_values.points(food.0);
+        ate_events.write(SnakeAteEvent);
+        run_stats.foods_eaten += 1;
+        debug!("Score: {}", score.0);
+    }
+}
+
+// A freshly spawned snake, at the start of a round or after a lost life, may
+// land on the food. The food then moves to a free cell, before the first
+// tick could count it as eaten.
+fn move_food_off_snake(
+    new_head_query: Query<(), Added<Head>>,
+    snake_query: Query<&Position, (Or<(With<Head>, With<BodySegment>)>, Without<Food>)>,
+    obstacle_query: Query<&Position, (With<Obstacle>, Without<Food>)>,
+    portal_query: Query<&Position, (With<Portal>, Without<Food>)>,
+    chain_query: Query<&Position, (With<ChainFood>, Without<Food>)>,
+    mut food_query: Query<(&mut Position, &mut Transform), With<Food>>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    spawn_region: Res<FoodSpawnRegion>,
+    mut test_mode: ResMut<TestMode>,
+) {
+    if new_head_query.is_empty() {
+        return;
+    }
+    let Ok((mut food_pos, mut food_transform)) = food_query.single_mut() else {
+        return;
+    };
+    if !snake_query
+        .iter()
+        .any(|pos| pos.x == food_pos.x && pos.y == food_pos.y)
+    {
+        return;
+    }
+
+    let occupied: HashSet<(i8, i8)> = snake_query
+        .iter()
+        .chain(obstacle_query.iter())
+        .chain(portal_query.iter())
+        .chain(chain_query.iter())
+        .map(|pos| (pos.x, pos.y))
+        .collect();
+    let bounds = spawn_region.bounds(*game_mode, &grid);
+    match random_free_cell(&mut test_mode, bounds, &occupied) {
+        Some((x, y)) => {
+            debug!("Food was under the snake, moved to {}, {}", x, y);
+            (food_pos.x, food_pos.y) = (x, y);
+            food_transform.translation = grid_to_screen_position(&food_pos, *grid_origin);
+        }
+        None => debug!("No free cell left for the food"),
+    }
+}
+
+// The first food is always on the same cell, unless the spawn region leaves
+// it out. It then goes to a random cell of the region the snake and the
+// level's portals don't start on.
+fn spawn_food(
+    mut commands: Commands,
+    snake_skin: Res<SnakeSkin>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    spawn_region: Res<FoodSpawnRegion>,
+    start_direction: Res<StartDirection>,
+    levels: Res<Levels>,
+    mut test_mode: ResMut<TestMode>,
+) {
+    let (from, to) = spawn_region.bounds(*game_mode, &grid);
+    let in_region = |(x, y): (i8, i8)| x >= from.0 && x <= to.0 && y >= from.1 && y <= to.1;
+
+    let (x, y) = if in_region(FIRST_FOOD_CELL) {
+        FIRST_FOOD_CELL
+    } else {
+        let body = StartDirection::BODY_POSITION;
+        let head = start_direction.head_position();
+        let mut occupied = HashSet::from([(body.x, body.y), (head.x, head.y)]);
+        for (a, b) in levels.current().portals.iter() {
+            occupied.insert(*a);
+            occupied.insert(*b);
+        }
+        random_free_cell(&mut test_mode, (from, to), &occupied).unwrap_or(FIRST_FOOD_CELL)
+    };
+
+    let position = Position { x, y };
+    let screen_position = grid_to_screen_transform(&position, *grid_origin);
+    commands.spawn((
+        Food(FoodKind::Normal),
+        snake_skin.sprite(FOOD_INDEX),
+        position,
+        screen_position,
+    ));
+}
+
+fn spawn_borders(
+    mut commands: Commands,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    border_thickness: Res<BorderThickness>,
+    snake_skin: Res<SnakeSkin>,
+) {
+    let border = border_cells(&grid, *border_thickness);
+
+    border.into_iter().for_each(|(x, y)| {
+        let pos = Position { x, y };
+        let screen_pos = grid_to_screen_transform(&pos, *grid_origin);
+        commands.spawn((
+            BorderSegment,
+            pos,
+            screen_pos,
+            border_sprite((x, y), &grid, *border_thickness, &snake_skin),
+        ));
+    });
+}
+
+// Corner piece for the four corners of the outermost ring, mirrored to face
+// outwards, and the straight piece everywhere else. Inner rings are covered
+// by the outer one, so they're all straight.
+fn border_sprite(
+    (x, y): (i8, i8),
+    grid: &GridConfig,
+    thickness: BorderThickness,
+    snake_skin: &SnakeSkin,
+) -> Sprite {
+    let ring = thickness.0 as i8;
+    let left = x == grid.from.0 - ring;
+    let right = x == grid.to.0 + ring;
+    let top = y == grid.to.1 + ring;
+    let bottom = y == grid.from.1 - ring;
+
+    if (left || right) && (top || bottom) {
+        let mut sprite = snake_skin.sprite(BORDER_CORNER_INDEX);
+        sprite.flip_x = right;
+        sprite.flip_y = bottom;
+        sprite
+    } else {
+        snake_skin.sprite(BORDER_STRAIGHT_INDEX)
+    }
+}
+
+// Every game starts on a freshly built field, so nothing from the previous
+// run (or an outdated board size) is left behind
+fn despawn_board(
+    mut commands: Commands,
+    board_query: Query<Entity, Or<(With<Tilemap>, With<BorderSegment>, With<Food>)>>,
+) {
+    for entity in board_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// Without deaths the run only ends when the player asks for it
+fn end_peaceful_game(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(key_bindings.end_game) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn update_peaceful_text(
+    peaceful: Res<Peaceful>,
+    key_bindings: Res<KeyBindings>,
+    game_state: Res<State<GameState>>,
+    strings: Res<Strings>,
+    mut query: Query<(&mut Text, &mut Visibility), With<PeacefulText>>,
+) {
+    let Ok((mut text, mut visibility)) = query.single_mut() else {
+        return;
+    };
+
+    let in_run = matches!(game_state.get(), GameState::InGame | GameState::Paused);
+    *visibility = if in_run && peaceful.0 {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+    text.0 = strings.format(
+        MessageId::PeacefulHint,
+        &[("key", &key_label(key_bindings.end_game))],
+    );
+}
+
+// Endless and peaceful games have no walls, so the border is hidden for their duration
+fn apply_border_visibility(
+    game_mode: Res<GameMode>,
+    peaceful: Res<Peaceful>,
+    mut border_query: Query<&mut Visibility, With<BorderSegment>>,
+) {
+    let visibility = if *game_mode == GameMode::Endless || peaceful.0 {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+
+    for mut border_visibility in border_query.iter_mut() {
+        *border_visibility = visibility;
+    }
+}
+
+fn reset_score(
+    mut score: ResMut<Score>,
+    mut displayed_score: ResMut<DisplayedScore>,
+    mut run_stats: ResMut<RunStats>,
+    strings: Res<Strings>,
+    mut score_text_query: Query<&mut Text2d, With<ScoreText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    score.0 = 0;
+    displayed_score.0 = 0.0;
+    *run_stats = RunStats::default();
+    update_score_text(score.0, &strings, &mut score_text_query);
+    next_state.set(GameState::InGame);
+}
+
+// Rings of cells around the field, innermost first, each corner once
+fn border_cells(grid: &GridConfig, thickness: BorderThickness) -> Vec<(i8, i8)> {
+    let mut border = Vec::new();
+
+    for ring in 1..=thickness.0 as i8 {
+        // Horizontal borders (top and bottom), corners included
+        for x in (grid.from.0 - ring)..=(grid.to.0 + ring) {
+            border.push((x, grid.to.1 + ring)); // Top border
+            border.push((x, grid.from.1 - ring)); // Bottom border
+        }
+
+        // Vertical borders (left and right) between the corners
+        for y in (grid.from.1 - ring + 1)..=(grid.to.1 + ring - 1) {
+            border.push((grid.from.0 - ring, y)); // Left border
+            border.push((grid.to.0 + ring, y)); // Right border
+        }
+    }
+
+    border
+}
+
+fn setup_tilemap_simple(
+    mut commands: Commands,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    snake_skin: Res<SnakeSkin>,
+    texture_atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+) {
+    // Перевіряємо чи завантажився atlas layout
+    let Some(atlas_layout) = texture_atlas_layouts.get(&game_assets.texture_atlas_layout) else {
+        println!("TextureAtlasLayout not loaded yet");
+        return;
+    };
+
+    create_tilemap_mesh(
+        &mut commands,
+        &grid,
+        *grid_origin,
+        &mut meshes,
+        &mut materials,
+        atlas_layout,
+        &snake_skin.current().texture,
+    );
+}
+
+fn create_tilemap_mesh(
+    commands: &mut Commands,
+    grid: &GridConfig,
+    grid_origin: GridOrigin,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    atlas_layout: &TextureAtlasLayout,
+    texture_handle: &Handle<Image>,
+) {
+    let map_width = grid.width();
+    let map_height = grid.height();
+    let tile_size = 8.0;
+
+    let mut vertices = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+
+    for y in 0..map_height {
+        for x in 0..map_width {
+            let vertex_index = vertices.len() as u32;
+
+            let x_pos = x as f32 * tile_size;
+            let y_pos = y as f32 * tile_size;
+
+            vertices.extend([
+                [x_pos, y_pos, 0.0],
+                [x_pos + tile_size, y_pos, 0.0],
+                [x_pos + tile_size, y_pos + tile_size, 0.0],
+                [x_pos, y_pos + tile_size, 0.0],
+            ]);
+
+            // Використовуємо тайл підлоги (або можете зробити рандомний)
+            let tile_index = FLOOR_INDEX;
+
+            // Перевіряємо чи існує тайл з таким індексом
+            if tile_index < atlas_layout.textures.len() {
+                let tile_rect = atlas_layout.textures[tile_index];
+
+                let atlas_size = atlas_layout.size.as_vec2();
+                let u_min = tile_rect.min.x as f32 / atlas_size.x;
+                let u_max = tile_rect.max.x as f32 / atlas_size.x;
+                let v_min = tile_rect.min.y as f32 / atlas_size.y;
+                let v_max = tile_rect.max.y as f32 / atlas_size.y;
+
+                uvs.extend([
+                    [u_min, v_max],
+                    [u_max, v_max],
+                    [u_max, v_min],
+                    [u_min, v_min],
+                ]);
+            } else {
+                println!("Sprite index out of range");
+            }
+
+            indices.extend([
+                vertex_index,
+                vertex_index + 1,
+                vertex_index + 2,
+                vertex_index,
+                vertex_index + 2,
+                vertex_index + 3,
+            ]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+
+    let mesh_handle = meshes.add(mesh);
+    let material_handle = materials.add(ColorMaterial::from(texture_handle.clone()));
+
+    commands.spawn((
+        Mesh2d(mesh_handle),
+        MeshMaterial2d(material_handle),
+        // The mesh starts at the outer corner of the field's bottom-left cell
+        Transform::from_translation(
+            grid_to_screen_position(
+                &Position {
+                    x: grid.from.0,
+                    y: grid.from.1,
+                },
+                grid_origin,
+            ) - Vec3::new(tile_size / 2.0, tile_size / 2.0, 10.0), // Далеко позаду всіх інших об'єктів
+        ),
+        Tilemap,
+    ));
+
+    println!("Tilemap created successfully!");
+}
+
+// Exponential approach: big jumps close quickly, and the value snaps to the
+// exact score once it's close enough.
+fn tween_score_text(
+    time: Res<Time>,
+    score: Res<Score>,
+    mut displayed_score: ResMut<DisplayedScore>,
+    strings: Res<Strings>,
+    mut score_text_query: Query<&mut Text2d, With<ScoreText>>,
+) {
+    let target = score.0 as f32;
+    if displayed_score.0 == target {
+        return;
+    }
+
+    let step = 1.0 - (-SCORE_TWEEN_RATE * time.delta_secs()).exp();
+    displayed_score.0 += (target - displayed_score.0) * step;
+    if (target - displayed_score.0).abs() < 0.05 {
+        displayed_score.0 = target;
+    }
+
+    update_score_text(
+        displayed_score.0.round() as usize,
+        &strings,
+        &mut score_text_query,
+    );
+}
+
+fn track_run_stats(
+    time: Res<Time>,
+    body_query: Query<(), With<BodySegment>>,
+    mut run_stats: ResMut<RunStats>,
+) {
+    run_stats.time_survived += time.delta_secs();
+    run_stats.length = body_query.iter().count() + 1;
+}
+
+fn update_score_text(
+    score: usize,
+    strings: &Strings,
+    score_text_query: &mut Query<&mut Text2d, With<ScoreText>>,
+) {
+    if let Ok(mut score_text) = score_text_query.single_mut() {
+        score_text.0 = strings.format(MessageId::Score, &[("score", &score)]);
+    }
+}
+
+// The whole game as one plugin, so another Bevy app can embed it:
+//
+//     App::new()
+//         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+//         .add_plugins(SnakeGamePlugin::default())
+//         .run();
+//
+// The host provides DefaultPlugins or an equivalent set: windowing, input,
+// rendering, text, states and an asset server reading the game's `assets/`
+// folder. Nearest filtering keeps the pixel art sharp. The game spawns its own
+// 2D camera, so the host shouldn't add another one. Settings, scores and saves
+// are written next to the executable.
+#[derive(Default)]
+pub struct SnakeGamePlugin {
+    test_mode: bool,
+    demo_gallery: bool,
+}
+
+impl SnakeGamePlugin {
+    // Picks up `--test-mode` (with the test_mode feature) and `--gallery`
+    pub fn from_args(args: &[String]) -> Self {
+        #[cfg(feature = "test_mode")]
+        let test_mode = args.iter().any(|arg| arg == test_mode::TEST_MODE_FLAG);
+        #[cfg(not(feature = "test_mode"))]
+        let test_mode = false;
+
+        Self {
+            test_mode,
+            demo_gallery: args.iter().any(|arg| arg == snake::GALLERY_FLAG),
+        }
+    }
+}
+
+impl Plugin for SnakeGamePlugin {
+    fn build(&self, app: &mut App) {
+        // Two attract demos side by side in one window, e.g. for a showcase
+        let demo_gallery = if self.demo_gallery {
+            snake::DemoGallery::side_by_side()
+        } else {
+            snake::DemoGallery::default()
+        };
+
+        // Kept when the host already set up its own window config
+        app.init_resource::<WindowConfig>()
+            .insert_resource(TestMode::new(self.test_mode))
+            .insert_resource(demo_gallery)
+            .add_plugins(window_config::WindowConfigPlugin)
+            .add_plugins(strings::StringsPlugin)
+            .add_plugins(assets_loader::AssetsLoaderPlugin)
+            .add_plugins(snake::SnakePlugin)
+            .add_plugins(pause::GamePausePlugin)
+            .add_plugins(game_over::GameOverPlugin)
+            .add_plugins(heatmap::HeatmapPlugin)
+            .add_plugins(ghost::GhostPlugin)
+            .add_plugins(lives::LivesPlugin)
+            .add_plugins(death_replay::DeathReplayPlugin)
+            .add_plugins(menu::MenuPlugin)
+            .add_plugins(menu_nav::MenuNavPlugin)
+            .add_plugins(timed_mode::TimedModePlugin)
+            .add_plugins(settings::SettingsPlugin)
+            .add_plugins(leaderboard::LeaderboardPlugin)
+            .add_plugins(showcase::ShowcasePlugin)
+            .add_plugins(saved_game::SavedGamePlugin)
+            .add_plugins(levels::LevelsPlugin)
+            .add_plugins(portals::PortalsPlugin)
+            .add_plugins(speed_tiles::SpeedTilesPlugin)
+            .add_plugins(controls::ControlsPlugin)
+            .add_plugins(controls_overlay::ControlsOverlayPlugin)
+            .add_plugins(assist::AssistPlugin)
+            .add_plugins(debug::DebugPlugin)
+            .add_plugins(console::ConsolePlugin)
+            .add_plugins(effects::EffectsPlugin)
+            .add_plugins(food_bob::FoodBobPlugin)
+            .add_plugins(food_chain::FoodChainPlugin)
+            .add_plugins(food_pop::FoodPopPlugin)
+            .add_plugins(camera::CameraPlugin)
+            .add_plugins(obstacles::ObstaclesPlugin)
+            .add_plugins(achievements::AchievementsPlugin)
+            .add_plugins(survival::SurvivalPlugin)
+            .add_plugins(game_config::GameConfigPlugin)
+            // Leaderboard integrations plug in here:
+            // .insert_resource(score_reporter::ActiveReporter::new(MyReporter))
+            .add_plugins(score_reporter::ScoreReporterPlugin)
+            .add_plugins(metrics::MetricsPlugin)
+            .add_plugins(music::MusicPlugin)
+            .add_plugins(challenge::ChallengePlugin)
+            .init_state::<GameState>()
+            .configure_sets(
+                Update,
+                (
+                    GameplaySet::Movement,
+                    GameplaySet::Collision,
+                    GameplaySet::Crash,
+                )
+                    .chain(),
+            )
+            .add_event::<CrashEvent>()
+            .add_event::<GameOverEvent>()
+            .add_event::<SnakeAteEvent>()
+            .init_resource::<Score>()
+            .init_resource::<DisplayedScore>()
+            .init_resource::<GameMode>()
+            .init_resource::<Peaceful>()
+            .init_resource::<FoodValues>()
+            .init_resource::<FoodSpawnRegion>()
+            .init_resource::<GridOrigin>()
+            .init_resource::<RunStats>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                OnEnter(GameState::StartGame),
+                (
+                    reset_score,
+                    (
+                        game_config::fit_grid_origin,
+                        despawn_board,
+                        setup_tilemap_simple,
+                        spawn_borders,
+                        spawn_food,
+                        apply_border_visibility,
+                    )
+                        .chain(),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    check_border_collision
+                        .run_if(not(resource_equals(GameMode::Endless)))
+                        .run_if(resource_equals(Peaceful(false)))
+                        .run_if(lives::head_vulnerable),
+                    check_food_collision,
+                    track_run_stats,
+                )
+                    .chain()
+                    .in_set(GameplaySet::Collision)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                move_food_off_snake
+                    .before(GameplaySet::Movement)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, (update_fps, tween_score_text))
+            .add_systems(
+                Update,
+                place_score_text.run_if(resource_changed::<GridOrigin>),
+            )
+            .add_systems(
+                Update,
+                update_peaceful_text.run_if(
+                    resource_changed::<Peaceful>
+                        .or(resource_changed::<KeyBindings>)
+                        .or(state_changed::<GameState>),
+                ),
+            )
+            .add_systems(
+                Update,
+                end_peaceful_game
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(resource_equals(Peaceful(true))),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn rectangular_border_covers_the_perimeter() {
+        let grid = GridConfig::walled(20, 10, 1);
+        assert_eq!((grid.width(), grid.height()), (20, 10));
+
+        let border = border_cells(&grid, BorderThickness(1));
+        // One ring around 20x10 cells, corners included
+        assert_eq!(border.len(), 2 * (20 + 10) + 4);
+        assert_eq!(border.iter().collect::<HashSet<_>>().len(), border.len());
+        for &(x, y) in &border {
+            assert!(
+                !grid.contains(&Position { x, y }),
+                "({x}, {y}) is inside the field"
+            );
+        }
+    }
+
+    #[test]
+    fn thick_rectangular_border_covers_every_ring() {
+        let grid = GridConfig::walled(20, 10, 2);
+        // The second ring takes the outermost cells of the 20x10 board
+        assert_eq!((grid.width(), grid.height()), (18, 8));
+
+        let border = border_cells(&grid, BorderThickness(2));
+        assert_eq!(border.len(), (2 * (18 + 8) + 4) + (2 * (20 + 10) + 4));
+    }
+
+    // Corner pieces of the border by cell, with their flips
+    fn border_corners(
+        grid: &GridConfig,
+        thickness: BorderThickness,
+    ) -> Vec<((i8, i8), bool, bool)> {
+        let snake_skin = snake::bench_skin();
+        let mut corners: Vec<_> = border_cells(grid, thickness)
+            .into_iter()
+            .filter_map(|cell| {
+                let sprite = border_sprite(cell, grid, thickness, &snake_skin);
+                let index = sprite.texture_atlas.as_ref().unwrap().index;
+                if index == BORDER_CORNER_INDEX {
+                    Some((cell, sprite.flip_x, sprite.flip_y))
+                } else {
+                    assert_eq!(index, BORDER_STRAIGHT_INDEX, "{:?}", cell);
+                    assert!(!sprite.flip_x && !sprite.flip_y, "{:?}", cell);
+                    None
+                }
+            })
+            .collect();
+        corners.sort();
+        corners
+    }
+
+    #[test]
+    fn border_corners_face_outwards() {
+        let grid = GridConfig::walled(20, 10, 1);
+        let (left, right) = (grid.from.0 - 1, grid.to.0 + 1);
+        let (bottom, top) = (grid.from.1 - 1, grid.to.1 + 1);
+        assert_eq!(
+            border_corners(&grid, BorderThickness(1)),
+            [
+                ((left, bottom), false, true),
+                ((left, top), false, false),
+                ((right, bottom), true, true),
+                ((right, top), true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_the_outer_ring_has_corners() {
+        let grid = GridConfig::walled(20, 10, 2);
+        let corners = border_corners(&grid, BorderThickness(2));
+        let cells: Vec<_> = corners.iter().map(|(cell, _, _)| *cell).collect();
+        assert_eq!(
+            cells,
+            [
+                (grid.from.0 - 2, grid.from.1 - 2),
+                (grid.from.0 - 2, grid.to.1 + 2),
+                (grid.to.0 + 2, grid.from.1 - 2),
+                (grid.to.0 + 2, grid.to.1 + 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn food_moves_off_a_snake_spawned_on_it() {
+        let mut world = World::new();
+        world.init_resource::<GameMode>();
+        world.init_resource::<GridConfig>();
+        world.init_resource::<FoodSpawnRegion>();
+        let origin = GridOrigin(Vec2::new(-20.0, 4.0));
+        world.insert_resource(origin);
+        world.insert_resource(TestMode::new(true));
+        // The snake starts right on the first food
+        let (x, y) = FIRST_FOOD_CELL;
+        world.spawn((Head, Position { x, y }));
+        world.spawn((BodySegment, Position { x: x - 1, y }));
+        let food = world
+            .spawn((
+                Food(FoodKind::Normal),
+                Position { x, y },
+                grid_to_screen_transform(&Position { x, y }, origin),
+            ))
+            .id();
+
+        world.run_system_once(move_food_off_snake).unwrap();
+
+        let moved = *world.get::<Position>(food).unwrap();
+        assert!(moved != Position { x, y } && moved != Position { x: x - 1, y });
+        assert!(GridConfig::default().contains(&moved), "{:?}", moved);
+        assert_eq!(
+            world.get::<Transform>(food).unwrap().translation,
+            grid_to_screen_position(&moved, origin)
+        );
+    }
+
+    #[test]
+    fn peaceful_game_ends_on_the_bound_key() {
+        let mut world = World::new();
+        world.insert_resource(KeyBindings {
+            end_game: KeyCode::KeyE,
+            ..KeyBindings::default()
+        });
+        world.init_resource::<NextState<GameState>>();
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::KeyQ);
+        world.insert_resource(keyboard_input);
+
+        world.run_system_once(end_peaceful_game).unwrap();
+        assert!(matches!(
+            world.resource::<NextState<GameState>>(),
+            NextState::Unchanged
+        ));
+
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyE);
+        world.run_system_once(end_peaceful_game).unwrap();
+        assert!(matches!(
+            world.resource::<NextState<GameState>>(),
+            NextState::Pending(GameState::MainMenu)
+        ));
+    }
+
+    #[test]
+    fn three_games_leave_one_board() {
+        let mut app = test_support::headless_app();
+        assert_eq!(test_support::count::<With<Tilemap>>(&mut app), 0);
+
+        for _ in 0..3 {
+            test_support::start_game(&mut app);
+        }
+
+        let grid = *app.world().resource::<GridConfig>();
+        let thickness = *app.world().resource::<BorderThickness>();
+        assert_eq!(test_support::count::<With<Tilemap>>(&mut app), 1);
+        assert_eq!(test_support::count::<With<Food>>(&mut app), 1);
+        assert_eq!(test_support::count::<With<Head>>(&mut app), 1);
+        assert_eq!(
+            test_support::count::<With<BorderSegment>>(&mut app),
+            border_cells(&grid, thickness).len()
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+
+use crate::{
+    CrashEvent, GameOverEvent, GameState, GameplaySet,
+    assets_loader::UsesUiFont,
+    snake::{Forgiving, Head},
+    strings::{MessageId, Strings},
+};
+
+const DEFAULT_LIVES: u8 = 1;
+const DEFAULT_INVULNERABILITY: f32 = 2.0;
+const INVULNERABLE_BLINK: f32 = 0.1;
+const INVULNERABLE_ALPHA: f32 = 0.3;
+
+pub struct LivesPlugin;
+
+impl Plugin for LivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LivesConfig>();
+        app.init_resource::<Lives>();
+        app.add_event::<SnakeRespawnEvent>();
+        app.add_systems(Startup, init_lives_text);
+        app.add_systems(OnEnter(GameState::StartGame), reset_lives);
+        app.add_systems(
+            Update,
+            (
+                start_invulnerability.before(GameplaySet::Movement),
+                tick_invulnerability.before(GameplaySet::Movement),
+                handle_crashes
+                    .in_set(GameplaySet::Crash)
+                    .run_if(resource_equals(Forgiving(false))),
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            update_lives_text.run_if(
+                resource_changed::<Lives>
+                    .or(resource_changed::<LivesConfig>)
+                    .or(resource_changed::<Forgiving>)
+                    .or(state_changed::<GameState>),
+            ),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct LivesConfig {
+    // Crashes a run can take, 1 ends it on the first one
+    pub lives: u8,
+    // Seconds after a respawn in which the head passes through walls and body
+    pub invulnerability: f32,
+}
+
+impl Default for LivesConfig {
+    fn default() -> Self {
+        Self {
+            lives: DEFAULT_LIVES,
+            invulnerability: DEFAULT_INVULNERABILITY,
+        }
+    }
+}
+
+// Lives left in the current run, the one being played included
+#[derive(Resource)]
+pub struct Lives(pub u8);
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self(DEFAULT_LIVES)
+    }
+}
+
+// Seconds the freshly respawned head has left in which it passes through the
+// border and its own body. Obstacles still count, and the window always runs out.
+#[derive(Component)]
+pub struct Invulnerable(pub f32);
+
+// Run condition for the collisions an invulnerable head ignores
+pub fn head_vulnerable(invulnerable_query: Query<(), (With<Head>, With<Invulnerable>)>) -> bool {
+    invulnerable_query.is_empty()
+}
+
+// A life was lost and the snake starts over from its starting cells
+#[derive(Event)]
+pub struct SnakeRespawnEvent;
+
+#[derive(Component)]
+struct LivesText;
+
+fn reset_lives(config: Res<LivesConfig>, mut lives: ResMut<Lives>) {
+    lives.0 = config.lives;
+}
+
+// The respawned head is spawned after the crash was handled, so it's picked
+// up on the next frame while the respawn event is still readable
+fn start_invulnerability(
+    mut commands: Commands,
+    mut respawn_events: EventReader<SnakeRespawnEvent>,
+    config: Res<LivesConfig>,
+    head_query: Query<Entity, Added<Head>>,
+) {
+    if respawn_events.read().count() == 0 || config.invulnerability <= 0.0 {
+        return;
+    }
+    for entity in head_query.iter() {
+        commands
+            .entity(entity)
+            .insert(Invulnerable(config.invulnerability));
+    }
+}
+
+// Blinks the head's alpha until the window runs out
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut head_query: Query<(Entity, &mut Invulnerable, &mut Sprite), With<Head>>,
+) {
+    for (entity, mut invulnerable, mut sprite) in head_query.iter_mut() {
+        invulnerable.0 -= time.delta_secs();
+        if invulnerable.0 <= 0.0 {
+            sprite.color.set_alpha(1.0);
+            commands.entity(entity).remove::<Invulnerable>();
+            continue;
+        }
+
+        let faded = ((invulnerable.0 / INVULNERABLE_BLINK) as u32).is_multiple_of(2);
+        sprite
+            .color
+            .set_alpha(if faded { INVULNERABLE_ALPHA } else { 1.0 });
+    }
+}
+
+// Several collisions on the same tick still cost a single life
+fn handle_crashes(
+    mut crash_events: EventReader<CrashEvent>,
+    mut lives: ResMut<Lives>,
+    mut respawn_events: EventWriter<SnakeRespawnEvent>,
+    mut game_over_events: EventWriter<GameOverEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(crash) = crash_events.read().next().copied() else {
+        return;
+    };
+    crash_events.clear();
+
+    lives.0 = lives.0.saturating_sub(1);
+    if lives.0 > 0 {
+        info!("Crashed, {} lives left", lives.0);
+        respawn_events.write(SnakeRespawnEvent);
+        return;
+    }
+
+    info!("Game Over");
+    game_over_events.write(crash.0);
+    next_state.set(GameState::GameOver);
+}
+
+fn init_lives_text(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Right),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        },
+        LivesText,
+        UsesUiFont,
+    ));
+}
+
+// Only shown in games with extra lives, classic and forgiving runs keep the plain HUD
+fn update_lives_text(
+    config: Res<LivesConfig>,
+    forgiving: Res<Forgiving>,
+    lives: Res<Lives>,
+    game_state: Res<State<GameState>>,
+    strings: Res<Strings>,
+    mut query: Query<(&mut Text, &mut Visibility), With<LivesText>>,
+) {
+    let Ok((mut text, mut visibility)) = query.single_mut() else {
+        return;
+    };
+
+    let in_run = matches!(
+        game_state.get(),
+        GameState::InGame | GameState::Paused | GameState::GameOver
+    );
+    *visibility = if in_run && config.lives > 1 && !forgiving.0 {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+    text.0 = strings.format(MessageId::Lives, &[("lives", &lives.0)]);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn crash_world(lives: u8) -> World {
+        let mut world = World::new();
+        world.insert_resource(Lives(lives));
+        world.init_resource::<NextState<GameState>>();
+        world.init_resource::<Events<CrashEvent>>();
+        world.init_resource::<Events<SnakeRespawnEvent>>();
+        world.init_resource::<Events<GameOverEvent>>();
+        world
+    }
+
+    fn crash(world: &mut World, times: usize) {
+        for _ in 0..times {
+            world.send_event(CrashEvent(GameOverEvent::SelfCollision));
+        }
+        world.run_system_once(handle_crashes).unwrap();
+    }
+
+    #[test]
+    fn crashes_on_one_tick_cost_one_life() {
+        let mut world = crash_world(3);
+        crash(&mut world, 2);
+
+        assert_eq!(world.resource::<Lives>().0, 2);
+        assert_eq!(world.resource::<Events<SnakeRespawnEvent>>().len(), 1);
+        assert!(world.resource::<Events<GameOverEvent>>().is_empty());
+        assert!(matches!(
+            world.resource::<NextState<GameState>>(),
+            NextState::Unchanged
+        ));
+    }
+
+    #[test]
+    fn last_life_ends_the_run() {
+        let mut world = crash_world(1);
+        crash(&mut world, 1);
+
+        assert_eq!(world.resource::<Lives>().0, 0);
+        assert!(world.resource::<Events<SnakeRespawnEvent>>().is_empty());
+        let ended: Vec<_> = world
+            .resource::<Events<GameOverEvent>>()
+            .iter_current_update_events()
+            .copied()
+            .collect();
+        assert_eq!(ended, [GameOverEvent::SelfCollision]);
+        assert!(matches!(
+            world.resource::<NextState<GameState>>(),
+            NextState::Pending(GameState::GameOver)
+        ));
+    }
+}
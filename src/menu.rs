@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+
+use crate::{
+    GameMode, GameState, Peaceful,
+    assets_loader::SnakeSkin,
+    challenge::ChallengeInput,
+    controls::{KeyBindings, key_label},
+    levels::Levels,
+    menu_nav::{MenuConfirm, MenuCursor, MenuNavSet},
+    saved_game::{ResumeGameEvent, SavedGame},
+    snake::Forgiving,
+    strings::{MessageId, Strings},
+    timed_mode::TimedModeConfig,
+};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, init_menu);
+        app.add_systems(OnEnter(GameState::MainMenu), show_menu);
+        app.add_systems(OnExit(GameState::MainMenu), hide_menu);
+        app.add_systems(
+            Update,
+            (handle_inputs, update_menu_text)
+                .chain()
+                .after(MenuNavSet)
+                .run_if(in_state(GameState::MainMenu)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuItem {
+    Play(GameMode),
+    Continue,
+    Challenge,
+    Peaceful,
+    Forgiving,
+    Level,
+    Skin,
+    Leaderboard,
+    Settings,
+}
+
+const MENU_ITEMS: [MenuItem; 11] = [
+    MenuItem::Play(GameMode::Classic),
+    MenuItem::Play(GameMode::Timed),
+    MenuItem::Play(GameMode::Endless),
+    MenuItem::Continue,
+    MenuItem::Challenge,
+    MenuItem::Peaceful,
+    MenuItem::Forgiving,
+    MenuItem::Level,
+    MenuItem::Skin,
+    MenuItem::Leaderboard,
+    MenuItem::Settings,
+];
+
+#[derive(Component)]
+struct MenuText;
+
+fn item_label(
+    item: MenuItem,
+    timed_config: &TimedModeConfig,
+    snake_skin: &SnakeSkin,
+    peaceful: &Peaceful,
+    forgiving: &Forgiving,
+    levels: &Levels,
+    saved_game: &SavedGame,
+    challenge_input: &ChallengeInput,
+    key_bindings: &KeyBindings,
+    strings: &Strings,
+) -> String {
+    match item {
+        MenuItem::Play(GameMode::Classic) => strings.get(MessageId::ModeClassic).to_string(),
+        MenuItem::Play(GameMode::Timed) => strings.format(
+            MessageId::ModeTimed,
+            &[("time", &format!("{:.0}", timed_config.duration))],
+        ),
+        MenuItem::Play(GameMode::Endless) => strings.get(MessageId::ModeEndless).to_string(),
+        MenuItem::Continue => match &saved_game.0 {
+            Some(run) => strings.format(MessageId::MenuContinue, &[("score", &run.score)]),
+            None => strings.get(MessageId::MenuContinueEmpty).to_string(),
+        },
+        MenuItem::Challenge if challenge_input.error => {
+            strings.get(MessageId::ChallengeInvalid).to_string()
+        }
+        MenuItem::Challenge if challenge_input.active => strings.format(
+            MessageId::MenuChallengeInput,
+            &[("code", &challenge_input.text)],
+        ),
+        MenuItem::Challenge => strings.get(MessageId::MenuChallenge).to_string(),
+        MenuItem::Peaceful => {
+            let state = if peaceful.0 {
+                strings.get(MessageId::On)
+            } else {
+                strings.get(MessageId::Off)
+            };
+            strings.format(
+                MessageId::MenuPeaceful,
+                &[
+                    ("state", &state),
+                    ("key", &key_label(key_bindings.end_game)),
+                ],
+            )
+        }
+        MenuItem::Forgiving => {
+            let state = if forgiving.0 {
+                strings.get(MessageId::On)
+            } else {
+                strings.get(MessageId::Off)
+            };
+            strings.format(MessageId::MenuForgiving, &[("state", &state)])
+        }
+        MenuItem::Level => {
+            strings.format(MessageId::MenuLevel, &[("level", &levels.current().name)])
+        }
+        MenuItem::Skin => {
+            strings.format(MessageId::MenuSkin, &[("skin", &snake_skin.current().name)])
+        }
+        MenuItem::Leaderboard => strings.get(MessageId::MenuLeaderboard).to_string(),
+        MenuItem::Settings => strings.get(MessageId::MenuSettings).to_string(),
+    }
+}
+
+fn menu_text(
+    cursor: &MenuCursor,
+    timed_config: &TimedModeConfig,
+    snake_skin: &SnakeSkin,
+    peaceful: &Peaceful,
+    forgiving: &Forgiving,
+    levels: &Levels,
+    saved_game: &SavedGame,
+    challenge_input: &ChallengeInput,
+    key_bindings: &KeyBindings,
+    strings: &Strings,
+) -> String {
+    let mut text = format!("{}\n\n", strings.get(MessageId::MenuTitle));
+    for (idx, item) in MENU_ITEMS.iter().enumerate() {
+        text.push_str(&format!(
+            "{}{}\n",
+            cursor.marker(idx),
+            item_label(
+                *item,
+                timed_config,
+                snake_skin,
+                peaceful,
+                forgiving,
+                levels,
+                saved_game,
+                challenge_input,
+                key_bindings,
+                strings
+            )
+        ));
+    }
+    text.push_str(&format!("\n{}", strings.get(MessageId::MenuStart)));
+    text
+}
+
+fn handle_inputs(
+    mut confirm_events: EventReader<MenuConfirm>,
+    menu_query: Query<(), With<MenuText>>,
+    mut game_mode: ResMut<GameMode>,
+    mut snake_skin: ResMut<SnakeSkin>,
+    mut peaceful: ResMut<Peaceful>,
+    mut forgiving: ResMut<Forgiving>,
+    mut levels: ResMut<Levels>,
+    mut challenge_input: ResMut<ChallengeInput>,
+    mut resume_events: EventWriter<ResumeGameEvent>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for event in confirm_events.read() {
+        if !menu_query.contains(event.entity) {
+            continue;
+        }
+        match MENU_ITEMS[event.index] {
+            MenuItem::Play(mode) => {
+                *game_mode = mode;
+                game_state.set(GameState::StartGame);
+            }
+            MenuItem::Continue => {
+                resume_events.write(ResumeGameEvent);
+            }
+            MenuItem::Challenge => challenge_input.start(),
+            MenuItem::Peaceful => peaceful.0 = !peaceful.0,
+            MenuItem::Forgiving => forgiving.0 = !forgiving.0,
+            MenuItem::Level => levels.select_next(),
+            MenuItem::Skin => snake_skin.select_next(),
+            MenuItem::Leaderboard => game_state.set(GameState::Leaderboard),
+            MenuItem::Settings => game_state.set(GameState::Settings),
+        }
+    }
+}
+
+fn init_menu(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        MenuText,
+        MenuCursor::new(MENU_ITEMS.len()),
+    ));
+}
+
+fn update_menu_text(
+    timed_config: Res<TimedModeConfig>,
+    snake_skin: Res<SnakeSkin>,
+    peaceful: Res<Peaceful>,
+    forgiving: Res<Forgiving>,
+    levels: Res<Levels>,
+    saved_game: Res<SavedGame>,
+    challenge_input: Res<ChallengeInput>,
+    key_bindings: Res<KeyBindings>,
+    strings: Res<Strings>,
+    mut query: Query<(&mut MenuCursor, &mut Text), With<MenuText>>,
+) {
+    let Ok((mut cursor, mut text)) = query.single_mut() else {
+        return;
+    };
+    // The cursor stays on the code while it's typed
+    if cursor.locked != challenge_input.active {
+        cursor.locked = challenge_input.active;
+    }
+    if !cursor.is_changed()
+        && !timed_config.is_changed()
+        && !snake_skin.is_changed()
+        && !peaceful.is_changed()
+        && !forgiving.is_changed()
+        && !levels.is_changed()
+        && !saved_game.is_changed()
+        && !challenge_input.is_changed()
+        && !key_bindings.is_changed()
+    {
+        return;
+    }
+    text.0 = menu_text(
+        &cursor,
+        &timed_config,
+        &snake_skin,
+        &peaceful,
+        &forgiving,
+        &levels,
+        &saved_game,
+        &challenge_input,
+        &key_bindings,
+        &strings,
+    );
+}
+
+fn show_menu(
+    timed_config: Res<TimedModeConfig>,
+    snake_skin: Res<SnakeSkin>,
+    peaceful: Res<Peaceful>,
+    forgiving: Res<Forgiving>,
+    levels: Res<Levels>,
+    saved_game: Res<SavedGame>,
+    challenge_input: Res<ChallengeInput>,
+    key_bindings: Res<KeyBindings>,
+    strings: Res<Strings>,
+    mut query: Query<(&MenuCursor, &mut Visibility, &mut Text), With<MenuText>>,
+) {
+    if let Ok((cursor, mut visibility, mut text)) = query.single_mut() {
+        *visibility = Visibility::Visible;
+        text.0 = menu_text(
+            cursor,
+            &timed_config,
+            &snake_skin,
+            &peaceful,
+            &forgiving,
+            &levels,
+            &saved_game,
+            &challenge_input,
+            &key_bindings,
+            &strings,
+        );
+    }
+}
+
+fn hide_menu(mut query: Query<&mut Visibility, With<MenuText>>) {
+    if let Ok(mut visibility) = query.single_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
@@ -0,0 +1,305 @@
+use bevy::prelude::*;
+
+const DEFAULT_REPEAT_DELAY: f32 = 0.4;
+const DEFAULT_REPEAT_INTERVAL: f32 = 0.08;
+
+// Shared up/down selection for text screens. A screen puts a `MenuCursor` on
+// its text entity and reads `MenuConfirm` for that entity after `MenuNavSet`.
+pub struct MenuNavPlugin;
+
+impl Plugin for MenuNavPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyRepeat>();
+        app.init_resource::<HeldNavKey>();
+        app.add_event::<MenuConfirm>();
+        app.add_systems(Update, navigate_menus.in_set(MenuNavSet));
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MenuNavSet;
+
+// Selected row of a list with `len` items. Only moves while the entity is
+// visible and not locked, e.g. while a screen waits for a key to rebind.
+#[derive(Component)]
+pub struct MenuCursor {
+    pub selected: usize,
+    pub len: usize,
+    pub locked: bool,
+}
+
+impl MenuCursor {
+    pub fn new(len: usize) -> Self {
+        Self {
+            selected: 0,
+            len,
+            locked: false,
+        }
+    }
+
+    // Prefix drawn in front of each row
+    pub fn marker(&self, idx: usize) -> &'static str {
+        if idx == self.selected { "> " } else { "  " }
+    }
+
+    fn move_up(&mut self) {
+        self.selected = (self.selected + self.len - 1) % self.len;
+    }
+
+    fn move_down(&mut self) {
+        self.selected = (self.selected + 1) % self.len;
+    }
+}
+
+// Holding up or down moves the cursor once, then again after `delay` seconds
+// and every `interval` seconds from there, like a text field's key repeat
+#[derive(Resource)]
+pub struct KeyRepeat {
+    pub delay: f32,
+    pub interval: f32,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            delay: DEFAULT_REPEAT_DELAY,
+            interval: DEFAULT_REPEAT_INTERVAL,
+        }
+    }
+}
+
+impl KeyRepeat {
+    // Repeats due while a key held for `before` seconds is now held for `now`,
+    // the press itself not counted
+    fn repeats_between(&self, before: f32, now: f32) -> usize {
+        self.repeats_until(now) - self.repeats_until(before)
+    }
+
+    fn repeats_until(&self, held: f32) -> usize {
+        if held < self.delay {
+            0
+        } else {
+            ((held - self.delay) / self.interval) as usize + 1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NavKey {
+    Up,
+    Down,
+}
+
+// The navigation key being held and for how long, in seconds
+#[derive(Resource, Default)]
+struct HeldNavKey(Option<(NavKey, f32)>);
+
+// Enter was pressed on `index` of the cursor on `entity`
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MenuConfirm {
+    pub entity: Entity,
+    pub index: usize,
+}
+
+// A new press takes over from the key held before it
+fn held_nav_key(
+    keyboard_input: &ButtonInput<KeyCode>,
+    key_repeat: &KeyRepeat,
+    held: &mut HeldNavKey,
+    delta: f32,
+) -> Option<(NavKey, usize)> {
+    let up = [KeyCode::KeyW, KeyCode::ArrowUp];
+    let down = [KeyCode::KeyS, KeyCode::ArrowDown];
+
+    if keyboard_input.any_just_pressed(up) {
+        held.0 = Some((NavKey::Up, 0.0));
+        return Some((NavKey::Up, 1));
+    }
+    if keyboard_input.any_just_pressed(down) {
+        held.0 = Some((NavKey::Down, 0.0));
+        return Some((NavKey::Down, 1));
+    }
+
+    let (key, before) = held.0?;
+    let still_held = match key {
+        NavKey::Up => keyboard_input.any_pressed(up),
+        NavKey::Down => keyboard_input.any_pressed(down),
+    };
+    if !still_held {
+        held.0 = None;
+        return None;
+    }
+    let now = before + delta;
+    held.0 = Some((key, now));
+    Some((key, key_repeat.repeats_between(before, now)))
+}
+
+fn navigate_menus(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_repeat: Res<KeyRepeat>,
+    mut held: ResMut<HeldNavKey>,
+    mut cursor_query: Query<(Entity, &mut MenuCursor, &Visibility)>,
+    mut confirm_events: EventWriter<MenuConfirm>,
+) {
+    let moves = held_nav_key(&keyboard_input, &key_repeat, &mut held, time.delta_secs());
+    let confirm = keyboard_input.just_pressed(KeyCode::Enter);
+
+    for (entity, mut cursor, visibility) in cursor_query.iter_mut() {
+        if *visibility != Visibility::Visible || cursor.locked || cursor.len == 0 {
+            continue;
+        }
+
+        if let Some((key, count)) = moves {
+            for _ in 0..count {
+                match key {
+                    NavKey::Up => cursor.move_up(),
+                    NavKey::Down => cursor.move_down(),
+                }
+            }
+        }
+
+        if confirm {
+            confirm_events.write(MenuConfirm {
+                entity,
+                index: cursor.selected,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn nav_app(len: usize) -> (App, Entity) {
+        let mut app = App::new();
+        app.init_resource::<Time>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.add_plugins(MenuNavPlugin);
+        let cursor = app
+            .world_mut()
+            .spawn((MenuCursor::new(len), Visibility::Visible))
+            .id();
+        (app, cursor)
+    }
+
+    // One frame with `key` pressed, released again afterwards
+    fn tap(app: &mut App, key: KeyCode) {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(key);
+        app.update();
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.release(key);
+        input.clear();
+    }
+
+    // One frame `seconds` long with whatever is pressed still held
+    fn hold_for(app: &mut App, seconds: f32) {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .clear();
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    fn selected(app: &App, cursor: Entity) -> usize {
+        app.world().get::<MenuCursor>(cursor).unwrap().selected
+    }
+
+    #[test]
+    fn cursor_wraps_around_three_items_and_confirms() {
+        let (mut app, cursor) = nav_app(3);
+
+        let mut visited = Vec::new();
+        for key in [KeyCode::ArrowDown, KeyCode::KeyS, KeyCode::ArrowDown] {
+            tap(&mut app, key);
+            visited.push(selected(&app, cursor));
+        }
+        tap(&mut app, KeyCode::ArrowUp);
+        visited.push(selected(&app, cursor));
+        assert_eq!(visited, [1, 2, 0, 2]);
+
+        tap(&mut app, KeyCode::Enter);
+        let events = app.world().resource::<Events<MenuConfirm>>();
+        let confirmed: Vec<_> = events.iter_current_update_events().collect();
+        assert_eq!(confirmed.len(), 1);
+        assert_eq!((confirmed[0].entity, confirmed[0].index), (cursor, 2));
+    }
+
+    #[test]
+    fn hidden_or_locked_cursor_stays_put() {
+        let (mut app, cursor) = nav_app(3);
+        app.world_mut()
+            .entity_mut(cursor)
+            .insert(Visibility::Hidden);
+        tap(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected(&app, cursor), 0);
+
+        app.world_mut()
+            .entity_mut(cursor)
+            .insert(Visibility::Visible);
+        app.world_mut()
+            .get_mut::<MenuCursor>(cursor)
+            .unwrap()
+            .locked = true;
+        tap(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected(&app, cursor), 0);
+    }
+
+    #[test]
+    fn held_key_repeats_after_the_delay() {
+        let (mut app, cursor) = nav_app(10);
+        app.insert_resource(KeyRepeat {
+            delay: 0.5,
+            interval: 0.25,
+        });
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::ArrowDown);
+        app.update();
+        assert_eq!(selected(&app, cursor), 1, "the press moves right away");
+
+        for _ in 0..3 {
+            hold_for(&mut app, 0.125);
+        }
+        assert_eq!(selected(&app, cursor), 1, "no repeat before the delay");
+
+        hold_for(&mut app, 0.125);
+        assert_eq!(selected(&app, cursor), 2, "first repeat at the delay");
+
+        // Held for a second in total: repeats at 0.5, 0.75 and 1.0
+        for _ in 0..4 {
+            hold_for(&mut app, 0.125);
+        }
+        assert_eq!(selected(&app, cursor), 4);
+
+        // A long frame catches up on every repeat it covered
+        hold_for(&mut app, 0.5);
+        assert_eq!(selected(&app, cursor), 6);
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .release(KeyCode::ArrowDown);
+        hold_for(&mut app, 1.0);
+        assert_eq!(selected(&app, cursor), 6, "releasing stops the repeat");
+    }
+
+    #[test]
+    fn repeats_count_from_the_delay() {
+        let key_repeat = KeyRepeat {
+            delay: 0.5,
+            interval: 0.25,
+        };
+        assert_eq!(key_repeat.repeats_between(0.0, 0.49), 0);
+        assert_eq!(key_repeat.repeats_between(0.0, 0.5), 1);
+        assert_eq!(key_repeat.repeats_between(0.5, 0.74), 0);
+        assert_eq!(key_repeat.repeats_between(0.5, 1.0), 2);
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{CrashEvent, GameOverEvent, RunStats, SnakeAteEvent};
+
+// Counters over every run of the session, logged when the game closes. Set
+// RUST_LOG=bv_demo_project=debug to also see each crash and score change.
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMetrics>();
+        app.add_systems(
+            Update,
+            (
+                count_crashes.run_if(on_event::<CrashEvent>),
+                count_foods.run_if(on_event::<SnakeAteEvent>),
+                count_finished_runs.run_if(on_event::<GameOverEvent>),
+            ),
+        );
+        app.add_systems(Last, log_metrics.run_if(on_event::<AppExit>));
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct GameMetrics {
+    // Crashes by what the head ran into, lost lives and rewinds included
+    pub deaths: HashMap<GameOverEvent, usize>,
+    pub foods_eaten: usize,
+    // Runs that reached the game over screen, restarts midway don't count
+    pub finished_runs: usize,
+    // Summed length of the snake at the end of each finished run
+    pub total_run_length: usize,
+}
+
+impl GameMetrics {
+    pub fn average_run_length(&self) -> Option<f32> {
+        (self.finished_runs > 0).then(|| self.total_run_length as f32 / self.finished_runs as f32)
+    }
+}
+
+// Several collisions on the same tick count as one crash, like in lives.rs
+fn count_crashes(mut crash_events: EventReader<CrashEvent>, mut metrics: ResMut<GameMetrics>) {
+    let Some(CrashEvent(cause)) = crash_events.read().next().copied() else {
+        return;
+    };
+    crash_events.clear();
+    debug!("Crash: {:?}", cause);
+    *metrics.deaths.entry(cause).or_default() += 1;
+}
+
+fn count_foods(mut ate_events: EventReader<SnakeAteEvent>, mut metrics: ResMut<GameMetrics>) {
+    metrics.foods_eaten += ate_events.read().count();
+}
+
+fn count_finished_runs(
+    mut game_over_events: EventReader<GameOverEvent>,
+    run_stats: Res<RunStats>,
+    mut metrics: ResMut<GameMetrics>,
+) {
+    let Some(cause) = game_over_events.read().last() else {
+        return;
+    };
+    debug!("Run over: {:?}, length {}", cause, run_stats.length);
+    metrics.finished_runs += 1;
+    metrics.total_run_length += run_stats.length;
+}
+
+fn log_metrics(metrics: Res<GameMetrics>) {
+    let mut deaths: Vec<_> = metrics.deaths.iter().collect();
+    deaths.sort_by_key(|(cause, _)| format!("{:?}", cause));
+    info!(
+        "Session metrics: {} finished run(s), {} food(s) eaten, average run length {}, deaths {:?}",
+        metrics.finished_runs,
+        metrics.foods_eaten,
+        metrics
+            .average_run_length()
+            .map_or("-".to_string(), |length| format!("{:.1}", length)),
+        deaths
+    );
+}
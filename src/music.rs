@@ -0,0 +1,96 @@
+use bevy::{
+    audio::{AudioSinkPlayback, Volume},
+    prelude::*,
+};
+
+use crate::{GameState, assets_loader::GameAssets, settings::Settings};
+
+const DEFAULT_MUSIC_VOLUME: f32 = 0.5;
+// Share of the volume left on the pause and game over screens
+const DUCK_FACTOR: f32 = 0.3;
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicConfig>();
+        app.init_resource::<MusicController>();
+        app.add_systems(
+            Update,
+            update_music.run_if(
+                state_changed::<GameState>
+                    .or(resource_changed::<Settings>)
+                    .or(resource_changed::<MusicConfig>),
+            ),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct MusicConfig {
+    // Linear, 1 plays the track as it is
+    pub volume: f32,
+}
+
+impl Default for MusicConfig {
+    fn default() -> Self {
+        Self {
+            volume: DEFAULT_MUSIC_VOLUME,
+        }
+    }
+}
+
+// The entity playing the track while a run is going, so its volume can be
+// changed without restarting it
+#[derive(Resource, Default)]
+pub struct MusicController {
+    pub entity: Option<Entity>,
+}
+
+// Starts with a run, ducks while paused or dead and stops outside of the
+// game. Muting in the settings stops it right away.
+fn update_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    music_config: Res<MusicConfig>,
+    game_assets: Option<Res<GameAssets>>,
+    mut controller: ResMut<MusicController>,
+    mut sink_query: Query<&mut AudioSink>,
+) {
+    let volume = match state.get() {
+        GameState::InGame => music_config.volume,
+        GameState::Paused | GameState::GameOver => music_config.volume * DUCK_FACTOR,
+        // Passed through on every restart, the next run takes it from here
+        GameState::StartGame => return,
+        _ => 0.0,
+    };
+
+    if !settings.music || volume <= 0.0 {
+        if let Some(entity) = controller.entity.take() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    match controller.entity {
+        Some(entity) => {
+            if let Ok(mut sink) = sink_query.get_mut(entity) {
+                sink.set_volume(Volume::Linear(volume));
+            }
+        }
+        None if *state.get() == GameState::InGame => {
+            let Some(music) = game_assets.and_then(|assets| assets.music.clone()) else {
+                return;
+            };
+            let entity = commands
+                .spawn((
+                    AudioPlayer::new(music),
+                    PlaybackSettings::LOOP.with_volume(Volume::Linear(volume)),
+                ))
+                .id();
+            controller.entity = Some(entity);
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::{HashSet, VecDeque};
+
+use bevy::prelude::*;
+
+use crate::{
+    CrashEvent, Food, GameMode, GameOverEvent, GameState, GameplaySet, GridConfig, GridOrigin,
+    Peaceful, Position, Score,
+    assets_loader::{BORDER_INDEX, SnakeSkin},
+    grid_to_screen_transform,
+    portals::Portal,
+    snake::{BodySegment, Head, SnakeErrors},
+    test_mode::TestMode,
+};
+
+const DEFAULT_POINTS_PER_OBSTACLE: usize = 5;
+const DEFAULT_MAX_OBSTACLES: usize = 8;
+// Random cells tried per obstacle before giving up until the next score change
+const PLACEMENT_ATTEMPTS: usize = 64;
+
+pub struct ObstaclesPlugin;
+
+impl Plugin for ObstaclesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ObstacleConfig>();
+        app.add_systems(OnEnter(GameState::StartGame), despawn_obstacles);
+        app.add_systems(
+            Update,
+            (
+                check_obstacle_collision.run_if(resource_equals(Peaceful(false))),
+                spawn_obstacles.run_if(resource_changed::<Score>),
+            )
+                .chain()
+                .in_set(GameplaySet::Collision)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct Obstacle;
+
+#[derive(Resource)]
+pub struct ObstacleConfig {
+    // A new obstacle appears every time the score grows by this much
+    pub points_per_obstacle: usize,
+    // Upper limit so the board never fills up
+    pub max_obstacles: usize,
+}
+
+impl Default for ObstacleConfig {
+    fn default() -> Self {
+        Self {
+            points_per_obstacle: DEFAULT_POINTS_PER_OBSTACLE,
+            max_obstacles: DEFAULT_MAX_OBSTACLES,
+        }
+    }
+}
+
+fn despawn_obstacles(mut commands: Commands, query: Query<Entity, With<Obstacle>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn check_obstacle_collision(
+    head_query: Query<&Position, With<Head>>,
+    obstacle_query: Query<&Position, (With<Obstacle>, Without<Head>)>,
+    mut crash_events: EventWriter<CrashEvent>,
+    mut snake_errors: ResMut<SnakeErrors>,
+) {
+    let head_pos = match head_query.single() {
+        Ok(head_pos) => head_pos,
+        Err(err) => {
+            snake_errors.report("check_obstacle_collision", err);
+            return;
+        }
+    };
+
+    if obstacle_query
+        .iter()
+        .any(|pos| pos.x == head_pos.x && pos.y == head_pos.y)
+    {
+        crash_events.write(CrashEvent(GameOverEvent::ObstacleCollision));
+    }
+}
+
+fn spawn_obstacles(
+    mut commands: Commands,
+    score: Res<Score>,
+    obstacle_config: Res<ObstacleConfig>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    snake_skin: Res<SnakeSkin>,
+    head_query: Query<&Position, With<Head>>,
+    body_query: Query<&Position, With<BodySegment>>,
+    food_query: Query<&Position, With<Food>>,
+    obstacle_query: Query<&Position, With<Obstacle>>,
+    portal_query: Query<&Position, With<Portal>>,
+    mut snake_errors: ResMut<SnakeErrors>,
+    mut test_mode: ResMut<TestMode>,
+) {
+    if obstacle_config.points_per_obstacle == 0 {
+        return;
+    }
+
+    let target = (score.0 / obstacle_config.points_per_obstacle).min(obstacle_config.max_obstacles);
+    let mut count = obstacle_query.iter().count();
+    if count >= target {
+        return;
+    }
+
+    let head = match head_query.single() {
+        Ok(head_pos) => (head_pos.x, head_pos.y),
+        Err(err) => {
+            snake_errors.report("spawn_obstacles", err);
+            return;
+        }
+    };
+    let food = food_query.single().ok().map(|pos| (pos.x, pos.y));
+
+    // Cells the snake can't pass through right now. Portals are kept free
+    // and counted as walls, so reachability never depends on teleporting.
+    let mut blocked: HashSet<(i8, i8)> = body_query
+        .iter()
+        .chain(obstacle_query.iter())
+        .chain(portal_query.iter())
+        .map(|pos| (pos.x, pos.y))
+        .collect();
+
+    let bounds = game_mode.field_bounds(&grid);
+
+    while count < target {
+        let placed = (0..PLACEMENT_ATTEMPTS).find_map(|_| {
+            let cell = test_mode.random_cell(bounds.0, bounds.1);
+            is_safe_cell(cell, head, food, &blocked, bounds).then_some(cell)
+        });
+
+        let Some((x, y)) = placed else {
+            break;
+        };
+
+        blocked.insert((x, y));
+        count += 1;
+
+        spawn_obstacle(&mut commands, &Position { x, y }, &snake_skin, *grid_origin);
+    }
+}
+
+pub fn spawn_obstacle(
+    commands: &mut Commands,
+    position: &Position,
+    snake_skin: &SnakeSkin,
+    grid_origin: GridOrigin,
+) {
+    commands.spawn((
+        Obstacle,
+        snake_skin.sprite(BORDER_INDEX),
+        *position,
+        grid_to_screen_transform(position, grid_origin),
+    ));
+}
+
+// An obstacle must not sit on the snake or the food, must leave every cell
+// next to the head open so the next tick can't be fatal, and must keep the
+// food reachable from the head.
+fn is_safe_cell(
+    cell: (i8, i8),
+    head: (i8, i8),
+    food: Option<(i8, i8)>,
+    blocked: &HashSet<(i8, i8)>,
+    bounds: ((i8, i8), (i8, i8)),
+) -> bool {
+    if cell == head || Some(cell) == food || blocked.contains(&cell) {
+        return false;
+    }
+    if (cell.0 - head.0).abs() + (cell.1 - head.1).abs() == 1 {
+        return false;
+    }
+
+    let Some(food) = food else {
+        return true;
+    };
+
+    let in_bounds =
+        |(x, y): (i8, i8)| x >= bounds.0.0 && x <= bounds.1.0 && y >= bounds.0.1 && y <= bounds.1.1;
+
+    // Flood fill from the head over free cells
+    let mut visited = HashSet::from([head]);
+    let mut queue = VecDeque::from([head]);
+    while let Some((x, y)) = queue.pop_front() {
+        if (x, y) == food {
+            return true;
+        }
+        for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if in_bounds(next) && next != cell && !blocked.contains(&next) && visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    false
+}
@@ -1,17 +1,45 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, window::WindowFocused};
 
-use crate::GameState;
+use crate::{
+    GameState, RunStats, Score,
+    assets_loader::UsesUiFont,
+    controls::KeyBindings,
+    settings::Settings,
+    snake::{BodySegment, Head},
+    strings::{MessageId, Strings},
+};
+
+const DEFAULT_AFK_TIMEOUT: f32 = 30.0;
+// Darkens the frozen board behind the pause text
+const PAUSE_DIM_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
 
 pub struct GamePausePlugin;
 
 impl Plugin for GamePausePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init_pause_message);
-        app.add_systems(OnEnter(GameState::Paused), show_pause);
+        app.init_resource::<AutoPauseOnBlur>();
+        app.init_resource::<AfkPause>();
+        app.init_resource::<AfkTimer>();
+        app.add_systems(OnEnter(GameState::InGame), reset_afk_timer);
+        app.add_systems(Startup, (init_pause_summary, init_pause_message).chain());
+        app.add_systems(
+            OnEnter(GameState::Paused),
+            (
+                show_pause,
+                show_pause_summary.run_if(|settings: Res<Settings>| settings.pause_summary),
+            )
+                .chain(),
+        );
         app.add_systems(OnExit(GameState::Paused), hide_pause);
         app.add_systems(
             Update,
-            handle_inputs_in_game.run_if(in_state(GameState::InGame)),
+            (
+                handle_inputs_in_game,
+                handle_quick_restart,
+                pause_on_focus_lost,
+                pause_when_afk,
+            )
+                .run_if(in_state(GameState::InGame)),
         );
         app.add_systems(
             Update,
@@ -23,27 +51,123 @@ impl Plugin for GamePausePlugin {
 #[derive(Component)]
 struct PauseText;
 
+// Dims the board while paused and holds the stats text
+#[derive(Component)]
+struct PauseSummary;
+
+#[derive(Component)]
+struct PauseSummaryText;
+
+// The run as it was when the game was paused, gone again once it resumes
+#[derive(Resource)]
+struct PauseStats {
+    score: usize,
+    // Head included
+    length: usize,
+    time: f32,
+}
+
+// Pause the game when the window loses focus. Regaining focus doesn't resume,
+// the player has to unpause explicitly.
+#[derive(Resource)]
+pub struct AutoPauseOnBlur(pub bool);
+
+impl Default for AutoPauseOnBlur {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+// Pause the game when the player hasn't touched the steering for a while, so
+// stepping away doesn't cost the run. Separate from the focus-loss pause.
+#[derive(Resource)]
+pub struct AfkPause {
+    pub enabled: bool,
+    // Seconds without steering input
+    pub timeout: f32,
+}
+
+impl Default for AfkPause {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: DEFAULT_AFK_TIMEOUT,
+        }
+    }
+}
+
+// Seconds since the last steering input, reset by the snake's input handling
+#[derive(Resource, Default)]
+pub struct AfkTimer(pub f32);
+
+fn reset_afk_timer(mut afk_timer: ResMut<AfkTimer>) {
+    afk_timer.0 = 0.0;
+}
+
+fn pause_when_afk(
+    time: Res<Time>,
+    afk_pause: Res<AfkPause>,
+    mut afk_timer: ResMut<AfkTimer>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if !afk_pause.enabled {
+        return;
+    }
+
+    afk_timer.0 += time.delta_secs();
+    if afk_timer.0 >= afk_pause.timeout {
+        println!("No input for {:.0}s, pausing", afk_pause.timeout);
+        game_state.set(GameState::Paused);
+    }
+}
+
 fn handle_inputs_in_game(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(key_bindings.pause) {
+        game_state.set(GameState::Paused);
+    }
+}
+
+// Throws the run away and starts a new one, the same reset a restart from the
+// game over screen goes through
+fn handle_quick_restart(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(key_bindings.quick_restart) {
+        println!("Quick restart");
+        game_state.set(GameState::StartGame);
+    }
+}
+
+fn pause_on_focus_lost(
+    mut focus_events: EventReader<WindowFocused>,
+    auto_pause: Res<AutoPauseOnBlur>,
     mut game_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+    if auto_pause.0 && lost_focus {
         game_state.set(GameState::Paused);
     }
 }
 
 fn handle_inputs_on_pause(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
     mut game_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    if keyboard_input.just_pressed(key_bindings.pause) {
         game_state.set(GameState::InGame);
     }
 }
 
-fn init_pause_message(mut commands: Commands) {
+fn init_pause_message(mut commands: Commands, strings: Res<Strings>) {
     commands.spawn((
-        Text::new("Paused"),
+        Text::new(strings.get(MessageId::Paused)),
         TextLayout::new_with_justify(JustifyText::Center),
         TextFont {
             font_size: 32.0,
@@ -61,17 +185,85 @@ fn init_pause_message(mut commands: Commands) {
             ..default()
         },
         PauseText,
+        UsesUiFont,
     ));
 }
 
-fn show_pause(mut query: Query<&mut Visibility, With<PauseText>>) {
+// Behind the pause message, which is spawned after it
+fn init_pause_summary(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                padding: UiRect::top(Val::Percent(20.0)),
+                ..default()
+            },
+            BackgroundColor(PAUSE_DIM_COLOR),
+            GlobalZIndex(-1),
+            Visibility::Hidden,
+            PauseSummary,
+        ))
+        .with_child((
+            Text::default(),
+            TextLayout::new_with_justify(JustifyText::Center),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            PauseSummaryText,
+            UsesUiFont,
+        ));
+}
+
+fn show_pause(
+    mut commands: Commands,
+    score: Res<Score>,
+    run_stats: Res<RunStats>,
+    head_query: Query<(), With<Head>>,
+    body_query: Query<(), With<BodySegment>>,
+    mut query: Query<&mut Visibility, With<PauseText>>,
+) {
     if let Ok(mut visibility) = query.single_mut() {
         *visibility = Visibility::Visible;
     }
+    commands.insert_resource(PauseStats {
+        score: score.0,
+        length: head_query.iter().count() + body_query.iter().count(),
+        time: run_stats.time_survived,
+    });
 }
 
-fn hide_pause(mut query: Query<&mut Visibility, With<PauseText>>) {
-    if let Ok(mut visibility) = query.single_mut() {
+fn show_pause_summary(
+    pause_stats: Res<PauseStats>,
+    strings: Res<Strings>,
+    mut summary_query: Query<&mut Visibility, With<PauseSummary>>,
+    mut text_query: Query<&mut Text, With<PauseSummaryText>>,
+) {
+    if let Ok(mut text) = text_query.single_mut() {
+        text.0 = strings.format(
+            MessageId::PauseSummary,
+            &[
+                ("score", &pause_stats.score),
+                ("length", &pause_stats.length),
+                ("time", &format!("{:.0}", pause_stats.time)),
+            ],
+        );
+    }
+    if let Ok(mut visibility) = summary_query.single_mut() {
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_pause(
+    mut commands: Commands,
+    mut query: Query<&mut Visibility, Or<(With<PauseText>, With<PauseSummary>)>>,
+) {
+    for mut visibility in query.iter_mut() {
         *visibility = Visibility::Hidden;
     }
+    commands.remove_resource::<PauseStats>();
 }
@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::{
+    FIRST_FOOD_CELL, GameMode, GameState, GameplaySet, GridConfig, GridOrigin, Position, TILE_SIZE,
+    grid_to_screen_transform,
+    levels::Levels,
+    obstacles::Obstacle,
+    snake::{BodySegment, Head, LastDirection, StartDirection},
+};
+
+const PORTAL_COLORS: [Color; 3] = [
+    Color::srgb(0.6, 0.3, 1.0),
+    Color::srgb(1.0, 0.6, 0.1),
+    Color::srgb(0.2, 0.8, 1.0),
+];
+const PORTAL_PULSE_SPEED: f32 = 4.0;
+// Between the floor and the snake
+const PORTAL_Z: f32 = -0.5;
+
+pub struct PortalsPlugin;
+
+impl Plugin for PortalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::StartGame),
+            (despawn_portals, spawn_portals).chain(),
+        );
+        app.add_systems(
+            Update,
+            check_portal_collision
+                .after(GameplaySet::Movement)
+                .before(GameplaySet::Collision)
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(Update, animate_portals);
+    }
+}
+
+// One end of a portal pair, `link` is the other end
+#[derive(Component)]
+pub struct Portal {
+    link: Entity,
+}
+
+fn despawn_portals(mut commands: Commands, query: Query<Entity, With<Portal>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn spawn_portals(
+    mut commands: Commands,
+    levels: Res<Levels>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    start_direction: Res<StartDirection>,
+) {
+    let in_field =
+        |(x, y): (i8, i8)| x >= grid.from.0 && x <= grid.to.0 && y >= grid.from.1 && y <= grid.to.1;
+    let body = StartDirection::BODY_POSITION;
+    let head = start_direction.head_position();
+    let mut used: HashSet<(i8, i8)> =
+        HashSet::from([(body.x, body.y), (head.x, head.y), FIRST_FOOD_CELL]);
+
+    for (idx, (a, b)) in levels.current().portals.iter().copied().enumerate() {
+        if a == b || !in_field(a) || !in_field(b) || used.contains(&a) || used.contains(&b) {
+            println!(
+                "Skipping portal {:?} <-> {:?}, the cells are not free",
+                a, b
+            );
+            continue;
+        }
+        used.insert(a);
+        used.insert(b);
+
+        let color = PORTAL_COLORS[idx % PORTAL_COLORS.len()];
+        let entity_a = spawn_portal_cell(&mut commands, a, color, *grid_origin);
+        let entity_b = spawn_portal_cell(&mut commands, b, color, *grid_origin);
+        commands.entity(entity_a).insert(Portal { link: entity_b });
+        commands.entity(entity_b).insert(Portal { link: entity_a });
+    }
+}
+
+fn spawn_portal_cell(
+    commands: &mut Commands,
+    (x, y): (i8, i8),
+    color: Color,
+    grid_origin: GridOrigin,
+) -> Entity {
+    let position = Position { x, y };
+    let mut transform = grid_to_screen_transform(&position, grid_origin);
+    transform.translation.z = PORTAL_Z;
+    commands
+        .spawn((
+            Sprite::from_color(color, Vec2::splat(TILE_SIZE)),
+            position,
+            transform,
+        ))
+        .id()
+}
+
+// Moves the head to the linked portal right after the tick that brought it
+// onto a portal. The body keeps following the head's previous cells, so it
+// passes through on the next ticks. A portal whose exit is blocked by the
+// body or an obstacle, or leads straight out of the field, acts as floor.
+fn check_portal_collision(
+    mut head_query: Query<(&mut Position, &LastDirection), With<Head>>,
+    portal_query: Query<(&Portal, &Position), Without<Head>>,
+    blocked_query: Query<&Position, (Or<(With<BodySegment>, With<Obstacle>)>, Without<Head>)>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    // Head cell after the last check, so a head resting on an exit isn't sent back
+    mut last_cell: Local<Option<(i8, i8)>>,
+) {
+    let Ok((mut head_pos, last_direction)) = head_query.single_mut() else {
+        return;
+    };
+    let cell = (head_pos.x, head_pos.y);
+    if *last_cell == Some(cell) {
+        return;
+    }
+    *last_cell = Some(cell);
+
+    let Some(portal) = portal_query
+        .iter()
+        .find(|(_, pos)| pos.x == cell.0 && pos.y == cell.1)
+        .map(|(portal, _)| portal)
+    else {
+        return;
+    };
+    let Ok((_, exit)) = portal_query.get(portal.link) else {
+        return;
+    };
+
+    let exit_cell = (exit.x, exit.y);
+    if blocked_query
+        .iter()
+        .any(|pos| pos.x == exit_cell.0 && pos.y == exit_cell.1)
+    {
+        return;
+    }
+
+    // Keep the direction of travel: the next cell past the exit must be on the field
+    let step = last_direction.0.offset();
+    let (from, to) = game_mode.field_bounds(&grid);
+    let next = (exit_cell.0 + step.0, exit_cell.1 + step.1);
+    if *game_mode != GameMode::Endless
+        && (next.0 < from.0 || next.0 > to.0 || next.1 < from.1 || next.1 > to.1)
+    {
+        return;
+    }
+
+    head_pos.x = exit_cell.0;
+    head_pos.y = exit_cell.1;
+    *last_cell = Some(exit_cell);
+}
+
+// Pulses the portals so they stand out from food and floor
+fn animate_portals(time: Res<Time>, mut query: Query<&mut Transform, With<Portal>>) {
+    let scale = 0.8 + 0.2 * (time.elapsed_secs() * PORTAL_PULSE_SPEED).sin();
+    for mut transform in query.iter_mut() {
+        transform.scale = Vec3::splat(scale);
+    }
+}
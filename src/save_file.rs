@@ -0,0 +1,9 @@
+use std::{fs, io, path::PathBuf};
+
+// Writes the whole file next to `path` first and renames it over the old one,
+// so quitting halfway through a save never leaves a truncated file behind
+pub fn write_atomic(path: &str, contents: &str) -> io::Result<()> {
+    let tmp_path = PathBuf::from(format!("{}.tmp", path));
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
@@ -0,0 +1,311 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Food, GameMode, GameState, GridConfig, GridOrigin, Peaceful, Position, RunStats, Score,
+    assets_loader::SnakeSkin,
+    grid_to_screen_position,
+    levels::Levels,
+    lives::Lives,
+    obstacles::{Obstacle, spawn_obstacle},
+    save_file::write_atomic,
+    snake::{BodySegment, Dir, Forgiving, Head, LastDirection, NextSegment, spawn_snake},
+    test_mode::{self, TestMode},
+    timed_mode::TimedMode,
+};
+
+// A single run that was left unfinished, see SavedRun
+const SAVE_PATH: &str = "savegame.ron";
+// Bump whenever SavedRun changes, older saves are then discarded
+const SAVE_VERSION: u32 = 1;
+
+pub struct SavedGamePlugin;
+
+impl Plugin for SavedGamePlugin {
+    fn build(&self, app: &mut App) {
+        if test_mode::uses_user_files(app) {
+            app.insert_resource(SavedGame::load(SAVE_PATH));
+        } else {
+            app.init_resource::<SavedGame>();
+        }
+        app.init_resource::<ResumingRun>();
+        app.add_event::<SaveGameEvent>();
+        app.add_event::<ResumeGameEvent>();
+        app.add_systems(
+            Update,
+            (
+                save_and_quit.run_if(in_state(GameState::Paused)),
+                load_game.run_if(on_event::<ResumeGameEvent>),
+            ),
+        );
+        app.add_systems(
+            OnEnter(GameState::InGame),
+            restore_run.run_if(|resuming: Res<ResumingRun>| resuming.0.is_some()),
+        );
+        app.add_systems(
+            Last,
+            save_game.run_if(on_event::<SaveGameEvent>.or(on_event::<AppExit>)),
+        );
+    }
+}
+
+// Everything needed to put a run back on the board
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedRun {
+    version: u32,
+    mode: GameMode,
+    peaceful: bool,
+    forgiving: bool,
+    level: String,
+    // Field the run was played on, resuming on another one is refused
+    grid: ((i8, i8), (i8, i8)),
+    // Head first
+    snake: Vec<Position>,
+    direction: Dir,
+    food: Position,
+    obstacles: Vec<Position>,
+    pub score: usize,
+    lives: u8,
+    // Seconds left in timed mode
+    time_remaining: f32,
+    foods_eaten: usize,
+    time_survived: f32,
+    // Added after the first version, older saves count from zero
+    #[serde(default)]
+    moves: usize,
+}
+
+// Only the version is read first, so a save from another version is
+// recognized as such instead of failing somewhere in the middle
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+// The run that can be continued from the main menu, if any
+#[derive(Resource, Default)]
+pub struct SavedGame(pub Option<SavedRun>);
+
+impl SavedGame {
+    fn load(path: &str) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+
+        match ron::from_str::<SaveHeader>(&contents) {
+            Ok(header) if header.version == SAVE_VERSION => {}
+            Ok(header) => {
+                println!(
+                    "Saved game in {} is from version {}, expected {}, ignoring it",
+                    path, header.version, SAVE_VERSION
+                );
+                return Self::default();
+            }
+            Err(err) => {
+                println!("Could not parse {}: {}, ignoring it", path, err);
+                return Self::default();
+            }
+        }
+
+        match ron::from_str::<SavedRun>(&contents) {
+            Ok(run) if run.snake.len() >= 2 => Self(Some(run)),
+            Ok(_) => {
+                println!("Saved game in {} has no snake, ignoring it", path);
+                Self::default()
+            }
+            Err(err) => {
+                println!("Could not parse {}: {}, ignoring it", path, err);
+                Self::default()
+            }
+        }
+    }
+}
+
+// Saves the run in progress. Sent from the pause screen, and the run is also
+// saved when the app closes during a game.
+#[derive(Event)]
+pub struct SaveGameEvent;
+
+// Continue the saved run, sent from the main menu
+#[derive(Event)]
+pub struct ResumeGameEvent;
+
+// Saved run being set up, restored once the new game has been built
+#[derive(Resource, Default)]
+struct ResumingRun(Option<SavedRun>);
+
+fn save_and_quit(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyQ) {
+        save_events.write(SaveGameEvent);
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn save_game(
+    game_state: Res<State<GameState>>,
+    game_mode: Res<GameMode>,
+    peaceful: Res<Peaceful>,
+    forgiving: Res<Forgiving>,
+    levels: Res<Levels>,
+    grid: Res<GridConfig>,
+    score: Res<Score>,
+    lives: Res<Lives>,
+    timed_mode: Res<TimedMode>,
+    run_stats: Res<RunStats>,
+    head_query: Query<(&Position, &LastDirection, &NextSegment), With<Head>>,
+    body_query: Query<(&Position, Option<&NextSegment>), (With<BodySegment>, Without<Head>)>,
+    food_query: Query<&Position, With<Food>>,
+    obstacle_query: Query<&Position, With<Obstacle>>,
+    test_mode: Res<TestMode>,
+    mut saved_game: ResMut<SavedGame>,
+) {
+    if !matches!(game_state.get(), GameState::InGame | GameState::Paused) {
+        return;
+    }
+    let (Ok((head_pos, direction, first_segment)), Ok(food_pos)) =
+        (head_query.single(), food_query.single())
+    else {
+        return;
+    };
+
+    let mut snake = vec![*head_pos];
+    let mut current = Some(first_segment.0);
+    while let Some(entity) = current
+        && let Ok((pos, next)) = body_query.get(entity)
+    {
+        snake.push(*pos);
+        current = next.map(|next| next.0);
+    }
+
+    let run = SavedRun {
+        version: SAVE_VERSION,
+        mode: *game_mode,
+        peaceful: peaceful.0,
+        forgiving: forgiving.0,
+        level: levels.current().name.clone(),
+        grid: (grid.from, grid.to),
+        snake,
+        direction: direction.0,
+        food: *food_pos,
+        obstacles: obstacle_query.iter().copied().collect(),
+        score: score.0,
+        lives: lives.0,
+        time_remaining: timed_mode.remaining,
+        foods_eaten: run_stats.foods_eaten,
+        time_survived: run_stats.time_survived,
+        moves: run_stats.moves,
+    };
+    if !test_mode.uses_user_files() {
+        saved_game.0 = Some(run);
+        return;
+    }
+
+    let contents = match ron::ser::to_string_pretty(&run, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(err) => {
+            println!("Could not serialize the game: {}", err);
+            return;
+        }
+    };
+    match write_atomic(SAVE_PATH, &contents) {
+        Ok(()) => {
+            println!("Game saved to {}", SAVE_PATH);
+            saved_game.0 = Some(run);
+        }
+        Err(err) => println!("Could not save the game to {}: {}", SAVE_PATH, err),
+    }
+}
+
+// Sets up the saved run's mode and level and starts a new game on them. The
+// save is used up, so the same run can't be continued twice.
+fn load_game(
+    grid: Res<GridConfig>,
+    mut saved_game: ResMut<SavedGame>,
+    mut resuming: ResMut<ResumingRun>,
+    mut game_mode: ResMut<GameMode>,
+    mut peaceful: ResMut<Peaceful>,
+    mut forgiving: ResMut<Forgiving>,
+    mut levels: ResMut<Levels>,
+    test_mode: Res<TestMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(run) = saved_game.0.take() else {
+        return;
+    };
+    if test_mode.uses_user_files()
+        && let Err(err) = fs::remove_file(SAVE_PATH)
+    {
+        println!("Could not remove {}: {}", SAVE_PATH, err);
+    }
+
+    if run.grid != (grid.from, grid.to) {
+        println!("Saved game was played on another field size, it can't be continued");
+        return;
+    }
+    if !levels.select_named(&run.level) {
+        println!("Level {:?} of the saved game no longer exists", run.level);
+        return;
+    }
+
+    *game_mode = run.mode;
+    peaceful.0 = run.peaceful;
+    forgiving.0 = run.forgiving;
+    resuming.0 = Some(run);
+    next_state.set(GameState::StartGame);
+}
+
+// Replaces the freshly started board with the saved one and waits paused,
+// so the player gets a moment before the snake moves again
+fn restore_run(
+    mut commands: Commands,
+    snake_skin: Res<SnakeSkin>,
+    mut resuming: ResMut<ResumingRun>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut timed_mode: ResMut<TimedMode>,
+    mut run_stats: ResMut<RunStats>,
+    snake_query: Query<Entity, Or<(With<Head>, With<BodySegment>)>>,
+    mut food_query: Query<(&mut Position, &mut Transform), With<Food>>,
+    grid_origin: Res<GridOrigin>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(run) = resuming.0.take() else {
+        return;
+    };
+
+    for entity in snake_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_snake(
+        &mut commands,
+        &run.snake,
+        run.direction,
+        &snake_skin,
+        *grid_origin,
+    );
+    for position in run.obstacles.iter() {
+        spawn_obstacle(&mut commands, position, &snake_skin, *grid_origin);
+    }
+
+    if let Ok((mut food_pos, mut food_transform)) = food_query.single_mut() {
+        *food_pos = run.food;
+        food_transform.translation = grid_to_screen_position(&run.food, *grid_origin);
+    }
+
+    score.0 = run.score;
+    lives.0 = run.lives;
+    timed_mode.remaining = run.time_remaining;
+    run_stats.foods_eaten = run.foods_eaten;
+    run_stats.time_survived = run.time_survived;
+    run_stats.moves = run.moves;
+    run_stats.length = run.snake.len();
+
+    println!("Continuing saved game, score: {}", run.score);
+    next_state.set(GameState::Paused);
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+
+use crate::{GameState, RunStats, Score};
+
+pub struct ScoreReporterPlugin;
+
+impl Plugin for ScoreReporterPlugin {
+    fn build(&self, app: &mut App) {
+        // Doesn't replace a reporter registered before the plugin was added
+        app.init_resource::<ActiveReporter>();
+        app.add_systems(OnEnter(GameState::GameOver), report_score);
+    }
+}
+
+// Sends the final score of every run somewhere, e.g. an online leaderboard.
+// Called once per game over on the main thread, so slow work such as network
+// requests should be handed off to a background task.
+pub trait ScoreReporter: Send + Sync + 'static {
+    fn report(&self, score: usize, stats: &RunStats);
+}
+
+// Default reporter, keeps scores local
+pub struct NoopReporter;
+
+impl ScoreReporter for NoopReporter {
+    fn report(&self, _score: usize, _stats: &RunStats) {}
+}
+
+// Register your own with `.insert_resource(ActiveReporter::new(MyReporter))` in `main()`
+#[derive(Resource)]
+pub struct ActiveReporter(Box<dyn ScoreReporter>);
+
+impl ActiveReporter {
+    pub fn new(reporter: impl ScoreReporter) -> Self {
+        Self(Box::new(reporter))
+    }
+}
+
+impl Default for ActiveReporter {
+    fn default() -> Self {
+        Self::new(NoopReporter)
+    }
+}
+
+fn report_score(reporter: Res<ActiveReporter>, score: Res<Score>, run_stats: Res<RunStats>) {
+    reporter.0.report(score.0, &run_stats);
+}
@@ -0,0 +1,669 @@
+use std::{fs, mem::discriminant};
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    camera::DEFAULT_ZOOM,
+    controls::{
+        ControlLayout, DEFAULT_BINDINGS, TurnMode, is_rebindable, key_from_name, key_label,
+        key_name,
+    },
+    menu_nav::{MenuConfirm, MenuCursor, MenuNavSet},
+    save_file::write_atomic,
+    snake::SnakeThickness,
+    strings::{MessageId, Strings},
+    test_mode::{self, TestMode},
+};
+
+// Player preferences, persisted between sessions as `key = value` lines
+const SETTINGS_PATH: &str = "settings.txt";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        if test_mode::uses_user_files(app) {
+            app.insert_resource(Settings::load(SETTINGS_PATH));
+        } else {
+            app.init_resource::<Settings>();
+        }
+        app.init_resource::<Rebinding>();
+        app.add_systems(Startup, init_settings_screen);
+        app.add_systems(OnEnter(GameState::Settings), show_settings);
+        app.add_systems(
+            OnExit(GameState::Settings),
+            (hide_settings, cancel_rebinding),
+        );
+        app.add_systems(
+            Last,
+            save_settings_on_exit
+                .run_if(on_event::<AppExit>)
+                .run_if(test_mode::saves_user_files),
+        );
+        app.add_systems(
+            Update,
+            (handle_inputs, update_settings_text)
+                .chain()
+                .after(MenuNavSet)
+                .run_if(in_state(GameState::Settings)),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct Settings {
+    pub control_layout: ControlLayout,
+    pub pause_key: KeyCode,
+    pub restart_key: KeyCode,
+    pub quick_restart_key: KeyCode,
+    pub end_game_key: KeyCode,
+    pub camera_zoom: f32,
+    pub invert_vertical: bool,
+    pub turn_mode: TurnMode,
+    pub score_in_title: bool,
+    pub death_replay: bool,
+    pub stable_camera: bool,
+    pub food_bob: bool,
+    pub hide_cursor: bool,
+    pub heatmap: bool,
+    pub ghost: bool,
+    pub restart_on_any_key: bool,
+    pub snake_thickness: SnakeThickness,
+    pub pause_summary: bool,
+    pub music: bool,
+    // Set once the controls overlay has been shown on first launch
+    pub seen_controls: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            control_layout: ControlLayout::default(),
+            pause_key: DEFAULT_BINDINGS.pause,
+            restart_key: DEFAULT_BINDINGS.restart,
+            quick_restart_key: DEFAULT_BINDINGS.quick_restart,
+            end_game_key: DEFAULT_BINDINGS.end_game,
+            camera_zoom: DEFAULT_ZOOM,
+            invert_vertical: false,
+            turn_mode: TurnMode::default(),
+            score_in_title: false,
+            death_replay: false,
+            stable_camera: false,
+            food_bob: true,
+            hide_cursor: false,
+            heatmap: false,
+            ghost: true,
+            restart_on_any_key: false,
+            snake_thickness: SnakeThickness::default(),
+            pause_summary: true,
+            music: true,
+            seen_controls: false,
+        }
+    }
+}
+
+impl Settings {
+    fn load(path: &str) -> Self {
+        let mut settings = Self::default();
+
+        let Ok(contents) = fs::read_to_string(path) else {
+            return settings;
+        };
+
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            match (key.trim(), value.trim()) {
+                ("control_layout", value) => match ControlLayout::from_key(value) {
+                    Some(layout) => settings.control_layout = layout,
+                    None => warn!("Unknown control layout in {}: {}", path, value),
+                },
+                ("pause_key", value) => match key_from_name(value) {
+                    Some(key) => settings.pause_key = key,
+                    None => warn!("Unknown pause key in {}: {}", path, value),
+                },
+                ("restart_key", value) => match key_from_name(value) {
+                    Some(key) => settings.restart_key = key,
+                    None => warn!("Unknown restart key in {}: {}", path, value),
+                },
+                ("quick_restart_key", value) => match key_from_name(value) {
+                    Some(key) => settings.quick_restart_key = key,
+                    None => warn!("Unknown quick restart key in {}: {}", path, value),
+                },
+                ("end_game_key", value) => match key_from_name(value) {
+                    Some(key) => settings.end_game_key = key,
+                    None => warn!("Unknown end game key in {}: {}", path, value),
+                },
+                ("camera_zoom", value) => match value.parse() {
+                    Ok(zoom) => settings.camera_zoom = zoom,
+                    Err(_) => warn!("Invalid camera zoom in {}: {}", path, value),
+                },
+                ("turn_mode", value) => match TurnMode::from_key(value) {
+                    Some(mode) => settings.turn_mode = mode,
+                    None => warn!("Unknown turn mode in {}: {}", path, value),
+                },
+                ("snake_thickness", value) => match SnakeThickness::from_key(value) {
+                    Some(thickness) => settings.snake_thickness = thickness,
+                    None => warn!("Unknown snake thickness in {}: {}", path, value),
+                },
+                ("seen_controls", value) => settings.seen_controls = value == "true",
+                (key, value) => match TOGGLES.iter().find(|toggle| toggle.key == key) {
+                    Some(toggle) => (toggle.set)(&mut settings, value == "true"),
+                    None => warn!("Unknown setting in {}: {}", path, key),
+                },
+            }
+        }
+
+        settings
+    }
+
+    // Every setting as it is written to the file, one `key = value` line each
+    fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = vec![
+            ("control_layout", self.control_layout.key().to_string()),
+            ("pause_key", key_name(self.pause_key)),
+            ("restart_key", key_name(self.restart_key)),
+            ("quick_restart_key", key_name(self.quick_restart_key)),
+            ("end_game_key", key_name(self.end_game_key)),
+            ("camera_zoom", self.camera_zoom.to_string()),
+            ("turn_mode", self.turn_mode.key().to_string()),
+            ("snake_thickness", self.snake_thickness.key().to_string()),
+        ];
+        entries.extend(
+            TOGGLES
+                .iter()
+                .map(|toggle| (toggle.key, (toggle.value)(self).to_string())),
+        );
+        entries.push(("seen_controls", self.seen_controls.to_string()));
+        entries
+    }
+
+    // Saves to the player's settings file, which test mode leaves alone
+    pub fn store(&self, test_mode: &TestMode) {
+        if test_mode.uses_user_files() {
+            self.save(SETTINGS_PATH);
+        }
+    }
+
+    pub fn save(&self, path: &str) {
+        let contents: String = self
+            .entries()
+            .into_iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect();
+        if let Err(err) = write_atomic(path, &contents) {
+            println!("Could not save settings to {}: {}", path, err);
+        }
+    }
+}
+
+// An on/off row of the settings screen and the setting it flips
+#[derive(Debug)]
+struct Toggle {
+    label: MessageId,
+    // Key in the settings file
+    key: &'static str,
+    value: fn(&Settings) -> bool,
+    set: fn(&mut Settings, bool),
+}
+
+const INVERT_VERTICAL: Toggle = Toggle {
+    label: MessageId::SettingInvertVertical,
+    key: "invert_vertical",
+    value: |settings| settings.invert_vertical,
+    set: |settings, on| settings.invert_vertical = on,
+};
+const SCORE_IN_TITLE: Toggle = Toggle {
+    label: MessageId::SettingScoreInTitle,
+    key: "score_in_title",
+    value: |settings| settings.score_in_title,
+    set: |settings, on| settings.score_in_title = on,
+};
+const DEATH_REPLAY: Toggle = Toggle {
+    label: MessageId::SettingDeathReplay,
+    key: "death_replay",
+    value: |settings| settings.death_replay,
+    set: |settings, on| settings.death_replay = on,
+};
+const STABLE_CAMERA: Toggle = Toggle {
+    label: MessageId::SettingStableCamera,
+    key: "stable_camera",
+    value: |settings| settings.stable_camera,
+    set: |settings, on| settings.stable_camera = on,
+};
+const FOOD_BOB: Toggle = Toggle {
+    label: MessageId::SettingFoodBob,
+    key: "food_bob",
+    value: |settings| settings.food_bob,
+    set: |settings, on| settings.food_bob = on,
+};
+const HIDE_CURSOR: Toggle = Toggle {
+    label: MessageId::SettingHideCursor,
+    key: "hide_cursor",
+    value: |settings| settings.hide_cursor,
+    set: |settings, on| settings.hide_cursor = on,
+};
+const HEATMAP: Toggle = Toggle {
+    label: MessageId::SettingHeatmap,
+    key: "heatmap",
+    value: |settings| settings.heatmap,
+    set: |settings, on| settings.heatmap = on,
+};
+const GHOST: Toggle = Toggle {
+    label: MessageId::SettingGhost,
+    key: "ghost",
+    value: |settings| settings.ghost,
+    set: |settings, on| settings.ghost = on,
+};
+const RESTART_ON_ANY_KEY: Toggle = Toggle {
+    label: MessageId::SettingRestartOnAnyKey,
+    key: "restart_on_any_key",
+    value: |settings| settings.restart_on_any_key,
+    set: |settings, on| settings.restart_on_any_key = on,
+};
+const PAUSE_SUMMARY: Toggle = Toggle {
+    label: MessageId::SettingPauseSummary,
+    key: "pause_summary",
+    value: |settings| settings.pause_summary,
+    set: |settings, on| settings.pause_summary = on,
+};
+const MUSIC: Toggle = Toggle {
+    label: MessageId::SettingMusic,
+    key: "music",
+    value: |settings| settings.music,
+    set: |settings, on| settings.music = on,
+};
+
+const TOGGLES: [&Toggle; 11] = [
+    &INVERT_VERTICAL,
+    &SCORE_IN_TITLE,
+    &DEATH_REPLAY,
+    &STABLE_CAMERA,
+    &FOOD_BOB,
+    &HIDE_CURSOR,
+    &HEATMAP,
+    &GHOST,
+    &RESTART_ON_ANY_KEY,
+    &PAUSE_SUMMARY,
+    &MUSIC,
+];
+
+#[derive(Debug, Clone, Copy)]
+enum SettingItem {
+    ControlLayout,
+    TurnMode,
+    PauseKey,
+    RestartKey,
+    QuickRestartKey,
+    EndGameKey,
+    SnakeThickness,
+    Toggle(&'static Toggle),
+}
+
+const SETTING_ITEMS: [SettingItem; 18] = [
+    SettingItem::ControlLayout,
+    SettingItem::Toggle(&INVERT_VERTICAL),
+    SettingItem::TurnMode,
+    SettingItem::PauseKey,
+    SettingItem::RestartKey,
+    SettingItem::QuickRestartKey,
+    SettingItem::EndGameKey,
+    SettingItem::Toggle(&SCORE_IN_TITLE),
+    SettingItem::Toggle(&DEATH_REPLAY),
+    SettingItem::Toggle(&STABLE_CAMERA),
+    SettingItem::Toggle(&FOOD_BOB),
+    SettingItem::Toggle(&HIDE_CURSOR),
+    SettingItem::Toggle(&HEATMAP),
+    SettingItem::Toggle(&GHOST),
+    SettingItem::Toggle(&RESTART_ON_ANY_KEY),
+    SettingItem::SnakeThickness,
+    SettingItem::Toggle(&PAUSE_SUMMARY),
+    SettingItem::Toggle(&MUSIC),
+];
+
+// Key binding waiting for the player to press a key, and the last rejected key
+#[derive(Resource, Default)]
+struct Rebinding {
+    capturing: Option<SettingItem>,
+    conflict: Option<KeyCode>,
+}
+
+#[derive(Component)]
+struct SettingsText;
+
+fn item_label(item: SettingItem, settings: &Settings, strings: &Strings) -> String {
+    match item {
+        SettingItem::ControlLayout => strings.format(
+            MessageId::SettingControls,
+            &[("layout", &settings.control_layout.label())],
+        ),
+        SettingItem::TurnMode => {
+            let mode = match settings.turn_mode {
+                TurnMode::Held => strings.get(MessageId::TurnModeHeld),
+                TurnMode::Tap => strings.get(MessageId::TurnModeTap),
+            };
+            strings.format(MessageId::SettingTurnMode, &[("mode", &mode)])
+        }
+        SettingItem::SnakeThickness => {
+            let thickness = match settings.snake_thickness {
+                SnakeThickness::Normal => strings.get(MessageId::ThicknessNormal),
+                SnakeThickness::Thick => strings.get(MessageId::ThicknessThick),
+            };
+            strings.format(
+                MessageId::SettingSnakeThickness,
+                &[("thickness", &thickness)],
+            )
+        }
+        SettingItem::Toggle(toggle) => {
+            let state = if (toggle.value)(settings) {
+                strings.get(MessageId::On)
+            } else {
+                strings.get(MessageId::Off)
+            };
+            strings.format(toggle.label, &[("state", &state)])
+        }
+        SettingItem::PauseKey => strings.format(
+            MessageId::SettingPauseKey,
+            &[("key", &key_label(settings.pause_key))],
+        ),
+        SettingItem::RestartKey => strings.format(
+            MessageId::SettingRestartKey,
+            &[("key", &key_label(settings.restart_key))],
+        ),
+        SettingItem::QuickRestartKey => strings.format(
+            MessageId::SettingQuickRestartKey,
+            &[("key", &key_label(settings.quick_restart_key))],
+        ),
+        SettingItem::EndGameKey => strings.format(
+            MessageId::SettingEndGameKey,
+            &[("key", &key_label(settings.end_game_key))],
+        ),
+    }
+}
+
+// The key an action item is bound to, None for items that aren't keys
+fn bound_key(item: SettingItem, settings: &Settings) -> Option<KeyCode> {
+    match item {
+        SettingItem::PauseKey => Some(settings.pause_key),
+        SettingItem::RestartKey => Some(settings.restart_key),
+        SettingItem::QuickRestartKey => Some(settings.quick_restart_key),
+        SettingItem::EndGameKey => Some(settings.end_game_key),
+        SettingItem::ControlLayout
+        | SettingItem::TurnMode
+        | SettingItem::SnakeThickness
+        | SettingItem::Toggle(_) => None,
+    }
+}
+
+// Keys the new binding may not share, the movement keys of the layout included
+fn taken_keys(item: SettingItem, settings: &Settings) -> Vec<KeyCode> {
+    let mut keys = settings.control_layout.bindings().steering().to_vec();
+    keys.extend(
+        SETTING_ITEMS
+            .iter()
+            .filter(|other| discriminant(*other) != discriminant(&item))
+            .filter_map(|other| bound_key(*other, settings)),
+    );
+    keys
+}
+
+fn settings_text(
+    cursor: &MenuCursor,
+    settings: &Settings,
+    rebinding: &Rebinding,
+    strings: &Strings,
+) -> String {
+    let mut text = format!("{}\n\n", strings.get(MessageId::SettingsTitle));
+    for (idx, item) in SETTING_ITEMS.iter().enumerate() {
+        text.push_str(&format!(
+            "{}{}\n",
+            cursor.marker(idx),
+            item_label(*item, settings, strings)
+        ));
+    }
+    let hint = if rebinding.capturing.is_some() {
+        strings.get(MessageId::SettingsPressKey).to_string()
+    } else if let Some(key) = rebinding.conflict {
+        strings.format(MessageId::SettingsKeyConflict, &[("key", &key_label(key))])
+    } else {
+        strings.get(MessageId::SettingsHint).to_string()
+    };
+    text.push_str(&format!("\n{}", hint));
+    text
+}
+
+fn handle_inputs(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut confirm_events: EventReader<MenuConfirm>,
+    mut cursor_query: Query<(Entity, &mut MenuCursor), With<SettingsText>>,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    test_mode: Res<TestMode>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Ok((settings_entity, mut cursor)) = cursor_query.single_mut() else {
+        return;
+    };
+
+    if let Some(item) = rebinding.capturing {
+        let Some(key) = keyboard_input
+            .get_just_pressed()
+            .copied()
+            .find(|key| is_rebindable(*key))
+        else {
+            return;
+        };
+
+        rebinding.capturing = None;
+        cursor.locked = false;
+        if taken_keys(item, &settings).contains(&key) {
+            println!("{:?} is already bound, keeping the old key", key);
+            rebinding.conflict = Some(key);
+            return;
+        }
+
+        match item {
+            SettingItem::PauseKey => settings.pause_key = key,
+            SettingItem::RestartKey => settings.restart_key = key,
+            SettingItem::QuickRestartKey => settings.quick_restart_key = key,
+            SettingItem::EndGameKey => settings.end_game_key = key,
+            SettingItem::ControlLayout
+            | SettingItem::TurnMode
+            | SettingItem::SnakeThickness
+            | SettingItem::Toggle(_) => {}
+        }
+        settings.store(&test_mode);
+        return;
+    }
+
+    for event in confirm_events.read() {
+        if event.entity != settings_entity {
+            continue;
+        }
+        rebinding.conflict = None;
+        match SETTING_ITEMS[event.index] {
+            SettingItem::ControlLayout => {
+                settings.control_layout = settings.control_layout.next();
+                settings.store(&test_mode);
+                let steering = settings.control_layout.bindings().steering();
+                if SETTING_ITEMS
+                    .iter()
+                    .filter_map(|item| bound_key(*item, &settings))
+                    .any(|key| steering.contains(&key))
+                {
+                    println!("Control layout shares a key with pause or restart");
+                }
+            }
+            SettingItem::TurnMode => {
+                settings.turn_mode = settings.turn_mode.next();
+                settings.store(&test_mode);
+            }
+            SettingItem::SnakeThickness => {
+                settings.snake_thickness = settings.snake_thickness.next();
+                settings.store(&test_mode);
+            }
+            SettingItem::Toggle(toggle) => {
+                let on = (toggle.value)(&settings);
+                (toggle.set)(&mut settings, !on);
+                settings.store(&test_mode);
+            }
+            item => {
+                // Navigation keys go to the capture until a key is picked
+                rebinding.capturing = Some(item);
+                cursor.locked = true;
+            }
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        game_state.set(GameState::MainMenu);
+    }
+}
+
+fn save_settings_on_exit(settings: Res<Settings>) {
+    settings.save(SETTINGS_PATH);
+}
+
+fn init_settings_screen(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        SettingsText,
+        MenuCursor::new(SETTING_ITEMS.len()),
+    ));
+}
+
+fn update_settings_text(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    strings: Res<Strings>,
+    mut query: Query<(Ref<MenuCursor>, &mut Text), With<SettingsText>>,
+) {
+    let Ok((cursor, mut text)) = query.single_mut() else {
+        return;
+    };
+    if !cursor.is_changed() && !settings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+    text.0 = settings_text(&cursor, &settings, &rebinding, &strings);
+}
+
+fn show_settings(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    strings: Res<Strings>,
+    mut query: Query<(&MenuCursor, &mut Visibility, &mut Text), With<SettingsText>>,
+) {
+    if let Ok((cursor, mut visibility, mut text)) = query.single_mut() {
+        *visibility = Visibility::Visible;
+        text.0 = settings_text(cursor, &settings, &rebinding, &strings);
+    }
+}
+
+fn hide_settings(mut query: Query<&mut Visibility, With<SettingsText>>) {
+    if let Ok(mut visibility) = query.single_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn cancel_rebinding(
+    mut rebinding: ResMut<Rebinding>,
+    mut cursor_query: Query<&mut MenuCursor, With<SettingsText>>,
+) {
+    *rebinding = Rebinding::default();
+    if let Ok(mut cursor) = cursor_query.single_mut() {
+        cursor.locked = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_survive_a_save_and_load() {
+        let path = std::env::temp_dir().join(format!("snake_settings_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut settings = Settings {
+            control_layout: ControlLayout::Arrows,
+            pause_key: KeyCode::KeyP,
+            turn_mode: TurnMode::Tap,
+            snake_thickness: SnakeThickness::Thick,
+            seen_controls: true,
+            ..Settings::default()
+        };
+        for toggle in TOGGLES {
+            let on = (toggle.value)(&settings);
+            (toggle.set)(&mut settings, !on);
+        }
+        settings.save(path);
+        let loaded = Settings::load(path);
+        let _ = fs::remove_file(path);
+
+        assert_eq!(loaded.entries(), settings.entries());
+    }
+
+    #[test]
+    fn lines_are_read_by_key_in_any_order() {
+        let path =
+            std::env::temp_dir().join(format!("snake_settings_keyed_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(
+            path,
+            "music = false\nunknown = 1\nturn_mode = tap\nghost = false\n",
+        )
+        .unwrap();
+        let loaded = Settings::load(path);
+        let _ = fs::remove_file(path);
+
+        assert!(!loaded.music && !loaded.ghost);
+        assert_eq!(loaded.turn_mode, TurnMode::Tap);
+        assert!(loaded.food_bob, "settings left out keep their default");
+    }
+
+    #[test]
+    fn every_toggle_has_a_row_and_its_own_key() {
+        for toggle in TOGGLES {
+            let rows = SETTING_ITEMS
+                .iter()
+                .filter(|item| matches!(item, SettingItem::Toggle(row) if row.key == toggle.key))
+                .count();
+            assert_eq!(rows, 1, "{}", toggle.key);
+        }
+        let mut keys: Vec<_> = Settings::default()
+            .entries()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        let count = keys.len();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), count);
+    }
+
+    #[test]
+    fn end_game_key_is_taken_for_the_other_actions() {
+        let settings = Settings::default();
+        assert!(taken_keys(SettingItem::PauseKey, &settings).contains(&settings.end_game_key));
+        assert!(!taken_keys(SettingItem::EndGameKey, &settings).contains(&settings.end_game_key));
+        assert!(taken_keys(SettingItem::EndGameKey, &settings).contains(&settings.pause_key));
+    }
+}
@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+
+use crate::{
+    GameState, GridConfig, GridOrigin, Position, RunStats, Score,
+    assets_loader::SnakeSkin,
+    leaderboard::Leaderboard,
+    snake::{BodySegment, Head, StartDirection, spawn_snake},
+};
+
+// Preset state for scripted screenshots and demos, e.g.
+//
+//     cargo run -- --score 120 --high-score 300 --length 12
+//
+// Each value can also come from the environment (SNAKE_SCORE,
+// SNAKE_HIGH_SCORE, SNAKE_LENGTH), the command line wins. Invalid values are
+// ignored. The score and length apply to the first run only.
+const SCORE_FLAG: &str = "--score";
+const HIGH_SCORE_FLAG: &str = "--high-score";
+const LENGTH_FLAG: &str = "--length";
+const SCORE_VAR: &str = "SNAKE_SCORE";
+const HIGH_SCORE_VAR: &str = "SNAKE_HIGH_SCORE";
+const LENGTH_VAR: &str = "SNAKE_LENGTH";
+// Head plus one segment, what every snake starts with
+const MIN_LENGTH: usize = 2;
+
+pub struct ShowcasePlugin;
+
+impl Plugin for ShowcasePlugin {
+    fn build(&self, app: &mut App) {
+        let args: Vec<String> = std::env::args().collect();
+        let showcase = Showcase::from_args(&args);
+        if showcase.is_empty() {
+            return;
+        }
+
+        if let Some(high_score) = showcase.high_score {
+            println!("Showcase: high score {}", high_score);
+            app.add_systems(Startup, move |mut leaderboard: ResMut<Leaderboard>| {
+                leaderboard.preload_high_score(high_score)
+            });
+        }
+        app.insert_resource(showcase);
+        app.add_systems(
+            OnEnter(GameState::InGame),
+            apply_showcase.run_if(resource_exists::<Showcase>),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct Showcase {
+    score: Option<usize>,
+    high_score: Option<usize>,
+    length: Option<usize>,
+}
+
+impl Showcase {
+    fn from_args(args: &[String]) -> Self {
+        Self {
+            score: read_value(args, SCORE_FLAG, SCORE_VAR),
+            high_score: read_value(args, HIGH_SCORE_FLAG, HIGH_SCORE_VAR),
+            length: read_value(args, LENGTH_FLAG, LENGTH_VAR).filter(|length| {
+                let valid = *length >= MIN_LENGTH;
+                if !valid {
+                    println!(
+                        "Showcase length must be at least {}, ignoring it",
+                        MIN_LENGTH
+                    );
+                }
+                valid
+            }),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.score.is_none() && self.high_score.is_none() && self.length.is_none()
+    }
+}
+
+// `--flag value` on the command line, else the environment variable
+fn read_value(args: &[String], flag: &str, var: &str) -> Option<usize> {
+    let raw = args
+        .iter()
+        .position(|arg| arg == flag)
+        .and_then(|idx| args.get(idx + 1).cloned())
+        .or_else(|| std::env::var(var).ok())?;
+
+    match raw.trim().parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            println!(
+                "Invalid showcase value for {}: {:?}, ignoring it",
+                flag, raw
+            );
+            None
+        }
+    }
+}
+
+// Lays the snake out backwards from the start cells, turning back and forth
+// like a lawnmower whenever it meets the edge of the field. None when the
+// snake doesn't fit.
+fn showcase_cells(
+    start_direction: StartDirection,
+    grid: &GridConfig,
+    length: usize,
+) -> Option<Vec<Position>> {
+    let (dx, dy) = start_direction.0.offset();
+    // Across the start direction, towards the side with more room
+    let toward_room = |from: i8, to: i8| if from.abs() >= to.abs() { -1 } else { 1 };
+    let (sx, sy) = if dx != 0 {
+        (0, toward_room(grid.from.1, grid.to.1))
+    } else {
+        (toward_room(grid.from.0, grid.to.0), 0)
+    };
+
+    let mut cells = vec![start_direction.head_position()];
+    let (mut step_x, mut step_y) = (-dx, -dy);
+    let mut current = StartDirection::BODY_POSITION;
+    while cells.len() < length {
+        if !grid.contains(&current) {
+            return None;
+        }
+        cells.push(current);
+
+        let next = Position {
+            x: current.x + step_x,
+            y: current.y + step_y,
+        };
+        if grid.contains(&next) {
+            current = next;
+        } else {
+            current = Position {
+                x: current.x + sx,
+                y: current.y + sy,
+            };
+            (step_x, step_y) = (-step_x, -step_y);
+        }
+    }
+    Some(cells)
+}
+
+// Runs once, on the first time the game is entered. The food is moved off the
+// longer snake by the regular spawn check.
+fn apply_showcase(
+    mut commands: Commands,
+    showcase: Res<Showcase>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    start_direction: Res<StartDirection>,
+    snake_skin: Res<SnakeSkin>,
+    mut score: ResMut<Score>,
+    mut run_stats: ResMut<RunStats>,
+    snake_query: Query<Entity, Or<(With<Head>, With<BodySegment>)>>,
+) {
+    commands.remove_resource::<Showcase>();
+
+    if let Some(value) = showcase.score {
+        println!("Showcase: score {}", value);
+        score.0 = value;
+    }
+
+    let Some(length) = showcase.length else {
+        return;
+    };
+    let Some(cells) = showcase_cells(*start_direction, &grid, length) else {
+        println!(
+            "Showcase: a snake of {} cells doesn't fit the field",
+            length
+        );
+        return;
+    };
+
+    println!("Showcase: length {}", length);
+    for entity in snake_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_snake(
+        &mut commands,
+        &cells,
+        start_direction.0,
+        &snake_skin,
+        *grid_origin,
+    );
+    run_stats.length = length;
+}
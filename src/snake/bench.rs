@@ -0,0 +1,149 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+
+use super::{
+    Dir, GrowConfig, HasMoved, MaxLength, NextSegment, ShowTrail, SnakeErrors, SnakeTurnEvent,
+    Timer, WaitForFirstInput, apply_segment_sprites, movements, rewind, spawn_body_segment,
+    spawn_head,
+};
+use crate::{
+    GameMode, GridConfig, GridOrigin, Peaceful, Position, RunStats,
+    assets_loader::{Skin, SnakeSkin},
+};
+
+// Headless timing of one `movements` tick for long snakes. Run it with
+//
+//     cargo run --release -- --bench-movements [LENGTHS...] [--ci]
+//
+// LENGTHS are body lengths, 50 200 1000 by default. With `--ci` the process
+// exits with an error when a tick takes longer than TICK_BUDGET on average.
+pub const BENCH_FLAG: &str = "--bench-movements";
+const CI_FLAG: &str = "--ci";
+const DEFAULT_LENGTHS: [usize; 3] = [50, 200, 1000];
+const WARMUP_TICKS: usize = 20;
+const MEASURED_TICKS: usize = 500;
+const TICK_BUDGET: Duration = Duration::from_millis(2);
+// Width of the serpentine the body starts in, keeps every cell inside i8 range
+const ROW_LENGTH: usize = 40;
+const MAX_LENGTH: usize = ROW_LENGTH * 128;
+
+pub fn run_movement_bench(args: &[String]) {
+    let ci = args.iter().any(|arg| arg == CI_FLAG);
+    let mut lengths: Vec<usize> = args
+        .iter()
+        .filter_map(|arg| arg.parse().ok())
+        .filter(|length| (1..=MAX_LENGTH).contains(length))
+        .collect();
+    if lengths.is_empty() {
+        lengths = DEFAULT_LENGTHS.to_vec();
+    }
+
+    let mut over_budget = false;
+    for length in lengths {
+        let per_tick = time_ticks(length);
+        println!(
+            "movements: {} segments, {:.1} us per tick",
+            length,
+            per_tick.as_secs_f64() * 1_000_000.0
+        );
+        over_budget |= per_tick > TICK_BUDGET;
+    }
+
+    if ci && over_budget {
+        println!("movements tick exceeded the {:?} budget", TICK_BUDGET);
+        std::process::exit(1);
+    }
+}
+
+fn time_ticks(length: usize) -> Duration {
+    let mut world = bench_world();
+    spawn_long_snake(&mut world, length);
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems((movements, apply_segment_sprites).chain());
+
+    for _ in 0..WARMUP_TICKS {
+        schedule.run(&mut world);
+    }
+
+    let start = Instant::now();
+    for _ in 0..MEASURED_TICKS {
+        schedule.run(&mut world);
+    }
+    start.elapsed() / MEASURED_TICKS as u32
+}
+
+// Just the resources `movements` reads. Endless mode wraps the head, so it
+// can keep moving right for any number of ticks.
+pub(super) fn bench_world() -> World {
+    let mut world = World::new();
+    world.insert_resource(Timer(0.0));
+    world.insert_resource(GameMode::Endless);
+    world.insert_resource(GridConfig::default());
+    world.init_resource::<GridOrigin>();
+    world.insert_resource(Peaceful(false));
+    world.insert_resource(WaitForFirstInput(false));
+    world.insert_resource(HasMoved(true));
+    world.init_resource::<rewind::AwaitingTurn>();
+    world.insert_resource(ShowTrail(false));
+    world.insert_resource(GrowConfig::default());
+    world.init_resource::<MaxLength>();
+    world.init_resource::<RunStats>();
+    world.insert_resource(SnakeErrors::default());
+    world.init_resource::<Events<SnakeTurnEvent>>();
+    world
+}
+
+// Sprites are never drawn, so empty handles are enough
+pub(crate) fn bench_skin() -> SnakeSkin {
+    SnakeSkin {
+        skins: vec![Skin {
+            name: "Bench",
+            path: "",
+            texture: Handle::default(),
+            texture_atlas_layout: Handle::default(),
+        }],
+        selected: 0,
+    }
+}
+
+// Lays the body out row by row behind the head, tail last
+fn spawn_long_snake(world: &mut World, length: usize) {
+    let positions: Vec<Position> = (0..length)
+        .map(|idx| {
+            let row = idx / ROW_LENGTH;
+            let column = idx % ROW_LENGTH;
+            let x = if row.is_multiple_of(2) {
+                -(column as i8)
+            } else {
+                column as i8 - (ROW_LENGTH as i8 - 1)
+            };
+            Position { x, y: -(row as i8) }
+        })
+        .collect();
+
+    let snake_skin = bench_skin();
+    let mut commands = world.commands();
+
+    let segments: Vec<Entity> = positions
+        .iter()
+        .map(|position| {
+            spawn_body_segment(&mut commands, position, &snake_skin, GridOrigin::default())
+        })
+        .collect();
+    for pair in segments.windows(2) {
+        commands.entity(pair[0]).insert(NextSegment(pair[1]));
+    }
+    spawn_head(
+        &mut commands,
+        &Position { x: 1, y: 0 },
+        Dir::Right,
+        segments[0],
+        &snake_skin,
+        GridOrigin::default(),
+    );
+
+    world.flush();
+    world.insert_resource(snake_skin);
+}
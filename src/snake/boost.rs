@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+
+use super::{
+    BodySegment, Head, NextSegment,
+    trail::{ShowTrail, spawn_trail},
+    update_segment_tiles,
+};
+use crate::{GameState, GridOrigin, Position, assets_loader::SnakeSkin};
+
+const DEFAULT_BOOST_FACTOR: f32 = 0.5;
+const DEFAULT_TICKS_PER_SEGMENT: u32 = 3;
+// Ctrl, like Shift for the dash, so it never clashes with a rebound key
+const BOOST_KEYS: [KeyCode; 2] = [KeyCode::ControlLeft, KeyCode::ControlRight];
+// Head plus this many segments is as short as boosting goes
+const MIN_BODY_SEGMENTS: usize = 1;
+
+pub struct BoostPlugin;
+
+impl Plugin for BoostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BoostConfig>();
+        app.init_resource::<Boosting>();
+        app.init_resource::<BoostTicks>();
+        app.add_systems(OnEnter(GameState::StartGame), reset_boost);
+    }
+}
+
+// Risk and reward: holding the boost key speeds the snake up, but every few
+// ticks of boosting eat its tail. Off unless enabled in config.ron.
+#[derive(Resource)]
+pub struct BoostConfig {
+    pub enabled: bool,
+    // Multiplier applied to the turn delay while boosting
+    pub factor: f32,
+    // Boosted ticks it takes to lose one tail segment
+    pub ticks_per_segment: u32,
+}
+
+impl Default for BoostConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            factor: DEFAULT_BOOST_FACTOR,
+            ticks_per_segment: DEFAULT_TICKS_PER_SEGMENT,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Boosting(pub bool);
+
+// Boosted ticks since the tail was last shortened
+#[derive(Resource, Default)]
+pub struct BoostTicks(u32);
+
+fn reset_boost(mut boosting: ResMut<Boosting>, mut boost_ticks: ResMut<BoostTicks>) {
+    boosting.0 = false;
+    boost_ticks.0 = 0;
+}
+
+pub(super) fn read_boost_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    boost_config: Res<BoostConfig>,
+    body_query: Query<(), With<BodySegment>>,
+    mut boosting: ResMut<Boosting>,
+) {
+    let key_held = keyboard_input.any_pressed(BOOST_KEYS);
+    let has_tail_to_spare = body_query.iter().count() > MIN_BODY_SEGMENTS;
+    boosting.0 = boost_config.enabled && key_held && has_tail_to_spare;
+}
+
+// Runs right after the tick. A boosted head leaves a trail behind, a lost
+// segment fades out where it was and the new last segment turns into the tail.
+pub(super) fn consume_tail(
+    mut commands: Commands,
+    boost_config: Res<BoostConfig>,
+    boosting: Res<Boosting>,
+    snake_skin: Res<SnakeSkin>,
+    show_trail: Res<ShowTrail>,
+    grid_origin: Res<GridOrigin>,
+    mut boost_ticks: ResMut<BoostTicks>,
+    head_query: Query<(Entity, Ref<Position>, &Sprite, &NextSegment), With<Head>>,
+    body_query: Query<(&Position, &Sprite, Option<&NextSegment>), With<BodySegment>>,
+) {
+    let Ok((head, head_pos, head_sprite, first_segment)) = head_query.single() else {
+        return;
+    };
+    if !boosting.0 || !head_pos.is_changed() {
+        return;
+    }
+
+    // The regular trail already covers the head when it's turned on
+    if !show_trail.0
+        && let Ok((first_pos, _, _)) = body_query.get(first_segment.0)
+        && let Some(atlas) = &head_sprite.texture_atlas
+    {
+        spawn_trail(
+            &mut commands,
+            first_pos,
+            atlas.index,
+            &snake_skin,
+            *grid_origin,
+        );
+    }
+
+    boost_ticks.0 += 1;
+    if boost_ticks.0 < boost_config.ticks_per_segment {
+        return;
+    }
+    boost_ticks.0 = 0;
+
+    let mut ordered_segments = vec![(head, *head_pos)];
+    let mut current = Some(first_segment.0);
+    while let Some(entity) = current
+        && let Ok((pos, _, next)) = body_query.get(entity)
+    {
+        ordered_segments.push((entity, *pos));
+        current = next.map(|next| next.0);
+    }
+    // The head always keeps a segment behind it
+    if ordered_segments.len() <= MIN_BODY_SEGMENTS + 1 {
+        return;
+    }
+
+    let Some((tail, tail_pos)) = ordered_segments.pop() else {
+        return;
+    };
+    if let Ok((_, sprite, _)) = body_query.get(tail)
+        && let Some(atlas) = &sprite.texture_atlas
+    {
+        spawn_trail(
+            &mut commands,
+            &tail_pos,
+            atlas.index,
+            &snake_skin,
+            *grid_origin,
+        );
+    }
+    commands.entity(tail).despawn();
+    if let Some((new_tail, _)) = ordered_segments.last() {
+        commands.entity(*new_tail).remove::<NextSegment>();
+    }
+    update_segment_tiles(&mut commands, &ordered_segments);
+}
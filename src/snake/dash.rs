@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+const DEFAULT_DASH_FACTOR: f32 = 0.5;
+const DEFAULT_DRAIN_RATE: f32 = 0.5;
+const DEFAULT_REGEN_RATE: f32 = 0.2;
+
+pub struct DashPlugin;
+
+impl Plugin for DashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DashConfig>();
+        app.init_resource::<Dashing>();
+        app.init_resource::<DashMeter>();
+        app.add_systems(Startup, init_dash_bar);
+        app.add_systems(OnEnter(GameState::StartGame), refill_dash_meter);
+        app.add_systems(Update, update_dash_bar);
+    }
+}
+
+#[derive(Resource)]
+pub struct DashConfig {
+    // Set to false for modes that don't allow dashing
+    pub enabled: bool,
+    // Multiplier applied to the turn delay while the dash key is held
+    pub factor: f32,
+    // Meter units (0.0 - 1.0) lost per second of dashing
+    pub drain_rate: f32,
+    // Meter units (0.0 - 1.0) regained per second while not dashing
+    pub regen_rate: f32,
+}
+
+impl Default for DashConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            factor: DEFAULT_DASH_FACTOR,
+            drain_rate: DEFAULT_DRAIN_RATE,
+            regen_rate: DEFAULT_REGEN_RATE,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Dashing(pub bool);
+
+// Remaining dash stamina, from 0.0 (empty) to 1.0 (full)
+#[derive(Resource)]
+pub struct DashMeter(pub f32);
+
+impl Default for DashMeter {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+#[derive(Component)]
+struct DashBarFrame;
+
+#[derive(Component)]
+struct DashBar;
+
+pub(super) fn read_dash_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    dash_config: Res<DashConfig>,
+    dash_meter: Res<DashMeter>,
+    mut dashing: ResMut<Dashing>,
+) {
+    let key_held =
+        keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+    dashing.0 = dash_config.enabled && key_held && dash_meter.0 > 0.0;
+}
+
+pub(super) fn update_dash_meter(
+    time: Res<Time>,
+    dash_config: Res<DashConfig>,
+    dashing: Res<Dashing>,
+    mut dash_meter: ResMut<DashMeter>,
+) {
+    let delta = if dashing.0 {
+        -dash_config.drain_rate * time.delta_secs()
+    } else {
+        dash_config.regen_rate * time.delta_secs()
+    };
+    dash_meter.0 = (dash_meter.0 + delta).clamp(0.0, 1.0);
+}
+
+fn refill_dash_meter(mut dash_meter: ResMut<DashMeter>) {
+    dash_meter.0 = 1.0;
+}
+
+fn init_dash_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(8.0),
+                left: Val::Px(8.0),
+                width: Val::Px(100.0),
+                height: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            DashBarFrame,
+        ))
+        .with_child((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.6, 1.0)),
+            DashBar,
+        ));
+}
+
+fn update_dash_bar(
+    dash_config: Res<DashConfig>,
+    dash_meter: Res<DashMeter>,
+    mut frame_query: Query<&mut Visibility, With<DashBarFrame>>,
+    mut bar_query: Query<&mut Node, With<DashBar>>,
+) {
+    if let Ok(mut visibility) = frame_query.single_mut() {
+        *visibility = if dash_config.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if let Ok(mut node) = bar_query.single_mut() {
+        node.width = Val::Percent(dash_meter.0 * 100.0);
+    }
+}
@@ -0,0 +1,413 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use super::{
+    AtlasTile, BodySegment, Dir, Head, determine_neck_type, determine_segment_type,
+    determine_tail_type, get_direction_between_positions, head_atlas_index,
+};
+use crate::{
+    GameState, GridOrigin, Position, TILE_SIZE, assets_loader::SnakeSkin, grid_to_screen_transform,
+};
+
+// Bundled run replayed on the main menu. One letter per tick: U, D, L or R.
+const DEMO_RUN_PATH: &str = "assets/demo_run.txt";
+const DEMO_TURN_DELAY: f32 = 0.25;
+const DEFAULT_IDLE_TIMEOUT: f32 = 5.0;
+// Shows several demo boards at once, see DemoGallery
+pub const GALLERY_FLAG: &str = "--gallery";
+// Width the gallery boards share, about what the camera shows across at the
+// default zoom, in world units
+const GALLERY_WIDTH: f32 = 192.0;
+// Space left between two gallery boards, in world units
+const GALLERY_GAP: f32 = TILE_SIZE;
+// Head first, same layout as a fresh game plus two extra segments
+const DEMO_START: [Position; 4] = [
+    Position { x: 1, y: 0 },
+    Position { x: 0, y: 0 },
+    Position { x: -1, y: 0 },
+    Position { x: -2, y: 0 },
+];
+
+pub struct DemoPlugin;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttractConfig>();
+        app.init_resource::<IdleTimer>();
+        app.init_resource::<DemoGallery>();
+        app.insert_resource(DemoRecording::load(DEMO_RUN_PATH));
+        app.add_systems(OnEnter(GameState::MainMenu), reset_idle_timer);
+        app.add_systems(OnExit(GameState::MainMenu), despawn_demo);
+        app.add_systems(
+            Update,
+            (
+                track_idle_time,
+                spawn_demo.run_if(no_demo_running),
+                step_demo,
+            )
+                .chain()
+                .run_if(in_state(GameState::MainMenu)),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct AttractConfig {
+    // Seconds without input on the main menu before the demo starts
+    pub idle_timeout: f32,
+}
+
+impl Default for AttractConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct DemoRecording(Vec<Dir>);
+
+impl DemoRecording {
+    fn load(path: &str) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                println!("Could not read {}: {}, demo disabled", path, err);
+                return Self(Vec::new());
+            }
+        };
+
+        let moves = contents
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(|line| line.chars())
+            .filter(|c| !c.is_whitespace())
+            .filter_map(|c| match c {
+                'U' => Some(Dir::Up),
+                'R' => Some(Dir::Right),
+                'D' => Some(Dir::Down),
+                'L' => Some(Dir::Left),
+                _ => {
+                    println!("Unknown move in {}: {}", path, c);
+                    None
+                }
+            })
+            .collect();
+
+        Self(moves)
+    }
+
+    // Lowest and highest cell any segment reaches during the replay
+    fn bounds(&self) -> (Position, Position) {
+        let mut positions = DEMO_START.to_vec();
+        let mut cells = positions.clone();
+        for dir in &self.0 {
+            advance(&mut positions, *dir);
+            cells.extend_from_slice(&positions);
+        }
+
+        let min = |axis: fn(&Position) -> i8| cells.iter().map(axis).min().unwrap_or(0);
+        let max = |axis: fn(&Position) -> i8| cells.iter().map(axis).max().unwrap_or(0);
+        (
+            Position {
+                x: min(|pos| pos.x),
+                y: min(|pos| pos.y),
+            },
+            Position {
+                x: max(|pos| pos.x),
+                y: max(|pos| pos.y),
+            },
+        )
+    }
+}
+
+#[derive(Resource, Default)]
+struct IdleTimer(f32);
+
+// How many demo boards replay at once. A single board in the middle by
+// default, the gallery lays several out side by side, scaled down to fit the
+// view. Every board only replays the bundled run, none of them can be played.
+#[derive(Resource)]
+pub struct DemoGallery {
+    boards: usize,
+}
+
+impl Default for DemoGallery {
+    fn default() -> Self {
+        Self { boards: 1 }
+    }
+}
+
+impl DemoGallery {
+    // Two boards next to each other, for `--gallery`
+    pub fn side_by_side() -> Self {
+        Self { boards: 2 }
+    }
+
+    // Boards of the gallery from left to right, each as wide as the cells
+    // from `min` to `max` and centered in its own slot
+    fn placements(&self, (min, max): (Position, Position)) -> Vec<BoardPlacement> {
+        if self.boards == 1 {
+            return vec![BoardPlacement {
+                offset: Vec2::ZERO,
+                scale: 1.0,
+            }];
+        }
+
+        let width = (max.x - min.x + 1) as f32 * TILE_SIZE;
+        let scale = (GALLERY_WIDTH / (self.boards as f32 * (width + GALLERY_GAP))).min(1.0);
+        let slot = (width + GALLERY_GAP) * scale;
+        let center = Vec2::new(
+            (min.x as f32 + max.x as f32) / 2.0,
+            (min.y as f32 + max.y as f32) / 2.0,
+        ) * TILE_SIZE;
+        (0..self.boards)
+            .map(|idx| BoardPlacement {
+                offset: Vec2::new((idx as f32 - (self.boards - 1) as f32 / 2.0) * slot, 0.0)
+                    - center * scale,
+                scale,
+            })
+            .collect()
+    }
+}
+
+// Where a demo board is drawn: its cells are scaled around cell 0, 0, then
+// moved by `offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BoardPlacement {
+    offset: Vec2,
+    scale: f32,
+}
+
+// Replay state of one demo board, which owns its segments. Boards don't
+// share anything, so any number of them can run at once.
+#[derive(Component)]
+struct DemoBoard {
+    positions: Vec<Position>,
+    step: usize,
+    timer: f32,
+    placement: BoardPlacement,
+}
+
+// Index along a demo snake, 0 is the head
+#[derive(Component)]
+struct DemoSegment {
+    board: Entity,
+    index: usize,
+}
+
+fn no_demo_running(board_query: Query<(), With<DemoBoard>>) -> bool {
+    board_query.is_empty()
+}
+
+fn reset_idle_timer(mut idle_timer: ResMut<IdleTimer>) {
+    idle_timer.0 = 0.0;
+}
+
+fn track_idle_time(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut idle_timer: ResMut<IdleTimer>,
+) {
+    if keyboard_input.get_just_pressed().next().is_some() {
+        idle_timer.0 = 0.0;
+    } else {
+        idle_timer.0 += time.delta_secs();
+    }
+}
+
+// Moves the snake one cell, the tail follows
+fn advance(positions: &mut Vec<Position>, dir: Dir) {
+    let (dx, dy) = dir.offset();
+    let head = positions[0];
+    positions.pop();
+    positions.insert(
+        0,
+        Position {
+            x: head.x + dx,
+            y: head.y + dy,
+        },
+    );
+}
+
+fn demo_transform(position: &Position, placement: BoardPlacement) -> Transform {
+    let mut transform = grid_to_screen_transform(position, GridOrigin::default());
+    transform.translation = transform.translation * placement.scale + placement.offset.extend(0.0);
+    transform.scale = Vec3::new(placement.scale, placement.scale, 1.0);
+    transform
+}
+
+fn spawn_demo(
+    mut commands: Commands,
+    idle_timer: Res<IdleTimer>,
+    attract_config: Res<AttractConfig>,
+    recording: Res<DemoRecording>,
+    gallery: Res<DemoGallery>,
+    snake_skin: Res<SnakeSkin>,
+    grid_origin: Res<GridOrigin>,
+    mut snake_query: Query<&mut Visibility, Or<(With<Head>, With<BodySegment>)>>,
+) {
+    if idle_timer.0 < attract_config.idle_timeout || recording.0.is_empty() {
+        return;
+    }
+
+    // The snake left over from the last game would overlap the demo
+    for mut visibility in snake_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+
+    // Boards start at different points of the recording so they don't move in lockstep
+    let placements = gallery.placements(recording.bounds());
+    for (idx, mut placement) in placements.iter().copied().enumerate() {
+        // The gallery moves with the board, so a single board still covers it
+        placement.offset += grid_origin.0;
+        let step = idx * recording.0.len() / placements.len();
+        let mut positions = DEMO_START.to_vec();
+        for dir in &recording.0[..step] {
+            advance(&mut positions, *dir);
+        }
+
+        let board = commands.spawn_empty().id();
+        for (index, position) in positions.iter().enumerate() {
+            let mut sprite = snake_skin.sprite(0);
+            set_demo_sprite(&mut sprite, index, &positions);
+            commands.spawn((
+                DemoSegment { board, index },
+                sprite,
+                demo_transform(position, placement),
+            ));
+        }
+        commands.entity(board).insert(DemoBoard {
+            positions,
+            step,
+            timer: DEMO_TURN_DELAY,
+            placement,
+        });
+    }
+}
+
+fn step_demo(
+    time: Res<Time>,
+    recording: Res<DemoRecording>,
+    mut board_query: Query<&mut DemoBoard>,
+    mut segment_query: Query<(&DemoSegment, &mut Sprite, &mut Transform)>,
+) {
+    for mut board in board_query.iter_mut() {
+        board.timer -= time.delta_secs();
+        if board.timer > 0.0 {
+            continue;
+        }
+        board.timer = DEMO_TURN_DELAY;
+
+        // Start over once the recording runs out
+        if board.step >= recording.0.len() {
+            board.step = 0;
+            board.positions = DEMO_START.to_vec();
+        } else {
+            let dir = recording.0[board.step];
+            advance(&mut board.positions, dir);
+            board.step += 1;
+        }
+    }
+
+    for (segment, mut sprite, mut transform) in segment_query.iter_mut() {
+        let Ok(board) = board_query.get(segment.board) else {
+            continue;
+        };
+        *transform = demo_transform(&board.positions[segment.index], board.placement);
+        set_demo_sprite(&mut sprite, segment.index, &board.positions);
+    }
+}
+
+// Picks the same head, neck, body and tail sprites the real snake uses
+fn set_demo_sprite(sprite: &mut Sprite, idx: usize, positions: &[Position]) {
+    let len = positions.len();
+    let atlas_tile = if idx == 0 {
+        let facing = positions
+            .get(1)
+            .and_then(|neck| get_direction_between_positions(neck, &positions[0]))
+            .unwrap_or(Dir::Right);
+        AtlasTile::new(head_atlas_index(facing))
+    } else if idx == len - 1 {
+        determine_tail_type(&positions[idx - 1], &positions[idx]).to_atlas_tile()
+    } else if idx == 1 {
+        determine_neck_type(&positions[0], &positions[1], &positions[2]).to_atlas_tile()
+    } else {
+        determine_segment_type(&positions[idx - 1], &positions[idx], &positions[idx + 1])
+            .to_atlas_tile()
+    };
+
+    atlas_tile.apply(sprite);
+}
+
+fn despawn_demo(
+    mut commands: Commands,
+    demo_query: Query<Entity, Or<(With<DemoBoard>, With<DemoSegment>)>>,
+    mut snake_query: Query<&mut Visibility, Or<(With<Head>, With<BodySegment>)>>,
+) {
+    for entity in demo_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for mut visibility in snake_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Left and right edge of a board in world units, a cell reaching half a
+    // tile past its center
+    fn horizontal_extent(placement: BoardPlacement, bounds: (Position, Position)) -> (f32, f32) {
+        let half_tile = TILE_SIZE / 2.0 * placement.scale;
+        let left = demo_transform(&bounds.0, placement).translation.x - half_tile;
+        let right = demo_transform(&bounds.1, placement).translation.x + half_tile;
+        (left, right)
+    }
+
+    #[test]
+    fn gallery_boards_fit_the_view_without_overlapping() {
+        let bounds = DemoRecording::load(DEMO_RUN_PATH).bounds();
+        for boards in 2..=4 {
+            let placements = DemoGallery { boards }.placements(bounds);
+            assert_eq!(placements.len(), boards);
+
+            let extents: Vec<_> = placements
+                .iter()
+                .map(|placement| horizontal_extent(*placement, bounds))
+                .collect();
+            assert!(extents[0].0 >= -GALLERY_WIDTH / 2.0, "{:?}", extents);
+            assert!(
+                extents[boards - 1].1 <= GALLERY_WIDTH / 2.0,
+                "{:?}",
+                extents
+            );
+            for pair in extents.windows(2) {
+                assert!(pair[0].1 < pair[1].0, "{:?}", extents);
+            }
+        }
+    }
+
+    #[test]
+    fn single_board_stays_on_the_game_grid() {
+        let bounds = DemoRecording::load(DEMO_RUN_PATH).bounds();
+        let placement = DemoGallery::default().placements(bounds)[0];
+        let cell = Position { x: 3, y: -2 };
+        assert_eq!(
+            demo_transform(&cell, placement),
+            grid_to_screen_transform(&cell, GridOrigin::default())
+        );
+    }
+
+    #[test]
+    fn bounds_cover_the_start_and_every_move() {
+        let recording = DemoRecording(vec![Dir::Up, Dir::Up, Dir::Left, Dir::Down]);
+        let (min, max) = recording.bounds();
+        assert_eq!((min.x, min.y), (-2, 0));
+        assert_eq!((max.x, max.y), (1, 2));
+    }
+}
@@ -0,0 +1,67 @@
+use std::{collections::HashSet, fmt};
+
+use bevy::{ecs::query::QuerySingleError, prelude::*};
+
+// Problems with the snake's entities that systems can't act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnakeError {
+    NoHead,
+    MultipleHeads,
+}
+
+impl fmt::Display for SnakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnakeError::NoHead => write!(f, "no snake head found"),
+            SnakeError::MultipleHeads => write!(f, "more than one snake head found"),
+        }
+    }
+}
+
+impl From<QuerySingleError> for SnakeError {
+    fn from(error: QuerySingleError) -> Self {
+        match error {
+            QuerySingleError::NoEntities(_) => SnakeError::NoHead,
+            QuerySingleError::MultipleEntities(_) => SnakeError::MultipleHeads,
+        }
+    }
+}
+
+// Remembers which errors were already logged so each system warns about each
+// kind of error once per game
+#[derive(Resource, Default)]
+pub struct SnakeErrors {
+    reported: HashSet<(&'static str, SnakeError)>,
+}
+
+impl SnakeErrors {
+    pub fn report(&mut self, system: &'static str, error: impl Into<SnakeError>) {
+        let error = error.into();
+        if self.reported.insert((system, error)) {
+            warn!("{}: {}, skipping", system, error);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.reported.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_system_reports_each_error_once() {
+        let mut errors = SnakeErrors::default();
+        errors.report("movements", SnakeError::NoHead);
+        errors.report("movements", SnakeError::NoHead);
+        errors.report("check_self_collision", SnakeError::NoHead);
+        errors.report("movements", SnakeError::MultipleHeads);
+        assert_eq!(errors.reported.len(), 3);
+
+        errors.clear();
+        errors.report("movements", SnakeError::NoHead);
+        assert_eq!(errors.reported.len(), 1);
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use super::{BodySegment, Head, NextSegment};
+
+const DEFAULT_HEAD_COLOR: (f32, f32, f32) = (1.0, 1.0, 1.0);
+const DEFAULT_TAIL_COLOR: (f32, f32, f32) = (0.45, 0.45, 0.45);
+
+pub struct GradientPlugin;
+
+impl Plugin for GradientPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BodyGradient>();
+        // After the tick's commands are applied, so a segment grown this frame
+        // is tinted before it is first drawn
+        app.add_systems(PostUpdate, apply_body_gradient);
+    }
+}
+
+// Tints the body from `head_color` next to the head to `tail_color` at the
+// tail. Off by default, the sprites keep their own colors then.
+#[derive(Resource)]
+pub struct BodyGradient {
+    pub enabled: bool,
+    pub head_color: (f32, f32, f32),
+    pub tail_color: (f32, f32, f32),
+}
+
+impl Default for BodyGradient {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            head_color: DEFAULT_HEAD_COLOR,
+            tail_color: DEFAULT_TAIL_COLOR,
+        }
+    }
+}
+
+impl BodyGradient {
+    // Color of body segment `index` out of `count`, 0 is the one behind the head
+    fn color(&self, index: usize, count: usize) -> Color {
+        if !self.enabled {
+            return Color::WHITE;
+        }
+        let t = if count > 1 {
+            index as f32 / (count - 1) as f32
+        } else {
+            0.0
+        };
+        let (r, g, b) = self.head_color;
+        let (tr, tg, tb) = self.tail_color;
+        Color::srgb(r + (tr - r) * t, g + (tg - g) * t, b + (tb - b) * t)
+    }
+}
+
+// Walks the body from the head, so the colors follow the snake's order.
+// Only sprites whose color actually differs are touched.
+fn apply_body_gradient(
+    gradient: Res<BodyGradient>,
+    head_query: Query<&NextSegment, With<Head>>,
+    mut body_query: Query<(&mut Sprite, Option<&NextSegment>), (With<BodySegment>, Without<Head>)>,
+) {
+    if !gradient.enabled && !gradient.is_changed() {
+        return;
+    }
+    let Ok(first_segment) = head_query.single() else {
+        return;
+    };
+
+    let mut ordered = Vec::new();
+    let mut current = Some(first_segment.0);
+    while let Some(entity) = current
+        && let Ok((_, next)) = body_query.get(entity)
+    {
+        ordered.push(entity);
+        current = next.map(|next| next.0);
+    }
+
+    for (index, entity) in ordered.iter().enumerate() {
+        if let Ok((mut sprite, _)) = body_query.get_mut(*entity) {
+            let color = gradient.color(index, ordered.len());
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+use crate::{GridOrigin, Position, grid_to_screen_transform};
+
+const DEFAULT_GROW_DURATION: f32 = 0.15;
+
+pub struct GrowPlugin;
+
+impl Plugin for GrowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GrowConfig>();
+        app.add_systems(Update, animate_growth);
+    }
+}
+
+#[derive(Resource)]
+pub struct GrowConfig {
+    // Seconds a new tail segment takes to scale up to full size, 0 to skip
+    pub duration: f32,
+}
+
+impl Default for GrowConfig {
+    fn default() -> Self {
+        Self {
+            duration: DEFAULT_GROW_DURATION,
+        }
+    }
+}
+
+// Seconds since the segment was added. Only the scale is animated, the
+// segment's Position counts for collisions from the first tick.
+#[derive(Component)]
+struct GrowAnim(f32);
+
+pub(super) fn start_growing(
+    commands: &mut Commands,
+    entity: Entity,
+    position: &Position,
+    grow_config: &GrowConfig,
+    grid_origin: GridOrigin,
+) {
+    if grow_config.duration <= 0.0 {
+        return;
+    }
+    commands.entity(entity).insert((
+        GrowAnim(0.0),
+        grid_to_screen_transform(position, grid_origin).with_scale(Vec3::ZERO),
+    ));
+}
+
+fn animate_growth(
+    mut commands: Commands,
+    time: Res<Time>,
+    grow_config: Res<GrowConfig>,
+    mut query: Query<(Entity, &mut GrowAnim, &mut Transform)>,
+) {
+    for (entity, mut grow_anim, mut transform) in query.iter_mut() {
+        grow_anim.0 += time.delta_secs();
+
+        let progress = if grow_config.duration > 0.0 {
+            (grow_anim.0 / grow_config.duration).min(1.0)
+        } else {
+            1.0
+        };
+        transform.scale = Vec3::splat(progress);
+
+        if progress >= 1.0 {
+            commands.entity(entity).remove::<GrowAnim>();
+        }
+    }
+}
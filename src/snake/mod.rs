@@ -1,11 +1,55 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    GameState, Position, Score, assets_loader::GameAssets, grid_to_screen_position,
-    grid_to_screen_transform,
+    CrashEvent, GameMode, GameOverEvent, GameState, GameplaySet, GridConfig, GridOrigin, Peaceful,
+    Position, RunStats, Score,
+    assets_loader::SnakeSkin,
+    controls::{InvertVertical, PlayerBindings, PlayerId, TurnMode},
+    grid_to_screen_position, grid_to_screen_transform,
+    lives::{self, SnakeRespawnEvent},
+    pause::AfkTimer,
+    speed_tiles::HeadSpeedFactor,
+    test_mode::TestMode,
 };
 
+mod bench;
+mod boost;
+mod dash;
+mod demo;
+mod error;
+mod gradient;
+mod grow;
+mod rewind;
+mod swipe;
+mod thickness;
+mod trail;
+
+#[cfg(test)]
+pub(crate) use bench::bench_skin;
+pub use bench::{BENCH_FLAG, run_movement_bench};
+pub use boost::BoostConfig;
+pub use dash::{DashConfig, Dashing};
+pub use demo::{AttractConfig, DemoGallery, GALLERY_FLAG};
+use error::SnakeError;
+pub use error::SnakeErrors;
+pub use gradient::BodyGradient;
+pub use grow::GrowConfig;
+pub use rewind::{Forgiving, RewindConfig};
+pub use swipe::SwipeConfig;
+pub use thickness::SnakeThickness;
+pub use trail::ShowTrail;
+
 const TIMER_TURN_DELAY: f32 = 0.8;
+const MAX_QUEUED_TURNS: usize = 2;
+// Seconds a FrameLocked frame counts for, a turn delay of 0.8 is 48 frames
+const FRAME_LOCKED_TICK: f32 = 1.0 / 60.0;
+// Only a lone head can turn around, anything longer would run into its neck
+const MAX_SHORT_LENGTH: usize = 1;
+const DEFAULT_RAMP_DURATION: f32 = 0.0;
+const DEFAULT_RAMP_START_DELAY: f32 = 1.6;
 
 pub struct SnakePlugin;
 
@@ -16,30 +60,184 @@ pub struct Head;
 pub struct BodySegment;
 
 #[derive(Component)]
-struct NextSegment(Entity);
+pub struct NextSegment(pub Entity);
 
 #[derive(Component)]
 pub struct Ate(pub bool);
 
+// The head moved in a different direction than on the previous tick
+#[derive(Event)]
+pub struct SnakeTurnEvent;
+
 #[derive(Resource)]
 struct Timer(f32);
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-enum Dir {
+// Seconds between ticks before the score speeds the snake up
+#[derive(Resource)]
+pub struct TurnDelay(pub f32);
+
+impl Default for TurnDelay {
+    fn default() -> Self {
+        Self(TIMER_TURN_DELAY)
+    }
+}
+
+// When enabled, the snake stays in place at the start of a round until the
+// player steers it for the first time
+#[derive(Resource, Default)]
+pub struct WaitForFirstInput(pub bool);
+
+#[derive(Resource, Default)]
+struct HasMoved(bool);
+
+// Eases players in: the turn delay starts at `start_delay` and reaches the
+// normal speed after `duration` seconds. A zero duration disables the ramp.
+#[derive(Resource)]
+pub struct RampConfig {
+    pub duration: f32,
+    pub start_delay: f32,
+}
+
+impl Default for RampConfig {
+    fn default() -> Self {
+        Self {
+            duration: DEFAULT_RAMP_DURATION,
+            start_delay: DEFAULT_RAMP_START_DELAY,
+        }
+    }
+}
+
+// Seconds since the snake spawned, pauses excluded
+#[derive(Resource, Default)]
+struct RampTimer(f32);
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Dir {
     Up,
     Right,
     Down,
     Left,
 }
 
+impl Dir {
+    pub fn opposite(self) -> Self {
+        match self {
+            Dir::Up => Dir::Down,
+            Dir::Right => Dir::Left,
+            Dir::Down => Dir::Up,
+            Dir::Left => Dir::Right,
+        }
+    }
+
+    // Cell offset of one step in this direction
+    pub fn offset(self) -> (i8, i8) {
+        match self {
+            Dir::Up => (0, 1),
+            Dir::Right => (1, 0),
+            Dir::Down => (0, -1),
+            Dir::Left => (-1, 0),
+        }
+    }
+}
+
 #[derive(Component)]
 struct Direction(Dir);
 
+// Way the snake faces when a round starts, the first body segment is placed
+// at the origin and the head one cell further in this direction
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct StartDirection(pub Dir);
+
+impl Default for StartDirection {
+    fn default() -> Self {
+        Self(Dir::Right)
+    }
+}
+
+impl StartDirection {
+    pub const BODY_POSITION: Position = Position { x: 0, y: 0 };
+
+    pub fn head_position(&self) -> Position {
+        let (dx, dy) = self.0.offset();
+        Position {
+            x: Self::BODY_POSITION.x + dx,
+            y: Self::BODY_POSITION.y + dy,
+        }
+    }
+}
+
+// Direction of the last step the head took
 #[derive(Component)]
-struct LastDirection(Dir);
+pub struct LastDirection(pub Dir);
+
+// Whether the snake may turn straight back the way it came
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ReversalPolicy {
+    #[default]
+    Never,
+    // Short snakes have no neck to run into, so they can reverse freely
+    AllowWhenShort,
+}
 
-// Enum to represent different types of body segments
-#[derive(Debug, Clone, Copy, PartialEq)]
+// The classic "can't bite your own neck" rule: on a tick the snake turns, the
+// head may land on the segment behind the neck, the only one it can reach
+// again that quickly, without crashing
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct NeckGrace(pub bool);
+
+impl ReversalPolicy {
+    // Whether a snake of `length` cells, head included, may take `new_direction`
+    pub fn allows(self, current: Dir, new_direction: Dir, length: usize) -> bool {
+        new_direction != current.opposite()
+            || (self == ReversalPolicy::AllowWhenShort && length <= MAX_SHORT_LENGTH)
+    }
+}
+
+// How the snake's timer advances. RealTime follows the wall clock, FrameLocked
+// advances it by a fixed FRAME_LOCKED_TICK every rendered frame, so the same
+// inputs on the same frames always give the same run, however long the
+// frames took.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum TimingMode {
+    #[default]
+    RealTime,
+    FrameLocked,
+}
+
+// Cap on the snake's length in cells, head included. Food eaten at the cap
+// still scores, the snake just doesn't grow. None grows without limit.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct MaxLength(pub Option<usize>);
+
+impl MaxLength {
+    fn allows_growth(self, length: usize) -> bool {
+        self.0.is_none_or(|max| length < max)
+    }
+}
+
+// Cells of the snake starting at `first_segment`, head included
+fn snake_length(
+    first_segment: Option<Entity>,
+    body_query: &Query<Option<&NextSegment>, (With<BodySegment>, Without<Head>)>,
+) -> usize {
+    let mut length = 1;
+    let mut current = first_segment;
+    while let Some(entity) = current
+        && let Ok(next) = body_query.get(entity)
+    {
+        length += 1;
+        current = next.map(|next| next.0);
+    }
+    length
+}
+
+// Turns tapped faster than the snake moves, applied one per tick
+#[derive(Component, Default)]
+struct TurnQueue(VecDeque<Dir>);
+
+// Enum to represent different types of body segments. Kept on each segment,
+// apply_segment_sprites draws it.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
 enum SegmentType {
     // Straight segments
     Horizontal, // Left-Right or Right-Left
@@ -57,29 +255,120 @@ enum SegmentType {
     TailDown,
     TailLeft,
 
+    // Neck segment (first body segment right after the head turned),
+    // named by the previous movement direction and the new head direction
+    NeckRightUp,
+    NeckRightDown,
+    NeckLeftUp,
+    NeckLeftDown,
+    NeckUpRight,
+    NeckUpLeft,
+    NeckDownRight,
+    NeckDownLeft,
+
     // Fallback
     None,
 }
 
 impl SegmentType {
-    // Map segment types to sprite atlas indices
-    fn to_atlas_index(self) -> usize {
+    const ALL: [SegmentType; 19] = [
+        SegmentType::Horizontal,
+        SegmentType::Vertical,
+        SegmentType::CornerRightUp,
+        SegmentType::CornerDownRight,
+        SegmentType::CornerLeftDown,
+        SegmentType::CornerUpLeft,
+        SegmentType::TailUp,
+        SegmentType::TailRight,
+        SegmentType::TailDown,
+        SegmentType::TailLeft,
+        SegmentType::NeckRightUp,
+        SegmentType::NeckRightDown,
+        SegmentType::NeckLeftUp,
+        SegmentType::NeckLeftDown,
+        SegmentType::NeckUpRight,
+        SegmentType::NeckUpLeft,
+        SegmentType::NeckDownRight,
+        SegmentType::NeckDownLeft,
+        SegmentType::None,
+    ];
+
+    // Map segment types to sprite atlas tiles. Mirrored orientations reuse
+    // one tile and flip it, so the atlas needs a single cell per shape.
+    fn to_atlas_tile(self) -> AtlasTile {
         match self {
-            SegmentType::Horizontal => 4,      // Horizontal straight segment
-            SegmentType::Vertical => 5,        // Vertical straight segment
-            SegmentType::CornerDownRight => 8, //
-            SegmentType::CornerLeftDown => 9,  //
-            SegmentType::CornerUpLeft => 13,   //
-            SegmentType::CornerRightUp => 12,  //
-            SegmentType::TailRight => 11,
-            SegmentType::TailDown => 14,
-            SegmentType::TailLeft => 15,
-            SegmentType::TailUp => 10,
-            SegmentType::None => 7,
+            SegmentType::Horizontal => AtlasTile::new(4), // Horizontal straight segment
+            SegmentType::Vertical => AtlasTile::new(5),   // Vertical straight segment
+            SegmentType::CornerDownRight => AtlasTile::new(8),
+            SegmentType::CornerLeftDown => AtlasTile::new(8).flip_x(),
+            SegmentType::CornerRightUp => AtlasTile::new(8).flip_y(),
+            SegmentType::CornerUpLeft => AtlasTile::new(8).flip_x().flip_y(),
+            SegmentType::TailUp => AtlasTile::new(9),
+            SegmentType::TailDown => AtlasTile::new(9).flip_y(),
+            SegmentType::TailRight => AtlasTile::new(10),
+            SegmentType::TailLeft => AtlasTile::new(10).flip_x(),
+            SegmentType::NeckRightUp => AtlasTile::new(15),
+            SegmentType::NeckRightDown => AtlasTile::new(15).flip_y(),
+            SegmentType::NeckLeftUp => AtlasTile::new(15).flip_x(),
+            SegmentType::NeckLeftDown => AtlasTile::new(15).flip_x().flip_y(),
+            SegmentType::NeckUpRight => AtlasTile::new(16),
+            SegmentType::NeckUpLeft => AtlasTile::new(16).flip_x(),
+            SegmentType::NeckDownRight => AtlasTile::new(16).flip_y(),
+            SegmentType::NeckDownLeft => AtlasTile::new(16).flip_x().flip_y(),
+            SegmentType::None => AtlasTile::new(7),
         }
     }
 }
 
+// Atlas cell plus the mirroring applied when drawing it
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AtlasTile {
+    index: usize,
+    flip_x: bool,
+    flip_y: bool,
+}
+
+impl AtlasTile {
+    const fn new(index: usize) -> Self {
+        Self {
+            index,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    const fn flip_x(self) -> Self {
+        Self {
+            flip_x: !self.flip_x,
+            ..self
+        }
+    }
+
+    const fn flip_y(self) -> Self {
+        Self {
+            flip_y: !self.flip_y,
+            ..self
+        }
+    }
+
+    fn apply(self, sprite: &mut Sprite) {
+        if let Some(ref mut atlas) = sprite.texture_atlas {
+            atlas.index = self.index;
+        }
+        sprite.flip_x = self.flip_x;
+        sprite.flip_y = self.flip_y;
+    }
+}
+
+// Every atlas cell the snake can be drawn with: the four heads and all segment types
+pub fn atlas_indices() -> impl Iterator<Item = usize> {
+    (0..4).chain(
+        SegmentType::ALL
+            .iter()
+            .map(|segment| segment.to_atlas_tile().index),
+    )
+}
+
 // Helper function to get direction between two positions
 fn get_direction_between_positions(from: &Position, to: &Position) -> Option<Dir> {
     let dx = to.x - from.x;
@@ -163,6 +452,31 @@ fn determine_segment_type(
     }
 }
 
+// The neck is the segment the head just left. When the head turned on the last
+// tick, use a neck sprite that flares out towards the head instead of a plain corner.
+fn determine_neck_type(
+    head_pos: &Position,
+    neck_pos: &Position,
+    next_pos: &Position,
+) -> SegmentType {
+    let previous_direction = get_direction_between_positions(next_pos, neck_pos);
+    let head_direction = get_direction_between_positions(neck_pos, head_pos);
+
+    match (previous_direction, head_direction) {
+        (Some(Dir::Right), Some(Dir::Up)) => SegmentType::NeckRightUp,
+        (Some(Dir::Right), Some(Dir::Down)) => SegmentType::NeckRightDown,
+        (Some(Dir::Left), Some(Dir::Up)) => SegmentType::NeckLeftUp,
+        (Some(Dir::Left), Some(Dir::Down)) => SegmentType::NeckLeftDown,
+        (Some(Dir::Up), Some(Dir::Right)) => SegmentType::NeckUpRight,
+        (Some(Dir::Up), Some(Dir::Left)) => SegmentType::NeckUpLeft,
+        (Some(Dir::Down), Some(Dir::Right)) => SegmentType::NeckDownRight,
+        (Some(Dir::Down), Some(Dir::Left)) => SegmentType::NeckDownLeft,
+
+        // No turn: the neck is a regular straight segment
+        _ => determine_segment_type(head_pos, neck_pos, next_pos),
+    }
+}
+
 fn determine_tail_type(prev_pos: &Position, tail_pos: &Position) -> SegmentType {
     let direction_to_next = get_direction_between_positions(prev_pos, tail_pos);
 
@@ -184,17 +498,67 @@ fn determine_tail_type(prev_pos: &Position, tail_pos: &Position) -> SegmentType
 
 impl Plugin for SnakePlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<SnakeErrors>();
+        app.add_event::<SnakeTurnEvent>();
+        app.init_resource::<WaitForFirstInput>();
+        app.init_resource::<TurnDelay>();
+        app.init_resource::<ReversalPolicy>();
+        app.init_resource::<NeckGrace>();
+        app.init_resource::<MaxLength>();
+        app.init_resource::<TimingMode>();
+        app.init_resource::<HasMoved>();
+        app.init_resource::<RampConfig>();
+        app.init_resource::<RampTimer>();
+        app.init_resource::<StartDirection>();
+        app.add_plugins((
+            boost::BoostPlugin,
+            dash::DashPlugin,
+            demo::DemoPlugin,
+            gradient::GradientPlugin,
+            grow::GrowPlugin,
+            rewind::RewindPlugin,
+            swipe::SwipePlugin,
+            thickness::ThicknessPlugin,
+            trail::TrailPlugin,
+        ));
         app.add_systems(Startup, startup);
+        app.add_systems(PostUpdate, apply_segment_sprites);
 
-        app.add_systems(OnEnter(GameState::StartGame), (despawn_snake, init_snake));
+        app.add_systems(
+            OnEnter(GameState::StartGame),
+            (
+                despawn_snake,
+                init_snake,
+                clear_snake_errors,
+                reset_has_moved,
+                reset_ramp_timer,
+            ),
+        );
+        // A lost life puts the snake back at the start, like a new round
+        app.add_systems(
+            Update,
+            (despawn_snake, init_snake, reset_has_moved)
+                .chain()
+                .after(GameplaySet::Crash)
+                .run_if(on_event::<SnakeRespawnEvent>)
+                .run_if(in_state(GameState::InGame)),
+        );
 
         app.add_systems(
             Update,
             (
                 handle_inputs,
+                swipe::read_swipe_input,
+                apply_queued_turn,
+                dash::read_dash_input,
+                dash::update_dash_meter,
+                boost::read_boost_input,
                 update_timer,
-                movements,
-                check_self_collision,
+                (movements, boost::consume_tail).in_set(GameplaySet::Movement),
+                check_self_collision
+                    .run_if(resource_equals(Peaceful(false)))
+                    .run_if(lives::head_vulnerable)
+                    .in_set(GameplaySet::Collision),
                 reset_timer,
             )
                 .chain()
@@ -211,28 +575,70 @@ fn startup(mut commands: Commands) {
 fn spawn_head(
     commands: &mut Commands,
     position: &Position,
+    direction: Dir,
     initial_body_segment: Entity,
-    game_assets: Res<GameAssets>,
+    snake_skin: &SnakeSkin,
+    grid_origin: GridOrigin,
+) -> Entity {
+    let screen_position = grid_to_screen_transform(position, grid_origin);
+    commands
+        .spawn((
+            Head,
+            PlayerId::One,
+            snake_skin.sprite(head_atlas_index(direction)),
+            *position,
+            screen_position,
+            Direction(direction),
+            LastDirection(direction),
+            TurnQueue::default(),
+            NextSegment(initial_body_segment),
+            Ate(false),
+        ))
+        .id()
+}
+
+// Rebuilds a snake on `cells`, head first, e.g. from a saved game. A snake
+// always has a body, so fewer than two cells spawn nothing.
+pub fn spawn_snake(
+    commands: &mut Commands,
+    cells: &[Position],
+    direction: Dir,
+    snake_skin: &SnakeSkin,
+    grid_origin: GridOrigin,
 ) {
-    let screen_position = grid_to_screen_transform(position);
-    commands.spawn((
-        Head,
-        Sprite {
-            image: game_assets.texture.clone(),
-            texture_atlas: Some(TextureAtlas {
-                layout: game_assets.texture_atlas_layout.clone(),
-                index: 1,
-                ..default()
-            }),
-            ..default()
-        },
-        *position,
-        screen_position,
-        Direction(Dir::Right),
-        LastDirection(Dir::Right),
-        NextSegment(initial_body_segment),
-        Ate(false),
-    ));
+    let Some((head_pos, body)) = cells.split_first() else {
+        return;
+    };
+    if body.is_empty() {
+        return;
+    }
+
+    // Spawned from the tail up, so each segment can point at the one behind it
+    let mut ordered_segments = Vec::with_capacity(cells.len());
+    let mut behind: Option<Entity> = None;
+    for position in body.iter().rev() {
+        let entity = spawn_body_segment(commands, position, snake_skin, grid_origin);
+        if let Some(behind) = behind {
+            commands.entity(entity).insert(NextSegment(behind));
+        }
+        behind = Some(entity);
+        ordered_segments.push((entity, *position));
+    }
+
+    let Some(first_segment) = behind else {
+        return;
+    };
+    let head = spawn_head(
+        commands,
+        head_pos,
+        direction,
+        first_segment,
+        snake_skin,
+        grid_origin,
+    );
+    ordered_segments.push((head, *head_pos));
+    ordered_segments.reverse();
+    update_segment_tiles(commands, &ordered_segments);
 }
 
 fn despawn_snake(
@@ -248,41 +654,219 @@ fn despawn_snake(
     }
 }
 
-fn init_snake(mut commands: Commands, game_assets: Res<GameAssets>) {
+fn clear_snake_errors(mut snake_errors: ResMut<SnakeErrors>) {
+    snake_errors.clear();
+}
+
+fn reset_has_moved(mut has_moved: ResMut<HasMoved>) {
+    has_moved.0 = false;
+}
+
+fn reset_ramp_timer(mut ramp_timer: ResMut<RampTimer>) {
+    ramp_timer.0 = 0.0;
+}
+
+fn init_snake(
+    mut commands: Commands,
+    snake_skin: Res<SnakeSkin>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    start_direction: Res<StartDirection>,
+) {
+    let mut start_direction = *start_direction;
+    if !grid.contains(&start_direction.head_position()) {
+        debug!(
+            "Snake facing {:?} would start outside the field, facing Right instead",
+            start_direction.0
+        );
+        start_direction = StartDirection::default();
+    }
+
     // BodySegment
-    let position = Position { x: 0, y: 0 };
-    let initial_body_segment = spawn_body_segment(&mut commands, &position, &game_assets);
+    let initial_body_segment = spawn_body_segment(
+        &mut commands,
+        &StartDirection::BODY_POSITION,
+        &snake_skin,
+        *grid_origin,
+    );
 
     // Head
-    let position = Position { x: 1, y: 0 };
-    spawn_head(&mut commands, &position, initial_body_segment, game_assets);
+    spawn_head(
+        &mut commands,
+        &start_direction.head_position(),
+        start_direction.0,
+        initial_body_segment,
+        &snake_skin,
+        *grid_origin,
+    );
 }
 
-fn update_timer(time: Res<Time>, mut timer: ResMut<Timer>) {
-    timer.0 -= time.delta_secs();
+// Dashing and boosting drain the timer faster instead of shortening the next
+// delay, so pressing or releasing their keys takes effect on the current turn.
+fn update_timer(
+    time: Res<Time>,
+    mut timer: ResMut<Timer>,
+    mut ramp_timer: ResMut<RampTimer>,
+    dashing: Res<Dashing>,
+    dash_config: Res<DashConfig>,
+    boosting: Res<boost::Boosting>,
+    boost_config: Res<BoostConfig>,
+    timing_mode: Res<TimingMode>,
+    test_mode: Res<TestMode>,
+) {
+    let delta_secs = match *timing_mode {
+        TimingMode::RealTime => test_mode.delta_secs(&time),
+        TimingMode::FrameLocked => FRAME_LOCKED_TICK,
+    };
+    ramp_timer.0 += delta_secs;
+
+    let mut delta = delta_secs;
+    if dashing.0 {
+        delta /= dash_config.factor;
+    }
+    if boosting.0 {
+        delta /= boost_config.factor;
+    }
+    timer.0 -= delta;
 }
 
-fn reset_timer(mut timer: ResMut<Timer>, score: Res<Score>) {
+fn reset_timer(
+    mut timer: ResMut<Timer>,
+    turn_delay: Res<TurnDelay>,
+    score: Res<Score>,
+    ramp_config: Res<RampConfig>,
+    ramp_timer: Res<RampTimer>,
+    dashing: Res<Dashing>,
+    dash_config: Res<DashConfig>,
+    boosting: Res<boost::Boosting>,
+    boost_config: Res<BoostConfig>,
+    head_speed_factor: Res<HeadSpeedFactor>,
+    mut run_stats: ResMut<RunStats>,
+) {
     if timer.0 < 0.0 {
-        timer.0 = TIMER_TURN_DELAY - (score.0 as f32 / 20.0);
+        let delay = turn_delay.0 - (score.0 as f32 / 20.0);
+        let delay = if ramp_config.duration > 0.0 {
+            let progress = (ramp_timer.0 / ramp_config.duration).min(1.0);
+            ramp_config.start_delay.lerp(delay, progress)
+        } else {
+            delay
+        };
+        // Speed tiles scale the delay, dashing and boosting then drain it faster on top
+        timer.0 = delay * head_speed_factor.0;
+
+        let mut effective_delay = timer.0;
+        if dashing.0 {
+            effective_delay *= dash_config.factor;
+        }
+        if boosting.0 {
+            effective_delay *= boost_config.factor;
+        }
+        if effective_delay > 0.0 {
+            run_stats.top_speed = run_stats.top_speed.max(1.0 / effective_delay);
+        }
     }
 }
 
 fn handle_inputs(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut head_query: Query<(&mut Direction, &LastDirection), With<Head>>,
+    player_bindings: Res<PlayerBindings>,
+    invert_vertical: Res<InvertVertical>,
+    turn_mode: Res<TurnMode>,
+    reversal_policy: Res<ReversalPolicy>,
+    mut head_query: Query<
+        (
+            &PlayerId,
+            &mut Direction,
+            &LastDirection,
+            &mut TurnQueue,
+            Option<&NextSegment>,
+        ),
+        With<Head>,
+    >,
+    body_query: Query<Option<&NextSegment>, (With<BodySegment>, Without<Head>)>,
+    mut has_moved: ResMut<HasMoved>,
+    mut afk_timer: ResMut<AfkTimer>,
+    mut snake_errors: ResMut<SnakeErrors>,
 ) {
-    if let Ok(mut head) = head_query.single_mut() {
-        let last_direction = head.1.0;
+    if head_query.is_empty() {
+        snake_errors.report("handle_inputs", SnakeError::NoHead);
+        return;
+    }
+
+    // Each head listens to its own player's keys
+    for (player, mut direction, last_direction, mut turn_queue, first_segment) in
+        head_query.iter_mut()
+    {
+        let key_bindings = player_bindings.get(*player);
+        // Holding a key counts too, even when it can't turn the snake right now
+        if keyboard_input.any_pressed(key_bindings.steering()) {
+            afk_timer.0 = 0.0;
+        }
+        // The reversal guard checks the resulting direction, so it holds either way
+        let (up_key, down_key) = invert_vertical.vertical_keys(key_bindings);
+        let length = snake_length(first_segment.map(|next| next.0), &body_query);
+
+        let active: Vec<Dir> = [
+            (key_bindings.left, Dir::Left),
+            (key_bindings.right, Dir::Right),
+            (up_key, Dir::Up),
+            (down_key, Dir::Down),
+        ]
+        .into_iter()
+        .filter(|(key, _)| turn_mode.is_active(&keyboard_input, *key))
+        .map(|(_, dir)| dir)
+        .collect();
+        let mut pressed = without_conflicts(&active).into_iter();
+
+        match *turn_mode {
+            TurnMode::Held => {
+                let Some(new_direction) =
+                    pressed.find(|dir| reversal_policy.allows(last_direction.0, *dir, length))
+                else {
+                    continue;
+                };
+                direction.0 = new_direction;
+                has_moved.0 = true;
+            }
+            // Each tap is checked against the turn before it, so quick
+            // combinations like up-left within one tick both take effect
+            TurnMode::Tap => {
+                for new_direction in pressed {
+                    let previous = turn_queue.0.back().copied().unwrap_or(direction.0);
+                    if new_direction == previous
+                        || !reversal_policy.allows(previous, new_direction, length)
+                        || turn_queue.0.len() >= MAX_QUEUED_TURNS
+                    {
+                        continue;
+                    }
+                    turn_queue.0.push_back(new_direction);
+                    has_moved.0 = true;
+                }
+            }
+        }
+    }
+}
 
-        if keyboard_input.pressed(KeyCode::KeyA) && last_direction != Dir::Right {
-            head.0.0 = Dir::Left;
-        } else if keyboard_input.pressed(KeyCode::KeyD) && last_direction != Dir::Left {
-            head.0.0 = Dir::Right;
-        } else if keyboard_input.pressed(KeyCode::KeyW) && last_direction != Dir::Down {
-            head.0.0 = Dir::Up;
-        } else if keyboard_input.pressed(KeyCode::KeyS) && last_direction != Dir::Up {
-            head.0.0 = Dir::Down;
+// Left with right, or up with down, cancel each other out so the snake keeps
+// its course instead of favoring whichever key is checked first. The other
+// axis still counts, e.g. left+right+up turns up.
+fn without_conflicts(active: &[Dir]) -> Vec<Dir> {
+    active
+        .iter()
+        .copied()
+        .filter(|dir| !active.contains(&dir.opposite()))
+        .collect()
+}
+
+// Starts the next queued turn once the previous one was taken on a tick
+fn apply_queued_turn(
+    mut head_query: Query<(&mut Direction, &LastDirection, &mut TurnQueue), With<Head>>,
+) {
+    for (mut direction, last_direction, mut turn_queue) in head_query.iter_mut() {
+        if direction.0 == last_direction.0
+            && let Some(next) = turn_queue.0.pop_front()
+        {
+            direction.0 = next;
         }
     }
 }
@@ -307,13 +891,30 @@ fn movements(
         (Entity, &mut Position, &mut Transform, Option<&NextSegment>),
         (With<BodySegment>, Without<Head>),
     >,
-    game_assets: Res<GameAssets>,
+    snake_skin: Res<SnakeSkin>,
+    game_mode: Res<GameMode>,
+    grid: Res<GridConfig>,
+    peaceful: Res<Peaceful>,
+    wait_for_first_input: Res<WaitForFirstInput>,
+    has_moved: Res<HasMoved>,
+    awaiting_turn: Res<rewind::AwaitingTurn>,
+    show_trail: Res<ShowTrail>,
+    // Grouped to stay within Bevy's system parameter limit
+    (grow_config, max_length, grid_origin): (Res<GrowConfig>, Res<MaxLength>, Res<GridOrigin>),
+    mut run_stats: ResMut<RunStats>,
+    mut turn_events: EventWriter<SnakeTurnEvent>,
+    mut snake_errors: ResMut<SnakeErrors>,
 ) {
     if timer.0 > 0.0 {
         return;
     }
 
-    if let Ok((
+    // Hold the snake in place until the player picks a direction
+    if (wait_for_first_input.0 || awaiting_turn.0) && !has_moved.0 {
+        return;
+    }
+
+    let (
         head_entity,
         mut head_pos,
         mut sprite,
@@ -322,142 +923,200 @@ fn movements(
         mut snake_ate,
         head_direction,
         head_next_segment,
-    )) = head_query.single_mut()
+    ) = match head_query.single_mut() {
+        Ok(head) => head,
+        Err(err) => {
+            snake_errors.report("movements", err);
+            return;
+        }
+    };
+
+    let prev_head_pos = *head_pos;
+
+    if show_trail.0
+        && let Some(atlas) = &sprite.texture_atlas
     {
-        let prev_head_pos = *head_pos;
+        trail::spawn_trail(
+            &mut commands,
+            &prev_head_pos,
+            atlas.index,
+            &snake_skin,
+            *grid_origin,
+        );
+    }
 
-        // Update head sprite and position
-        match head_direction.0 {
-            Dir::Left => {
-                if let Some(ref mut atlas) = sprite.texture_atlas {
-                    atlas.index = 3;
-                }
-                head_pos.x -= 1;
-            }
-            Dir::Right => {
-                if let Some(ref mut atlas) = sprite.texture_atlas {
-                    atlas.index = 1;
-                }
-                head_pos.x += 1;
-            }
-            Dir::Up => {
-                if let Some(ref mut atlas) = sprite.texture_atlas {
-                    atlas.index = 0;
-                }
-                head_pos.y += 1;
-            }
-            Dir::Down => {
-                if let Some(ref mut atlas) = sprite.texture_atlas {
-                    atlas.index = 2;
-                }
-                head_pos.y -= 1;
-            }
-        }
+    // Update head sprite and position
+    if let Some(ref mut atlas) = sprite.texture_atlas {
+        atlas.index = head_atlas_index(head_direction.0);
+    }
+    let (dx, dy) = head_direction.0.offset();
+    head_pos.x += dx;
+    head_pos.y += dy;
 
-        head_last_direction.0 = head_direction.0;
+    if *game_mode == GameMode::Endless || peaceful.0 {
+        wrap_position(&mut head_pos, game_mode.field_bounds(&grid));
+    }
 
-        head_transform.translation = grid_to_screen_position(&head_pos);
+    if head_last_direction.0 != head_direction.0 {
+        turn_events.write(SnakeTurnEvent);
+    }
+    head_last_direction.0 = head_direction.0;
 
-        let mut ordered_segments = vec![(head_entity, *head_pos)];
+    head_transform.translation = grid_to_screen_position(&head_pos, *grid_origin);
+    run_stats.moves += 1;
 
-        let mut current_segment_id = head_next_segment.0;
-        let mut prev_pos = prev_head_pos;
-        let mut last_segment_entity: Option<Entity> = None;
+    let mut ordered_segments = vec![(head_entity, *head_pos)];
 
-        loop {
-            if let Ok((entity, mut segment_pos, mut segment_transform, next_segment)) =
-                body_query.get_mut(current_segment_id)
-            {
-                let old_segment_pos = *segment_pos;
-                *segment_pos = prev_pos;
-                segment_transform.translation = grid_to_screen_position(&segment_pos);
-                prev_pos = old_segment_pos;
-                ordered_segments.push((current_segment_id, *segment_pos));
+    let mut current_segment_id = head_next_segment.0;
+    let mut prev_pos = prev_head_pos;
+    let mut last_segment_entity: Option<Entity> = None;
 
-                if let Some(next) = next_segment {
-                    current_segment_id = next.0;
-                } else {
-                    last_segment_entity = Some(entity);
-                    break;
-                }
-            } else {
-                break;
-            }
+    while let Ok((entity, mut segment_pos, mut segment_transform, next_segment)) =
+        body_query.get_mut(current_segment_id)
+    {
+        let old_segment_pos = *segment_pos;
+        *segment_pos = prev_pos;
+        segment_transform.translation = grid_to_screen_position(&segment_pos, *grid_origin);
+        prev_pos = old_segment_pos;
+        ordered_segments.push((current_segment_id, *segment_pos));
+
+        if let Some(next) = next_segment {
+            current_segment_id = next.0;
+        } else {
+            last_segment_entity = Some(entity);
+            break;
         }
+    }
 
-        if snake_ate.0 {
-            if let Some(last_entity) = last_segment_entity {
-                snake_ate.0 = false;
+    if snake_ate.0 && !max_length.allows_growth(ordered_segments.len()) {
+        snake_ate.0 = false;
+    }
 
-                let new_segment_pos = prev_pos;
-                let new_segment_entity =
-                    spawn_body_segment(&mut commands, &new_segment_pos, &game_assets);
+    if snake_ate.0
+        && let Some(last_entity) = last_segment_entity
+    {
+        snake_ate.0 = false;
+
+        let new_segment_pos = prev_pos;
+        let new_segment_entity =
+            spawn_body_segment(&mut commands, &new_segment_pos, &snake_skin, *grid_origin);
+        grow::start_growing(
+            &mut commands,
+            new_segment_entity,
+            &new_segment_pos,
+            &grow_config,
+            *grid_origin,
+        );
 
-                commands
-                    .entity(last_entity)
-                    .insert(NextSegment(new_segment_entity));
+        commands
+            .entity(last_entity)
+            .insert(NextSegment(new_segment_entity));
 
-                ordered_segments.push((new_segment_entity, new_segment_pos));
-            }
-        }
+        ordered_segments.push((new_segment_entity, new_segment_pos));
+    }
 
-        // println!("------------");
-        // println!("{:?}", ordered_segments);
+    // println!("------------");
+    // println!("{:?}", ordered_segments);
 
-        let len = ordered_segments.len();
-        if len >= 3 {
-            for idx in 1..len - 1 {
-                let prev = ordered_segments[idx - 1];
-                let next = ordered_segments[idx + 1];
-                let curr = ordered_segments[idx];
+    update_segment_tiles(&mut commands, &ordered_segments);
+}
 
-                let segment_type = determine_segment_type(&prev.1, &curr.1, &next.1);
-                let atlas_index = segment_type.to_atlas_index();
+// Atlas cell of the head facing `dir`. Every head is drawn through this, so a
+// freshly spawned head already matches the way it moves.
+pub fn head_atlas_index(dir: Dir) -> usize {
+    match dir {
+        Dir::Up => 0,
+        Dir::Right => 1,
+        Dir::Down => 2,
+        Dir::Left => 3,
+    }
+}
 
-                let mut entity_commands = commands.entity(curr.0);
-                entity_commands.queue(move |mut entity: EntityWorldMut| {
-                    // Check if the component exists on the entity.
-                    if let Some(mut sprite) = entity.get_mut::<Sprite>() {
-                        if let Some(ref mut atlas) = sprite.texture_atlas {
-                            atlas.index = atlas_index;
-                        }
-                    }
-                });
-            }
+// Picks the neck, body and tail sprites from the cells around each segment.
+// `ordered_segments` runs from the head to the tail.
+fn update_segment_tiles(commands: &mut Commands, ordered_segments: &[(Entity, Position)]) {
+    let len = ordered_segments.len();
+    if len >= 3 {
+        for idx in 1..len - 1 {
+            let prev = ordered_segments[idx - 1];
+            let next = ordered_segments[idx + 1];
+            let curr = ordered_segments[idx];
+
+            let segment_type = if idx == 1 {
+                determine_neck_type(&prev.1, &curr.1, &next.1)
+            } else {
+                determine_segment_type(&prev.1, &curr.1, &next.1)
+            };
+            commands.entity(curr.0).insert(segment_type);
         }
+    }
 
-        if len >= 2 {
-            let prev = ordered_segments[len - 2];
-            let tail = ordered_segments[len - 1];
+    if len >= 2 {
+        let prev = ordered_segments[len - 2];
+        let tail = ordered_segments[len - 1];
 
-            let segment_type = determine_tail_type(&prev.1, &tail.1);
-            let atlas_index = segment_type.to_atlas_index();
+        let segment_type = determine_tail_type(&prev.1, &tail.1);
+        commands.entity(tail.0).insert(segment_type);
+    }
+}
 
-            let mut entity_commands = commands.entity(tail.0);
-            entity_commands.queue(move |mut entity: EntityWorldMut| {
-                // Check if the component exists on the entity.
-                if let Some(mut sprite) = entity.get_mut::<Sprite>() {
-                    if let Some(ref mut atlas) = sprite.texture_atlas {
-                        atlas.index = atlas_index;
-                    }
-                }
-            });
-        }
+// Runs after everything that can move the snake this frame, ticks, respawns
+// and rewinds alike
+fn apply_segment_sprites(
+    mut segment_query: Query<(&SegmentType, &mut Sprite), Changed<SegmentType>>,
+) {
+    for (segment_type, mut sprite) in segment_query.iter_mut() {
+        segment_type.to_atlas_tile().apply(&mut sprite);
+    }
+}
+
+// Moves a position that left the field to the opposite edge
+fn wrap_position(position: &mut Position, (from, to): ((i8, i8), (i8, i8))) {
+    if position.x < from.0 {
+        position.x = to.0;
+    } else if position.x > to.0 {
+        position.x = from.0;
+    }
+    if position.y < from.1 {
+        position.y = to.1;
+    } else if position.y > to.1 {
+        position.y = from.1;
     }
 }
 
 fn check_self_collision(
-    head_query: Query<&Position, With<Head>>,
-    body_query: Query<&Position, (With<BodySegment>, Without<Head>)>,
-    mut next_state: ResMut<NextState<GameState>>,
+    neck_grace: Res<NeckGrace>,
+    mut turn_events: EventReader<SnakeTurnEvent>,
+    head_query: Query<(&Position, &NextSegment), With<Head>>,
+    body_query: Query<
+        (Entity, &Position, Option<&NextSegment>),
+        (With<BodySegment>, Without<Head>),
+    >,
+    mut crash_events: EventWriter<CrashEvent>,
+    mut snake_errors: ResMut<SnakeErrors>,
 ) {
-    if let Ok(head_pos) = head_query.single() {
-        for body_pos in body_query.iter() {
-            if head_pos.x == body_pos.x && head_pos.y == body_pos.y {
-                println!("Game Over");
-                next_state.set(GameState::GameOver);
-                break;
-            }
+    let just_turned = turn_events.read().count() > 0;
+    let (head_pos, neck) = match head_query.single() {
+        Ok(head) => head,
+        Err(err) => {
+            snake_errors.report("check_self_collision", err);
+            return;
+        }
+    };
+    // The segment behind the neck, where a head that just turned around lands
+    let spared = (neck_grace.0 && just_turned)
+        .then(|| body_query.get(neck.0).ok().and_then(|(_, _, next)| next))
+        .flatten()
+        .map(|next| next.0);
+
+    for (entity, body_pos, _) in body_query.iter() {
+        if Some(entity) == spared {
+            continue;
+        }
+        if head_pos.x == body_pos.x && head_pos.y == body_pos.y {
+            debug!("Head ran into its body at {:?}", head_pos);
+            crash_events.write(CrashEvent(GameOverEvent::SelfCollision));
+            break;
         }
     }
 }
@@ -465,24 +1124,598 @@ fn check_self_collision(
 fn spawn_body_segment(
     commands: &mut Commands,
     position: &Position,
-    game_assets: &Res<GameAssets>,
+    snake_skin: &SnakeSkin,
+    grid_origin: GridOrigin,
 ) -> Entity {
-    let new_screen_position = grid_to_screen_transform(position);
-    let new_segment_entity = commands
-        .spawn((
-            BodySegment,
-            *position,
-            new_screen_position,
-            Sprite {
-                image: game_assets.texture.clone(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: game_assets.texture_atlas_layout.clone(),
-                    index: 15, // Tail segment
-                    ..default()
-                }),
-                ..default()
-            },
-        ))
-        .id();
-    new_segment_entity
+    let new_screen_position = grid_to_screen_transform(position, grid_origin);
+
+    let mut sprite = snake_skin.sprite(0);
+    SegmentType::TailLeft.to_atlas_tile().apply(&mut sprite);
+    commands
+        .spawn((BodySegment, *position, new_screen_position, sprite))
+        .id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Food, FoodKind, FoodSpawnRegion, FoodValues, SnakeAteEvent, check_food_collision};
+
+    fn pos(x: i8, y: i8) -> Position {
+        Position { x, y }
+    }
+
+    // The bench's headless world plus what the collision check reads
+    fn snake_world() -> World {
+        let mut world = bench::bench_world();
+        world.insert_resource(bench::bench_skin());
+        world.init_resource::<NeckGrace>();
+        world.init_resource::<Events<CrashEvent>>();
+        world
+    }
+
+    fn spawn(world: &mut World, cells: &[Position], direction: Dir) {
+        let snake_skin = bench::bench_skin();
+        spawn_snake(
+            &mut world.commands(),
+            cells,
+            direction,
+            &snake_skin,
+            GridOrigin::default(),
+        );
+        world.flush();
+    }
+
+    fn head_positions(world: &mut World) -> Vec<Position> {
+        world
+            .query_filtered::<&Position, With<Head>>()
+            .iter(world)
+            .copied()
+            .collect()
+    }
+
+    // Cells of a three-cell snake that moved `previous` and then `turn`,
+    // returned as (head, neck, segment behind the neck)
+    fn turned(previous: Dir, turn: Dir) -> (Position, Position, Position) {
+        let (px, py) = previous.offset();
+        let (tx, ty) = turn.offset();
+        (pos(tx, ty), pos(0, 0), pos(-px, -py))
+    }
+
+    #[test]
+    fn neck_flares_towards_the_head_after_every_turn() {
+        let cases = [
+            (
+                Dir::Right,
+                Dir::Up,
+                SegmentType::NeckRightUp,
+                AtlasTile::new(15),
+            ),
+            (
+                Dir::Right,
+                Dir::Down,
+                SegmentType::NeckRightDown,
+                AtlasTile::new(15).flip_y(),
+            ),
+            (
+                Dir::Left,
+                Dir::Up,
+                SegmentType::NeckLeftUp,
+                AtlasTile::new(15).flip_x(),
+            ),
+            (
+                Dir::Left,
+                Dir::Down,
+                SegmentType::NeckLeftDown,
+                AtlasTile::new(15).flip_x().flip_y(),
+            ),
+            (
+                Dir::Up,
+                Dir::Right,
+                SegmentType::NeckUpRight,
+                AtlasTile::new(16),
+            ),
+            (
+                Dir::Up,
+                Dir::Left,
+                SegmentType::NeckUpLeft,
+                AtlasTile::new(16).flip_x(),
+            ),
+            (
+                Dir::Down,
+                Dir::Right,
+                SegmentType::NeckDownRight,
+                AtlasTile::new(16).flip_y(),
+            ),
+            (
+                Dir::Down,
+                Dir::Left,
+                SegmentType::NeckDownLeft,
+                AtlasTile::new(16).flip_x().flip_y(),
+            ),
+        ];
+
+        for (previous, turn, expected, tile) in cases {
+            let (head, neck, next) = turned(previous, turn);
+            let neck_type = determine_neck_type(&head, &neck, &next);
+            assert_eq!(neck_type, expected, "{previous:?} then {turn:?}");
+            assert_eq!(
+                neck_type.to_atlas_tile(),
+                tile,
+                "{previous:?} then {turn:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn neck_stays_straight_without_a_turn() {
+        for (dir, expected) in [
+            (Dir::Up, SegmentType::Vertical),
+            (Dir::Down, SegmentType::Vertical),
+            (Dir::Left, SegmentType::Horizontal),
+            (Dir::Right, SegmentType::Horizontal),
+        ] {
+            let (head, neck, next) = turned(dir, dir);
+            assert_eq!(
+                determine_neck_type(&head, &neck, &next),
+                expected,
+                "{dir:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn two_heads_are_skipped_without_panicking() {
+        let mut world = snake_world();
+        spawn(&mut world, &[pos(1, 0), pos(0, 0)], Dir::Right);
+        spawn(&mut world, &[pos(1, 5), pos(0, 5)], Dir::Right);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((apply_queued_turn, movements, check_self_collision).chain());
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+
+        let mut heads = head_positions(&mut world);
+        heads.sort_by_key(|head| head.y);
+        assert_eq!(heads, [pos(1, 0), pos(1, 5)]);
+        assert!(world.resource::<Events<CrashEvent>>().is_empty());
+    }
+
+    #[test]
+    fn mirrored_orientations_share_a_flipped_tile() {
+        // (segment, index, flip_x, flip_y)
+        let cases = [
+            (SegmentType::Horizontal, 4, false, false),
+            (SegmentType::Vertical, 5, false, false),
+            (SegmentType::CornerDownRight, 8, false, false),
+            (SegmentType::CornerLeftDown, 8, true, false),
+            (SegmentType::CornerRightUp, 8, false, true),
+            (SegmentType::CornerUpLeft, 8, true, true),
+            (SegmentType::TailUp, 9, false, false),
+            (SegmentType::TailDown, 9, false, true),
+            (SegmentType::TailRight, 10, false, false),
+            (SegmentType::TailLeft, 10, true, false),
+        ];
+
+        for (segment, index, flip_x, flip_y) in cases {
+            let mut sprite = bench::bench_skin().sprite(0);
+            segment.to_atlas_tile().apply(&mut sprite);
+            assert_eq!(
+                (
+                    sprite.texture_atlas.map(|atlas| atlas.index),
+                    sprite.flip_x,
+                    sprite.flip_y
+                ),
+                (Some(index), flip_x, flip_y),
+                "{segment:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn applying_a_tile_clears_the_previous_flip() {
+        let mut sprite = bench::bench_skin().sprite(0);
+        SegmentType::CornerUpLeft.to_atlas_tile().apply(&mut sprite);
+        SegmentType::Horizontal.to_atlas_tile().apply(&mut sprite);
+        assert!(!sprite.flip_x && !sprite.flip_y);
+    }
+
+    fn head_index(world: &mut World) -> usize {
+        let mut sprites = world.query_filtered::<&Sprite, With<Head>>();
+        let sprite = sprites.single(world).unwrap();
+        sprite.texture_atlas.as_ref().unwrap().index
+    }
+
+    #[test]
+    fn head_index_matches_the_direction_from_spawn_to_movement() {
+        let cases = [
+            (Dir::Up, 0),
+            (Dir::Right, 1),
+            (Dir::Down, 2),
+            (Dir::Left, 3),
+        ];
+        for (dir, index) in cases {
+            assert_eq!(head_atlas_index(dir), index, "{:?}", dir);
+
+            let mut world = snake_world();
+            let (bx, by) = dir.opposite().offset();
+            spawn(
+                &mut world,
+                &[pos(0, 0), pos(bx, by), pos(2 * bx, 2 * by)],
+                dir,
+            );
+            assert_eq!(head_index(&mut world), index, "spawned facing {:?}", dir);
+
+            let mut schedule = Schedule::default();
+            schedule.add_systems(movements);
+            schedule.run(&mut world);
+            assert_ne!(head_positions(&mut world), [pos(0, 0)], "{:?}", dir);
+            assert_eq!(head_index(&mut world), index, "moving {:?}", dir);
+        }
+    }
+
+    #[test]
+    fn reversal_policy_by_length() {
+        // (policy, length, may reverse)
+        let cases = [
+            (ReversalPolicy::Never, 1, false),
+            (ReversalPolicy::Never, 3, false),
+            (ReversalPolicy::AllowWhenShort, 1, true),
+            (ReversalPolicy::AllowWhenShort, 3, false),
+        ];
+        for (policy, length, reverses) in cases {
+            assert_eq!(
+                policy.allows(Dir::Right, Dir::Left, length),
+                reverses,
+                "{:?} at length {}",
+                policy,
+                length
+            );
+            // Turning sideways never depends on the policy
+            assert!(policy.allows(Dir::Right, Dir::Up, length));
+        }
+    }
+
+    // Holds the left key for one frame of input handling, returns where the
+    // head of a snake on `cells` facing right wants to go next
+    fn steer_left(policy: ReversalPolicy, cells: &[Position]) -> Dir {
+        let mut world = snake_world();
+        world.insert_resource(policy);
+        world.init_resource::<PlayerBindings>();
+        world.init_resource::<InvertVertical>();
+        world.init_resource::<TurnMode>();
+        world.init_resource::<AfkTimer>();
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::KeyA);
+        world.insert_resource(keyboard_input);
+
+        if cells.len() == 1 {
+            // Nothing spawns a lone head, so it's put together by hand
+            world.spawn((
+                Head,
+                PlayerId::One,
+                Direction(Dir::Right),
+                LastDirection(Dir::Right),
+                TurnQueue::default(),
+                cells[0],
+            ));
+        } else {
+            spawn(&mut world, cells, Dir::Right);
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(handle_inputs);
+        schedule.run(&mut world);
+
+        let mut directions = world.query_filtered::<&Direction, With<Head>>();
+        directions.single(&world).unwrap().0
+    }
+
+    #[test]
+    fn lone_head_reverses_only_when_allowed() {
+        let head = [pos(0, 0)];
+        assert_eq!(steer_left(ReversalPolicy::Never, &head), Dir::Right);
+        assert_eq!(steer_left(ReversalPolicy::AllowWhenShort, &head), Dir::Left);
+    }
+
+    #[test]
+    fn three_cell_snake_never_reverses() {
+        let cells = [pos(0, 0), pos(-1, 0), pos(-2, 0)];
+        assert_eq!(steer_left(ReversalPolicy::Never, &cells), Dir::Right);
+        assert_eq!(
+            steer_left(ReversalPolicy::AllowWhenShort, &cells),
+            Dir::Right
+        );
+    }
+
+    #[test]
+    fn opposite_keys_cancel_out() {
+        use Dir::*;
+        // (held keys, directions left to steer with)
+        let cases: [(&[Dir], &[Dir]); 12] = [
+            (&[], &[]),
+            (&[Up], &[Up]),
+            (&[Left, Up], &[Left, Up]),
+            (&[Left, Right], &[]),
+            (&[Up, Down], &[]),
+            (&[Left, Right, Up], &[Up]),
+            (&[Left, Right, Down], &[Down]),
+            (&[Up, Down, Left], &[Left]),
+            (&[Up, Down, Right], &[Right]),
+            (&[Left, Right, Up, Down], &[]),
+            // Order doesn't matter, only which keys are held
+            (&[Right, Up, Left], &[Up]),
+            (&[Down, Right, Up], &[Right]),
+        ];
+        for (active, expected) in cases {
+            assert_eq!(without_conflicts(active), expected, "{:?}", active);
+        }
+    }
+
+    #[test]
+    fn segment_joins_the_sides_its_neighbours_are_on() {
+        use Dir::*;
+        use SegmentType::*;
+        // (side of the segment towards the head, side towards the tail, tile)
+        let cases = [
+            (Left, Right, Horizontal),
+            (Right, Left, Horizontal),
+            (Up, Down, Vertical),
+            (Down, Up, Vertical),
+            (Down, Right, CornerDownRight),
+            (Right, Down, CornerDownRight),
+            (Left, Down, CornerLeftDown),
+            (Down, Left, CornerLeftDown),
+            (Up, Left, CornerUpLeft),
+            (Left, Up, CornerUpLeft),
+            (Right, Up, CornerRightUp),
+            (Up, Right, CornerRightUp),
+        ];
+        let side = |dir: Dir| {
+            let (x, y) = dir.offset();
+            pos(x, y)
+        };
+        for (towards_head, towards_tail, expected) in cases {
+            assert_eq!(
+                determine_segment_type(&side(towards_head), &pos(0, 0), &side(towards_tail)),
+                expected,
+                "{:?} to {:?}",
+                towards_head,
+                towards_tail
+            );
+        }
+    }
+
+    // Tiles along the snake from the head to the tail, as drawn
+    fn drawn_tiles(world: &mut World) -> Vec<AtlasTile> {
+        let mut heads = world.query_filtered::<(Entity, &NextSegment), With<Head>>();
+        let (head, first_segment) = heads.single(world).unwrap();
+        let mut chain = vec![head];
+        let mut current = Some(first_segment.0);
+        while let Some(entity) = current {
+            chain.push(entity);
+            current = world.get::<NextSegment>(entity).map(|next| next.0);
+        }
+
+        chain
+            .into_iter()
+            .map(|entity| {
+                let sprite = world.get::<Sprite>(entity).unwrap();
+                AtlasTile {
+                    index: sprite.texture_atlas.as_ref().unwrap().index,
+                    flip_x: sprite.flip_x,
+                    flip_y: sprite.flip_y,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sprites_follow_a_sequence_of_turns() {
+        let mut world = snake_world();
+        spawn(
+            &mut world,
+            &[pos(0, 0), pos(-1, 0), pos(-2, 0), pos(-3, 0)],
+            Dir::Right,
+        );
+        let mut schedule = Schedule::default();
+        schedule.add_systems((movements, apply_segment_sprites).chain());
+
+        let mut steer = |world: &mut World, dir: Dir| {
+            let mut directions = world.query_filtered::<&mut Direction, With<Head>>();
+            directions.single_mut(world).unwrap().0 = dir;
+            schedule.run(world);
+            drawn_tiles(world)
+        };
+
+        // Head, neck, body, tail
+        let head = |dir| AtlasTile::new(head_atlas_index(dir));
+        assert_eq!(
+            steer(&mut world, Dir::Up),
+            [
+                head(Dir::Up),
+                SegmentType::NeckRightUp.to_atlas_tile(),
+                SegmentType::Horizontal.to_atlas_tile(),
+                SegmentType::TailLeft.to_atlas_tile(),
+            ]
+        );
+        assert_eq!(
+            steer(&mut world, Dir::Up),
+            [
+                head(Dir::Up),
+                SegmentType::Vertical.to_atlas_tile(),
+                SegmentType::CornerUpLeft.to_atlas_tile(),
+                SegmentType::TailLeft.to_atlas_tile(),
+            ]
+        );
+        assert_eq!(
+            steer(&mut world, Dir::Left),
+            [
+                head(Dir::Left),
+                SegmentType::NeckUpLeft.to_atlas_tile(),
+                SegmentType::Vertical.to_atlas_tile(),
+                SegmentType::TailDown.to_atlas_tile(),
+            ]
+        );
+        // Raw indices, so a wrong mapping can't hide behind to_atlas_tile
+        let indices: Vec<_> = drawn_tiles(&mut world)
+            .iter()
+            .map(|tile| tile.index)
+            .collect();
+        assert_eq!(indices, [3, 16, 5, 9]);
+    }
+
+    // Cells of the snake after it ate `meals` foods in a row, each laid right
+    // in front of the head, and the score they gave
+    fn eat_in_a_row(max_length: MaxLength, meals: usize) -> (usize, usize) {
+        let mut world = snake_world();
+        world.insert_resource(max_length);
+        world.insert_resource(Score(0));
+        world.init_resource::<FoodValues>();
+        world.init_resource::<FoodSpawnRegion>();
+        world.insert_resource(TestMode::new(true));
+        world.init_resource::<Events<SnakeAteEvent>>();
+        spawn(&mut world, &[pos(0, 0), pos(-1, 0), pos(-2, 0)], Dir::Right);
+        let food = world
+            .spawn((Food(FoodKind::Normal), pos(1, 0), Transform::default()))
+            .id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((movements, check_food_collision).chain());
+        for _ in 0..meals {
+            let head = head_positions(&mut world)[0];
+            *world.get_mut::<Position>(food).unwrap() = pos(head.x + 1, head.y);
+            schedule.run(&mut world);
+        }
+        // One more tick for the last meal to grow the snake
+        schedule.run(&mut world);
+
+        let length = world.query::<&BodySegment>().iter(&world).count() + 1;
+        (length, world.resource::<Score>().0)
+    }
+
+    #[test]
+    fn eating_at_the_cap_scores_without_growing() {
+        assert_eq!(eat_in_a_row(MaxLength(Some(3)), 4), (3, 4));
+        assert_eq!(eat_in_a_row(MaxLength(Some(5)), 4), (5, 4));
+        assert_eq!(eat_in_a_row(MaxLength(None), 4), (7, 4));
+    }
+
+    // Steps a snake takes over `frame_times`, one frame each. Its timer runs
+    // as in the game, from a 0.5 s turn delay.
+    fn steps_over(timing_mode: TimingMode, frame_times: &[f32]) -> usize {
+        let mut world = snake_world();
+        world.insert_resource(timing_mode);
+        world.insert_resource(TurnDelay(0.5));
+        world.insert_resource(Timer(0.5));
+        world.init_resource::<Time>();
+        world.init_resource::<RampConfig>();
+        world.init_resource::<RampTimer>();
+        world.init_resource::<Dashing>();
+        world.init_resource::<DashConfig>();
+        world.init_resource::<boost::Boosting>();
+        world.init_resource::<BoostConfig>();
+        world.init_resource::<HeadSpeedFactor>();
+        world.init_resource::<TestMode>();
+        world.insert_resource(Score(0));
+        spawn(&mut world, &[pos(0, 0), pos(-1, 0)], Dir::Right);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((update_timer, movements, reset_timer).chain());
+        for seconds in frame_times {
+            world
+                .resource_mut::<Time>()
+                .advance_by(std::time::Duration::from_secs_f32(*seconds));
+            schedule.run(&mut world);
+        }
+        world.resource::<RunStats>().moves
+    }
+
+    #[test]
+    fn frame_locked_steps_only_count_frames() {
+        let steady = [1.0 / 60.0; 300];
+        let uneven: Vec<f32> = (0..300)
+            .map(|frame| if frame % 3 == 0 { 0.2 } else { 0.001 })
+            .collect();
+
+        // 300 frames of 1/60 s are five seconds, a step every 30 frames
+        assert_eq!(steps_over(TimingMode::FrameLocked, &steady), 10);
+        assert_eq!(steps_over(TimingMode::FrameLocked, &uneven), 10);
+        assert_eq!(steps_over(TimingMode::FrameLocked, &[5.0; 300]), 10);
+
+        // The same frames on the wall clock depend on how long they took
+        assert_eq!(steps_over(TimingMode::RealTime, &steady), 10);
+        assert_ne!(steps_over(TimingMode::RealTime, &uneven), 10);
+    }
+
+    // Crashes of a four-cell snake going right that turns straight back
+    fn crashes_turning_around(neck_grace: bool) -> usize {
+        let mut world = snake_world();
+        world.insert_resource(NeckGrace(neck_grace));
+        spawn(
+            &mut world,
+            &[pos(0, 0), pos(-1, 0), pos(-2, 0), pos(-3, 0)],
+            Dir::Right,
+        );
+        let mut directions = world.query_filtered::<&mut Direction, With<Head>>();
+        directions.single_mut(&mut world).unwrap().0 = Dir::Left;
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((movements, check_self_collision).chain());
+        schedule.run(&mut world);
+        assert_eq!(head_positions(&mut world), [pos(-1, 0)]);
+        // Past the turn the head runs along where the body was, not into it
+        schedule.run(&mut world);
+        assert_eq!(head_positions(&mut world), [pos(-2, 0)]);
+
+        world.resource::<Events<CrashEvent>>().len()
+    }
+
+    #[test]
+    fn neck_grace_spares_a_snake_turning_around() {
+        assert_eq!(crashes_turning_around(false), 1);
+        assert_eq!(crashes_turning_around(true), 0);
+    }
+
+    #[test]
+    fn neck_grace_spares_only_the_segment_behind_the_neck() {
+        let mut world = snake_world();
+        world.insert_resource(NeckGrace(true));
+        // Curled up so the next step up lands on the last segment
+        spawn(
+            &mut world,
+            &[pos(0, 0), pos(1, 0), pos(1, 1), pos(0, 1), pos(-1, 1)],
+            Dir::Up,
+        );
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((movements, check_self_collision).chain());
+        schedule.run(&mut world);
+
+        assert_eq!(head_positions(&mut world), [pos(0, 1)]);
+        assert_eq!(world.resource::<Events<CrashEvent>>().len(), 1);
+    }
+
+    #[test]
+    fn neck_grace_only_applies_on_a_turn() {
+        let mut world = snake_world();
+        world.insert_resource(NeckGrace(true));
+        spawn(
+            &mut world,
+            &[pos(0, 0), pos(-1, 0), pos(-2, 0), pos(-3, 0)],
+            Dir::Right,
+        );
+        // Already heading left, so this tick sends no turn event
+        let mut heads = world.query_filtered::<(&mut Direction, &mut LastDirection), With<Head>>();
+        let (mut direction, mut last_direction) = heads.single_mut(&mut world).unwrap();
+        direction.0 = Dir::Left;
+        last_direction.0 = Dir::Left;
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((movements, check_self_collision).chain());
+        schedule.run(&mut world);
+
+        assert!(world.resource::<Events<SnakeTurnEvent>>().is_empty());
+        assert_eq!(world.resource::<Events<CrashEvent>>().len(), 1);
+    }
 }
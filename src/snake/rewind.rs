@@ -0,0 +1,263 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use super::{
+    BodySegment, Direction, HasMoved, Head, LastDirection, NextSegment, TurnQueue,
+    get_direction_between_positions, head_atlas_index, update_segment_tiles,
+};
+use crate::{
+    CrashEvent, GameState, GameplaySet, GridOrigin, Position, Score, grid_to_screen_position,
+};
+
+const DEFAULT_REWIND_TICKS: usize = 2;
+const DEFAULT_REWIND_PENALTY: usize = 3;
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Forgiving>();
+        app.init_resource::<RewindConfig>();
+        app.init_resource::<BodyHistory>();
+        app.init_resource::<AwaitingTurn>();
+        app.add_systems(OnEnter(GameState::StartGame), reset_rewind);
+        app.add_systems(
+            Update,
+            (
+                record_body_history
+                    .after(GameplaySet::Collision)
+                    .before(GameplaySet::Crash),
+                rewind_on_crash
+                    .in_set(GameplaySet::Crash)
+                    .run_if(resource_equals(Forgiving(true))),
+                clear_awaiting_turn,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// Forgiving difficulty: a crash rewinds the snake a few ticks and costs points
+// instead of a life, so the run never ends on a collision
+#[derive(Resource, Default, PartialEq)]
+pub struct Forgiving(pub bool);
+
+#[derive(Resource)]
+pub struct RewindConfig {
+    // Ticks the snake is moved back after a crash
+    pub ticks: usize,
+    // Points taken off the score for each rewind
+    pub penalty: usize,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        Self {
+            ticks: DEFAULT_REWIND_TICKS,
+            penalty: DEFAULT_REWIND_PENALTY,
+        }
+    }
+}
+
+// Cells the head went through, newest first. Every segment follows the head,
+// so segment `i` is always on `cells[i]` and `cells[i + n]` is where it was
+// `n` ticks ago.
+#[derive(Resource, Default)]
+struct BodyHistory {
+    cells: VecDeque<Position>,
+}
+
+// After a rewind the snake holds still until the player steers it again
+#[derive(Resource, Default)]
+pub struct AwaitingTurn(pub bool);
+
+fn reset_rewind(mut history: ResMut<BodyHistory>, mut awaiting_turn: ResMut<AwaitingTurn>) {
+    history.cells.clear();
+    awaiting_turn.0 = false;
+}
+
+// Body segments from the neck to the tail
+fn body_chain(
+    first_segment: Entity,
+    body_query: &Query<(&Position, Option<&NextSegment>), (With<BodySegment>, Without<Head>)>,
+) -> Vec<(Entity, Position)> {
+    let mut chain = Vec::new();
+    let mut current = Some(first_segment);
+    while let Some(entity) = current
+        && let Ok((pos, next)) = body_query.get(entity)
+    {
+        chain.push((entity, *pos));
+        current = next.map(|next| next.0);
+    }
+    chain
+}
+
+fn record_body_history(
+    config: Res<RewindConfig>,
+    head_query: Query<(Ref<Position>, &NextSegment), With<Head>>,
+    body_query: Query<(&Position, Option<&NextSegment>), (With<BodySegment>, Without<Head>)>,
+    mut history: ResMut<BodyHistory>,
+) {
+    let Ok((head_pos, first_segment)) = head_query.single() else {
+        return;
+    };
+
+    // A new snake starts with the cells it was spawned on
+    if head_pos.is_added() || history.cells.is_empty() {
+        let body = body_chain(first_segment.0, &body_query);
+        history.cells = std::iter::once(*head_pos)
+            .chain(body.into_iter().map(|(_, pos)| pos))
+            .collect();
+        return;
+    }
+
+    // A rewind already put the head back on the newest cell
+    if !head_pos.is_changed()
+        || history
+            .cells
+            .front()
+            .is_some_and(|pos| pos.x == head_pos.x && pos.y == head_pos.y)
+    {
+        return;
+    }
+
+    history.cells.push_front(*head_pos);
+    let length = body_query.iter().count() + 1;
+    history.cells.truncate(length + config.ticks + 1);
+}
+
+fn rewind_on_crash(
+    mut crash_events: EventReader<CrashEvent>,
+    config: Res<RewindConfig>,
+    mut commands: Commands,
+    mut history: ResMut<BodyHistory>,
+    mut score: ResMut<Score>,
+    mut has_moved: ResMut<HasMoved>,
+    mut awaiting_turn: ResMut<AwaitingTurn>,
+    grid_origin: Res<GridOrigin>,
+    mut head_query: Query<
+        (
+            Entity,
+            &NextSegment,
+            &mut Direction,
+            &mut LastDirection,
+            &mut TurnQueue,
+            &mut Sprite,
+        ),
+        With<Head>,
+    >,
+    mut snake_query: Query<
+        (&mut Position, &mut Transform, Option<&NextSegment>),
+        Or<(With<Head>, With<BodySegment>)>,
+    >,
+) {
+    // Several collisions on the same tick still rewind once
+    if crash_events.read().count() == 0 {
+        return;
+    }
+    let Ok((head, first_segment, mut direction, mut last_direction, mut turn_queue, mut sprite)) =
+        head_query.single_mut()
+    else {
+        return;
+    };
+
+    let mut chain = vec![head];
+    let mut current = Some(first_segment.0);
+    while let Some(entity) = current
+        && let Ok((_, _, next)) = snake_query.get(entity)
+    {
+        chain.push(entity);
+        current = next.map(|next| next.0);
+    }
+
+    // Early in a run there may be less history than asked for
+    let ticks = config
+        .ticks
+        .min(history.cells.len().saturating_sub(chain.len()));
+    history.cells.drain(..ticks);
+
+    let mut ordered_segments = Vec::with_capacity(chain.len());
+    for (entity, cell) in chain.into_iter().zip(history.cells.iter()) {
+        if let Ok((mut position, mut transform, _)) = snake_query.get_mut(entity) {
+            *position = *cell;
+            transform.translation = grid_to_screen_position(cell, *grid_origin);
+        }
+        ordered_segments.push((entity, *cell));
+    }
+    update_segment_tiles(&mut commands, &ordered_segments);
+
+    // Face the way the head came, the player picks where to go from here
+    let facing = history
+        .cells
+        .get(1)
+        .zip(history.cells.front())
+        .and_then(|(from, to)| get_direction_between_positions(from, to))
+        .unwrap_or(last_direction.0);
+    direction.0 = facing;
+    last_direction.0 = facing;
+    turn_queue.0.clear();
+    if let Some(ref mut atlas) = sprite.texture_atlas {
+        atlas.index = head_atlas_index(facing);
+    }
+
+    score.0 = score.0.saturating_sub(config.penalty);
+    has_moved.0 = false;
+    awaiting_turn.0 = true;
+    info!("Rewound {} ticks, score: {}", ticks, score.0);
+}
+
+fn clear_awaiting_turn(has_moved: Res<HasMoved>, mut awaiting_turn: ResMut<AwaitingTurn>) {
+    if awaiting_turn.0 && has_moved.0 {
+        awaiting_turn.0 = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::super::{Dir, apply_segment_sprites, bench, movements, spawn_snake};
+    use crate::GameOverEvent;
+
+    #[test]
+    fn crash_rewinds_the_snake_and_costs_points() {
+        let mut world = bench::bench_world();
+        let snake_skin = bench::bench_skin();
+        world.init_resource::<RewindConfig>();
+        world.init_resource::<BodyHistory>();
+        world.insert_resource(Score(10));
+        world.init_resource::<Events<CrashEvent>>();
+        let cells = [
+            Position { x: 2, y: 0 },
+            Position { x: 1, y: 0 },
+            Position { x: 0, y: 0 },
+        ];
+        spawn_snake(
+            &mut world.commands(),
+            &cells,
+            Dir::Right,
+            &snake_skin,
+            GridOrigin::default(),
+        );
+        world.flush();
+        world.insert_resource(snake_skin);
+
+        let mut tick = Schedule::default();
+        tick.add_systems((movements, apply_segment_sprites, record_body_history).chain());
+        for _ in 0..3 {
+            tick.run(&mut world);
+        }
+
+        world.send_event(CrashEvent(GameOverEvent::SelfCollision));
+        world.run_system_once(rewind_on_crash).unwrap();
+
+        let mut head = world.query_filtered::<&Position, With<Head>>();
+        assert_eq!(*head.single(&world).unwrap(), Position { x: 3, y: 0 });
+        assert_eq!(world.resource::<Score>().0, 10 - DEFAULT_REWIND_PENALTY);
+        assert!(world.resource::<AwaitingTurn>().0);
+        assert!(!world.resource::<HasMoved>().0);
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use super::{
+    BodySegment, Dir, Direction, HasMoved, Head, LastDirection, NextSegment, ReversalPolicy,
+    SnakeErrors, snake_length,
+};
+use crate::pause::AfkTimer;
+
+const DEFAULT_MIN_SWIPE_DISTANCE: f32 = 30.0;
+
+pub struct SwipePlugin;
+
+impl Plugin for SwipePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SwipeConfig>();
+        app.init_resource::<SwipeStart>();
+    }
+}
+
+#[derive(Resource)]
+pub struct SwipeConfig {
+    // Drag length in logical pixels before a swipe turns the snake
+    pub min_distance: f32,
+}
+
+impl Default for SwipeConfig {
+    fn default() -> Self {
+        Self {
+            min_distance: DEFAULT_MIN_SWIPE_DISTANCE,
+        }
+    }
+}
+
+// Where the current drag started, or the point of the last recognised swipe
+#[derive(Resource, Default)]
+pub(super) struct SwipeStart(Option<Vec2>);
+
+// Position of the pressed pointer this frame and whether the press just began.
+// A touch takes priority over the mouse.
+fn pointer_position(
+    mouse_input: &ButtonInput<MouseButton>,
+    window: Option<&Window>,
+    touches: &Touches,
+) -> Option<(Vec2, bool)> {
+    if let Some(touch) = touches.iter().next() {
+        return Some((touch.position(), touches.just_pressed(touch.id())));
+    }
+
+    if mouse_input.pressed(MouseButton::Left) {
+        let position = window?.cursor_position()?;
+        return Some((position, mouse_input.just_pressed(MouseButton::Left)));
+    }
+
+    None
+}
+
+// Window coordinates grow downwards, so a negative delta.y is a swipe up
+fn swipe_direction(delta: Vec2) -> Dir {
+    if delta.x.abs() > delta.y.abs() {
+        if delta.x > 0.0 { Dir::Right } else { Dir::Left }
+    } else if delta.y > 0.0 {
+        Dir::Down
+    } else {
+        Dir::Up
+    }
+}
+
+pub(super) fn read_swipe_input(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    swipe_config: Res<SwipeConfig>,
+    mut swipe_start: ResMut<SwipeStart>,
+    reversal_policy: Res<ReversalPolicy>,
+    mut head_query: Query<(&mut Direction, &LastDirection, Option<&NextSegment>), With<Head>>,
+    body_query: Query<Option<&NextSegment>, (With<BodySegment>, Without<Head>)>,
+    mut has_moved: ResMut<HasMoved>,
+    mut afk_timer: ResMut<AfkTimer>,
+    mut snake_errors: ResMut<SnakeErrors>,
+) {
+    let Some((position, just_pressed)) =
+        pointer_position(&mouse_input, window_query.single().ok(), &touches)
+    else {
+        swipe_start.0 = None;
+        return;
+    };
+
+    let start = match swipe_start.0 {
+        Some(start) if !just_pressed => start,
+        _ => {
+            swipe_start.0 = Some(position);
+            return;
+        }
+    };
+
+    let delta = position - start;
+    if delta.length() < swipe_config.min_distance {
+        return;
+    }
+
+    // Keep tracking from here so one long drag can steer several times
+    swipe_start.0 = Some(position);
+    afk_timer.0 = 0.0;
+
+    let (mut direction, last_direction, first_segment) = match head_query.single_mut() {
+        Ok(head) => head,
+        Err(err) => {
+            snake_errors.report("read_swipe_input", err);
+            return;
+        }
+    };
+
+    let new_direction = swipe_direction(delta);
+    let length = snake_length(first_segment.map(|next| next.0), &body_query);
+    if reversal_policy.allows(last_direction.0, new_direction, length) {
+        direction.0 = new_direction;
+        has_moved.0 = true;
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+
+use super::{BodySegment, Head, NextSegment};
+use crate::{TILE_SIZE, settings::Settings};
+
+// Sprite size of a Thick snake, the grid and collisions stay one cell per segment
+const THICK_SIZE: f32 = TILE_SIZE * 2.0;
+// Thick segments overlap their neighbours, so each one sits slightly behind
+// the one in front of it. Stays in front of the ghost (z -0.05).
+const THICK_Z_SPREAD: f32 = 0.04;
+
+pub struct ThicknessPlugin;
+
+impl Plugin for ThicknessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnakeThickness>();
+        app.add_systems(
+            Update,
+            apply_thickness_setting.run_if(resource_changed::<Settings>),
+        );
+        // After the tick has moved the segments, which resets their depth
+        app.add_systems(
+            PostUpdate,
+            apply_snake_thickness.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+// How big the snake is drawn. Thick doubles every sprite for a chunkier look.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnakeThickness {
+    #[default]
+    Normal,
+    Thick,
+}
+
+impl SnakeThickness {
+    pub fn next(self) -> Self {
+        match self {
+            SnakeThickness::Normal => SnakeThickness::Thick,
+            SnakeThickness::Thick => SnakeThickness::Normal,
+        }
+    }
+
+    // Identifier used in the settings file
+    pub fn key(self) -> &'static str {
+        match self {
+            SnakeThickness::Normal => "normal",
+            SnakeThickness::Thick => "thick",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [SnakeThickness::Normal, SnakeThickness::Thick]
+            .into_iter()
+            .find(|thickness| thickness.key() == key)
+    }
+
+    fn size(self) -> Option<Vec2> {
+        match self {
+            SnakeThickness::Normal => None,
+            SnakeThickness::Thick => Some(Vec2::splat(THICK_SIZE)),
+        }
+    }
+}
+
+fn apply_thickness_setting(settings: Res<Settings>, mut thickness: ResMut<SnakeThickness>) {
+    thickness.set_if_neq(settings.snake_thickness);
+}
+
+// Sizes the head and every segment the same way, and layers them from the
+// head down so a turn always shows the segment closer to the head on top.
+// The size is set on the sprite rather than the transform, so the grow
+// animation's scale still applies on top of it.
+fn apply_snake_thickness(
+    thickness: Res<SnakeThickness>,
+    mut head_query: Query<(&mut Sprite, &mut Transform, &NextSegment), With<Head>>,
+    mut body_query: Query<
+        (&mut Sprite, &mut Transform, Option<&NextSegment>),
+        (With<BodySegment>, Without<Head>),
+    >,
+) {
+    if *thickness == SnakeThickness::Normal && !thickness.is_changed() {
+        return;
+    }
+    let size = thickness.size();
+    let thick = *thickness == SnakeThickness::Thick;
+
+    for (mut sprite, mut transform, first_segment) in head_query.iter_mut() {
+        if sprite.custom_size != size {
+            sprite.custom_size = size;
+        }
+        if transform.translation.z != 0.0 {
+            transform.translation.z = 0.0;
+        }
+
+        let mut ordered = Vec::new();
+        let mut current = Some(first_segment.0);
+        while let Some(entity) = current
+            && let Ok((_, _, next)) = body_query.get(entity)
+        {
+            ordered.push(entity);
+            current = next.map(|next| next.0);
+        }
+
+        for (index, entity) in ordered.iter().enumerate() {
+            if let Ok((mut sprite, mut transform, _)) = body_query.get_mut(*entity) {
+                if sprite.custom_size != size {
+                    sprite.custom_size = size;
+                }
+                let z = if thick {
+                    -THICK_Z_SPREAD * (index + 1) as f32 / ordered.len() as f32
+                } else {
+                    0.0
+                };
+                if transform.translation.z != z {
+                    transform.translation.z = z;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+use crate::{GridOrigin, Position, assets_loader::SnakeSkin, grid_to_screen_transform};
+
+// Seconds a trail sprite takes to fade out
+const TRAIL_LIFETIME: f32 = 0.4;
+const TRAIL_START_ALPHA: f32 = 0.35;
+const MAX_TRAIL_SPRITES: usize = 8;
+// Behind the snake (z 0), in front of the floor tiles
+const TRAIL_Z: f32 = -1.0;
+
+pub struct TrailPlugin;
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowTrail>();
+        app.add_systems(Update, age_trail);
+    }
+}
+
+// Leaves fading copies of the head behind it to give a sense of speed
+#[derive(Resource, Default)]
+pub struct ShowTrail(pub bool);
+
+// Seconds since the sprite was left behind
+#[derive(Component)]
+struct Trail(f32);
+
+pub(super) fn spawn_trail(
+    commands: &mut Commands,
+    position: &Position,
+    atlas_index: usize,
+    snake_skin: &SnakeSkin,
+    grid_origin: GridOrigin,
+) {
+    let mut sprite = snake_skin.sprite(atlas_index);
+    sprite.color = Color::srgba(1.0, 1.0, 1.0, TRAIL_START_ALPHA);
+
+    let mut transform = grid_to_screen_transform(position, grid_origin);
+    transform.translation.z = TRAIL_Z;
+
+    commands.spawn((Trail(0.0), sprite, transform));
+}
+
+fn age_trail(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trail_query: Query<(Entity, &mut Trail, &mut Sprite)>,
+) {
+    let mut alive = Vec::new();
+
+    for (entity, mut trail, mut sprite) in trail_query.iter_mut() {
+        trail.0 += time.delta_secs();
+        if trail.0 >= TRAIL_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let alpha = TRAIL_START_ALPHA * (1.0 - trail.0 / TRAIL_LIFETIME);
+        sprite.color.set_alpha(alpha);
+        alive.push((trail.0, entity));
+    }
+
+    // Drop the oldest sprites when the snake moves faster than the trail fades
+    if alive.len() > MAX_TRAIL_SPRITES {
+        alive.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, entity) in alive.iter().take(alive.len() - MAX_TRAIL_SPRITES) {
+            commands.entity(*entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+use crate::{
+    GameState, GameplaySet, GridConfig, GridOrigin, Position, TILE_SIZE, grid_to_screen_transform,
+    levels::Levels, portals::Portal, snake::Head,
+};
+
+const FAST_COLOR: Color = Color::srgba(0.3, 0.9, 1.0, 0.45);
+const SLOW_COLOR: Color = Color::srgba(0.6, 0.4, 0.2, 0.6);
+// Above the floor, below portals and the snake
+const SPEED_TILE_Z: f32 = -0.75;
+
+pub struct SpeedTilesPlugin;
+
+impl Plugin for SpeedTilesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeadSpeedFactor>();
+        app.add_systems(
+            OnEnter(GameState::StartGame),
+            (
+                despawn_speed_tiles,
+                spawn_speed_tiles,
+                reset_head_speed_factor,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            Update,
+            update_head_speed_factor
+                .after(GameplaySet::Movement)
+                .before(GameplaySet::Collision)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// Multiplies the turn delay while the head is on this cell, like the dash
+// factor: below 1 is a fast zone, above 1 a slow one
+#[derive(Component)]
+pub struct SpeedTile {
+    pub factor: f32,
+}
+
+// Factor of the tile under the head, 1 when it's on regular floor
+#[derive(Resource)]
+pub struct HeadSpeedFactor(pub f32);
+
+impl Default for HeadSpeedFactor {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+fn despawn_speed_tiles(mut commands: Commands, query: Query<Entity, With<SpeedTile>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn spawn_speed_tiles(
+    mut commands: Commands,
+    levels: Res<Levels>,
+    grid: Res<GridConfig>,
+    grid_origin: Res<GridOrigin>,
+    portal_query: Query<&Position, With<Portal>>,
+) {
+    for ((x, y), factor) in levels.current().speed_tiles.iter().copied() {
+        let in_field = x >= grid.from.0 && x <= grid.to.0 && y >= grid.from.1 && y <= grid.to.1;
+        let on_portal = portal_query.iter().any(|pos| pos.x == x && pos.y == y);
+        if !in_field || on_portal || factor <= 0.0 {
+            println!("Skipping speed tile {:?} with factor {}", (x, y), factor);
+            continue;
+        }
+
+        let color = if factor < 1.0 { FAST_COLOR } else { SLOW_COLOR };
+        let position = Position { x, y };
+        let mut transform = grid_to_screen_transform(&position, *grid_origin);
+        transform.translation.z = SPEED_TILE_Z;
+        commands.spawn((
+            SpeedTile { factor },
+            Sprite::from_color(color, Vec2::splat(TILE_SIZE)),
+            position,
+            transform,
+        ));
+    }
+}
+
+fn reset_head_speed_factor(mut head_speed_factor: ResMut<HeadSpeedFactor>) {
+    head_speed_factor.0 = 1.0;
+}
+
+fn update_head_speed_factor(
+    head_query: Query<&Position, With<Head>>,
+    tile_query: Query<(&SpeedTile, &Position), Without<Head>>,
+    mut head_speed_factor: ResMut<HeadSpeedFactor>,
+) {
+    let Ok(head_pos) = head_query.single() else {
+        return;
+    };
+
+    let factor = tile_query
+        .iter()
+        .find(|(_, pos)| pos.x == head_pos.x && pos.y == head_pos.y)
+        .map_or(1.0, |(tile, _)| tile.factor);
+    if head_speed_factor.0 != factor {
+        head_speed_factor.0 = factor;
+    }
+}
@@ -0,0 +1,399 @@
+use std::{collections::HashMap, fs};
+
+use bevy::prelude::*;
+
+// Swap this file to translate the game. Lines are `key = text`, `#` starts a comment,
+// `\n` is a line break and `{name}` is replaced with a value at runtime.
+const STRINGS_PATH: &str = "assets/strings.txt";
+
+pub struct StringsPlugin;
+
+impl Plugin for StringsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Strings::load(STRINGS_PATH));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageId {
+    Fps,
+    Score,
+    Time,
+    Paused,
+    GameOver,
+    MenuTitle,
+    MenuStart,
+    ModeClassic,
+    ModeTimed,
+    ModeEndless,
+    MenuSkin,
+    MenuSettings,
+    SettingsTitle,
+    SettingsHint,
+    SettingControls,
+    TrapWarning,
+    AchievementUnlocked,
+    SettingPauseKey,
+    SettingRestartKey,
+    SettingsPressKey,
+    SettingsKeyConflict,
+    ControlsOverlay,
+    StatLength,
+    StatTime,
+    StatFoods,
+    StatTopSpeed,
+    MenuPeaceful,
+    On,
+    Off,
+    SettingInvertVertical,
+    MenuLeaderboard,
+    LeaderboardTitle,
+    LeaderboardEntry,
+    LeaderboardEmpty,
+    LeaderboardHint,
+    SettingScoreInTitle,
+    WindowTitleScore,
+    SettingTurnMode,
+    TurnModeHeld,
+    TurnModeTap,
+    MenuLevel,
+    SettingDeathReplay,
+    SettingStableCamera,
+    Lives,
+    MenuForgiving,
+    MenuContinue,
+    MenuContinueEmpty,
+    SettingFoodBob,
+    StatPointsPerFood,
+    StatEfficiency,
+    SettingHideCursor,
+    SettingQuickRestartKey,
+    SettingHeatmap,
+    SettingGhost,
+    LoadError,
+    GameOverWall,
+    GameOverSelf,
+    GameOverTime,
+    SettingRestartOnAnyKey,
+    AnyKey,
+    SettingSnakeThickness,
+    ThicknessNormal,
+    ThicknessThick,
+    SettingPauseSummary,
+    PauseSummary,
+    SettingMusic,
+    MenuChallenge,
+    MenuChallengeInput,
+    ChallengeInvalid,
+    ChallengeCode,
+    SettingEndGameKey,
+    PeacefulHint,
+}
+
+impl MessageId {
+    const ALL: [MessageId; 72] = [
+        MessageId::Fps,
+        MessageId::Score,
+        MessageId::Time,
+        MessageId::Paused,
+        MessageId::GameOver,
+        MessageId::MenuTitle,
+        MessageId::MenuStart,
+        MessageId::ModeClassic,
+        MessageId::ModeTimed,
+        MessageId::ModeEndless,
+        MessageId::MenuSkin,
+        MessageId::MenuSettings,
+        MessageId::SettingsTitle,
+        MessageId::SettingsHint,
+        MessageId::SettingControls,
+        MessageId::TrapWarning,
+        MessageId::AchievementUnlocked,
+        MessageId::SettingPauseKey,
+        MessageId::SettingRestartKey,
+        MessageId::SettingsPressKey,
+        MessageId::SettingsKeyConflict,
+        MessageId::ControlsOverlay,
+        MessageId::StatLength,
+        MessageId::StatTime,
+        MessageId::StatFoods,
+        MessageId::StatTopSpeed,
+        MessageId::MenuPeaceful,
+        MessageId::On,
+        MessageId::Off,
+        MessageId::SettingInvertVertical,
+        MessageId::MenuLeaderboard,
+        MessageId::LeaderboardTitle,
+        MessageId::LeaderboardEntry,
+        MessageId::LeaderboardEmpty,
+        MessageId::LeaderboardHint,
+        MessageId::SettingScoreInTitle,
+        MessageId::WindowTitleScore,
+        MessageId::SettingTurnMode,
+        MessageId::TurnModeHeld,
+        MessageId::TurnModeTap,
+        MessageId::MenuLevel,
+        MessageId::SettingDeathReplay,
+        MessageId::SettingStableCamera,
+        MessageId::Lives,
+        MessageId::MenuForgiving,
+        MessageId::MenuContinue,
+        MessageId::MenuContinueEmpty,
+        MessageId::SettingFoodBob,
+        MessageId::StatPointsPerFood,
+        MessageId::StatEfficiency,
+        MessageId::SettingHideCursor,
+        MessageId::SettingQuickRestartKey,
+        MessageId::SettingHeatmap,
+        MessageId::SettingGhost,
+        MessageId::LoadError,
+        MessageId::GameOverWall,
+        MessageId::GameOverSelf,
+        MessageId::GameOverTime,
+        MessageId::SettingRestartOnAnyKey,
+        MessageId::AnyKey,
+        MessageId::SettingSnakeThickness,
+        MessageId::ThicknessNormal,
+        MessageId::ThicknessThick,
+        MessageId::SettingPauseSummary,
+        MessageId::PauseSummary,
+        MessageId::SettingMusic,
+        MessageId::MenuChallenge,
+        MessageId::MenuChallengeInput,
+        MessageId::ChallengeInvalid,
+        MessageId::ChallengeCode,
+        MessageId::SettingEndGameKey,
+        MessageId::PeacefulHint,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            MessageId::Fps => "fps",
+            MessageId::Score => "score",
+            MessageId::Time => "time",
+            MessageId::Paused => "paused",
+            MessageId::GameOver => "game_over",
+            MessageId::MenuTitle => "menu_title",
+            MessageId::MenuStart => "menu_start",
+            MessageId::ModeClassic => "mode_classic",
+            MessageId::ModeTimed => "mode_timed",
+            MessageId::ModeEndless => "mode_endless",
+            MessageId::MenuSkin => "menu_skin",
+            MessageId::MenuSettings => "menu_settings",
+            MessageId::SettingsTitle => "settings_title",
+            MessageId::SettingsHint => "settings_hint",
+            MessageId::SettingControls => "setting_controls",
+            MessageId::TrapWarning => "trap_warning",
+            MessageId::AchievementUnlocked => "achievement_unlocked",
+            MessageId::SettingPauseKey => "setting_pause_key",
+            MessageId::SettingRestartKey => "setting_restart_key",
+            MessageId::SettingsPressKey => "settings_press_key",
+            MessageId::SettingsKeyConflict => "settings_key_conflict",
+            MessageId::ControlsOverlay => "controls_overlay",
+            MessageId::StatLength => "stat_length",
+            MessageId::StatTime => "stat_time",
+            MessageId::StatFoods => "stat_foods",
+            MessageId::StatTopSpeed => "stat_top_speed",
+            MessageId::MenuPeaceful => "menu_peaceful",
+            MessageId::On => "on",
+            MessageId::Off => "off",
+            MessageId::SettingInvertVertical => "setting_invert_vertical",
+            MessageId::MenuLeaderboard => "menu_leaderboard",
+            MessageId::LeaderboardTitle => "leaderboard_title",
+            MessageId::LeaderboardEntry => "leaderboard_entry",
+            MessageId::LeaderboardEmpty => "leaderboard_empty",
+            MessageId::LeaderboardHint => "leaderboard_hint",
+            MessageId::SettingScoreInTitle => "setting_score_in_title",
+            MessageId::WindowTitleScore => "window_title_score",
+            MessageId::SettingTurnMode => "setting_turn_mode",
+            MessageId::TurnModeHeld => "turn_mode_held",
+            MessageId::TurnModeTap => "turn_mode_tap",
+            MessageId::MenuLevel => "menu_level",
+            MessageId::SettingDeathReplay => "setting_death_replay",
+            MessageId::SettingStableCamera => "setting_stable_camera",
+            MessageId::Lives => "lives",
+            MessageId::MenuForgiving => "menu_forgiving",
+            MessageId::MenuContinue => "menu_continue",
+            MessageId::MenuContinueEmpty => "menu_continue_empty",
+            MessageId::SettingFoodBob => "setting_food_bob",
+            MessageId::StatPointsPerFood => "stat_points_per_food",
+            MessageId::StatEfficiency => "stat_efficiency",
+            MessageId::SettingHideCursor => "setting_hide_cursor",
+            MessageId::SettingQuickRestartKey => "setting_quick_restart_key",
+            MessageId::SettingHeatmap => "setting_heatmap",
+            MessageId::SettingGhost => "setting_ghost",
+            MessageId::LoadError => "load_error",
+            MessageId::GameOverWall => "game_over_wall",
+            MessageId::GameOverSelf => "game_over_self",
+            MessageId::GameOverTime => "game_over_time",
+            MessageId::SettingRestartOnAnyKey => "setting_restart_on_any_key",
+            MessageId::AnyKey => "any_key",
+            MessageId::SettingSnakeThickness => "setting_snake_thickness",
+            MessageId::ThicknessNormal => "thickness_normal",
+            MessageId::ThicknessThick => "thickness_thick",
+            MessageId::SettingPauseSummary => "setting_pause_summary",
+            MessageId::PauseSummary => "pause_summary",
+            MessageId::SettingMusic => "setting_music",
+            MessageId::MenuChallenge => "menu_challenge",
+            MessageId::MenuChallengeInput => "menu_challenge_input",
+            MessageId::ChallengeInvalid => "challenge_invalid",
+            MessageId::ChallengeCode => "challenge_code",
+            MessageId::SettingEndGameKey => "setting_end_game_key",
+            MessageId::PeacefulHint => "peaceful_hint",
+        }
+    }
+
+    // English defaults, used when the file is missing or lacks a key
+    fn default_text(self) -> &'static str {
+        match self {
+            MessageId::Fps => "FPS: {fps}",
+            MessageId::Score => "Score: {score}",
+            MessageId::Time => "Time: {time}",
+            MessageId::Paused => "Paused\nQ: save and quit",
+            MessageId::GameOver => {
+                "GAME OVER\nScore: {score}\n{stats}\nPress {restart} to restart\nPress {menu} for menu"
+            }
+            MessageId::MenuTitle => "SNAKE",
+            MessageId::MenuStart => "Press Enter to start",
+            MessageId::ModeClassic => "Classic",
+            MessageId::ModeTimed => "Timed ({time}s)",
+            MessageId::ModeEndless => "Endless",
+            MessageId::MenuSkin => "Skin: {skin}",
+            MessageId::MenuSettings => "Settings",
+            MessageId::SettingsTitle => "SETTINGS",
+            MessageId::SettingsHint => "Enter to change, Esc to go back",
+            MessageId::SettingControls => "Controls: {layout}",
+            MessageId::TrapWarning => "Careful, you're boxing yourself in!",
+            MessageId::AchievementUnlocked => "Achievement unlocked: {name}",
+            MessageId::SettingPauseKey => "Pause key: {key}",
+            MessageId::SettingRestartKey => "Restart key: {key}",
+            MessageId::SettingsPressKey => "Press the new key",
+            MessageId::SettingsKeyConflict => "{key} is already in use",
+            MessageId::ControlsOverlay => {
+                "CONTROLS\nUp: {up}\nDown: {down}\nLeft: {left}\nRight: {right}\nDash: Shift\nPause: {pause}\nRestart: {restart}\nQuick restart: {quick_restart}\n\nPress H to close"
+            }
+            MessageId::StatLength => "Length: {value}",
+            MessageId::StatTime => "Time survived: {value}s",
+            MessageId::StatFoods => "Food eaten: {value}",
+            MessageId::StatTopSpeed => "Top speed: {value} cells/s",
+            MessageId::MenuPeaceful => "Peaceful: {state} ({key} ends the game)",
+            MessageId::On => "On",
+            MessageId::Off => "Off",
+            MessageId::SettingInvertVertical => "Invert up/down: {state}",
+            MessageId::MenuLeaderboard => "Leaderboard",
+            MessageId::LeaderboardTitle => "TOP SCORES",
+            MessageId::LeaderboardEntry => "{rank}. {score}   {date}",
+            MessageId::LeaderboardEmpty => "No scores yet",
+            MessageId::LeaderboardHint => "Esc to go back",
+            MessageId::SettingScoreInTitle => "Score in title: {state}",
+            MessageId::WindowTitleScore => "{title} — Score: {score}",
+            MessageId::SettingTurnMode => "Turning: {mode}",
+            MessageId::TurnModeHeld => "Hold key",
+            MessageId::TurnModeTap => "Tap",
+            MessageId::MenuLevel => "Level: {level}",
+            MessageId::SettingDeathReplay => "Death replay: {state}",
+            MessageId::SettingStableCamera => "Stable camera: {state}",
+            MessageId::Lives => "Lives: {lives}",
+            MessageId::MenuForgiving => "Forgiving: {state}",
+            MessageId::MenuContinue => "Continue ({score} points)",
+            MessageId::MenuContinueEmpty => "Continue (no saved game)",
+            MessageId::SettingFoodBob => "Food animation: {state}",
+            MessageId::StatPointsPerFood => "Points per food: {value}",
+            MessageId::StatEfficiency => "Foods per 100 moves: {value}",
+            MessageId::SettingHideCursor => "Hide cursor in game: {state}",
+            MessageId::SettingQuickRestartKey => "Quick restart key: {key}",
+            MessageId::SettingHeatmap => "Heatmap after a run: {state}",
+            MessageId::SettingGhost => "Best run ghost (timed): {state}",
+            MessageId::LoadError => {
+                "Failed to load assets\n{paths}\n\nPress {retry} to retry\nPress {quit} to quit"
+            }
+            MessageId::GameOverWall => "You hit the wall",
+            MessageId::GameOverSelf => "You bit yourself",
+            MessageId::GameOverTime => "Time's up",
+            MessageId::SettingRestartOnAnyKey => "Restart on any key: {state}",
+            MessageId::AnyKey => "any key",
+            MessageId::SettingSnakeThickness => "Snake thickness: {thickness}",
+            MessageId::ThicknessNormal => "Normal",
+            MessageId::ThicknessThick => "Thick",
+            MessageId::SettingPauseSummary => "Pause summary: {state}",
+            MessageId::PauseSummary => "Score: {score}\nLength: {length}\nTime: {time}s",
+            MessageId::SettingMusic => "Music: {state}",
+            MessageId::MenuChallenge => "Enter challenge code",
+            MessageId::MenuChallengeInput => "Code: {code}_",
+            MessageId::ChallengeInvalid => "Invalid code, Esc to cancel",
+            MessageId::ChallengeCode => "Challenge code: {code}",
+            MessageId::SettingEndGameKey => "End peaceful game key: {key}",
+            MessageId::PeacefulHint => "Peaceful: {key} ends the game",
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Strings {
+    messages: HashMap<MessageId, String>,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Self {
+            messages: MessageId::ALL
+                .iter()
+                .map(|id| (*id, id.default_text().to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl Strings {
+    fn load(path: &str) -> Self {
+        let mut strings = Self::default();
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                println!("Could not read {}: {}, using default strings", path, err);
+                return strings;
+            }
+        };
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, text)) = line.split_once('=') else {
+                println!("Skipping malformed line in {}: {}", path, line);
+                continue;
+            };
+
+            let key = key.trim();
+            match MessageId::ALL.iter().find(|id| id.key() == key) {
+                Some(id) => {
+                    strings
+                        .messages
+                        .insert(*id, text.trim().replace("\\n", "\n"));
+                }
+                None => println!("Unknown string key in {}: {}", path, key),
+            }
+        }
+
+        strings
+    }
+
+    pub fn get(&self, id: MessageId) -> &str {
+        self.messages
+            .get(&id)
+            .map(String::as_str)
+            .unwrap_or(id.default_text())
+    }
+
+    // Fills `{name}` placeholders with the given values
+    pub fn format(&self, id: MessageId, args: &[(&str, &dyn ToString)]) -> String {
+        let mut text = self.get(id).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+use crate::{GameState, Score};
+
+const DEFAULT_INTERVAL: f32 = 10.0;
+const DEFAULT_POINTS: usize = 0;
+
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SurvivalConfig>();
+        app.init_resource::<SurvivalTimer>();
+        app.add_systems(OnEnter(GameState::StartGame), reset_survival_timer);
+        app.add_systems(
+            Update,
+            award_survival_points.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// Points awarded for every `interval` seconds survived. Zero points keeps the
+// classic food-only scoring.
+#[derive(Resource)]
+pub struct SurvivalConfig {
+    pub interval: f32,
+    pub points: usize,
+}
+
+impl Default for SurvivalConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            points: DEFAULT_POINTS,
+        }
+    }
+}
+
+// Seconds survived since the last award
+#[derive(Resource, Default)]
+pub struct SurvivalTimer(pub f32);
+
+fn reset_survival_timer(mut survival_timer: ResMut<SurvivalTimer>) {
+    survival_timer.0 = 0.0;
+}
+
+// Only ticks in InGame, so time spent paused doesn't count
+fn award_survival_points(
+    time: Res<Time>,
+    survival_config: Res<SurvivalConfig>,
+    mut survival_timer: ResMut<SurvivalTimer>,
+    mut score: ResMut<Score>,
+) {
+    if survival_config.points == 0 || survival_config.interval <= 0.0 {
+        return;
+    }
+
+    survival_timer.0 += time.delta_secs();
+    while survival_timer.0 >= survival_config.interval {
+        survival_timer.0 -= survival_config.interval;
+        // The HUD counter follows Score on its own
+        score.0 += survival_config.points;
+    }
+}
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// Deterministic runs for scripted end-to-end checks: seeded food and obstacle
+// placement, a fixed tick length instead of wall-clock time and no animations.
+// Only available in builds with the `test_mode` feature:
+//
+//     cargo run --features test_mode -- --test-mode
+#[cfg(feature = "test_mode")]
+pub const TEST_MODE_FLAG: &str = "--test-mode";
+// Seconds the snake's timer advances per frame, whatever the frame took
+const FIXED_TICK: f32 = 1.0 / 60.0;
+const SEED: u64 = 0x5eed;
+
+#[derive(Resource)]
+pub struct TestMode {
+    pub enabled: bool,
+    rng: ChaCha8Rng,
+    // A challenge run draws from the seeded generator too
+    seeded: bool,
+}
+
+impl TestMode {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            rng: ChaCha8Rng::seed_from_u64(SEED),
+            seeded: false,
+        }
+    }
+
+    // Restarts the seeded sequence from `seed`, for the rest of the session
+    pub fn seed_run(&mut self, seed: u64) {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.seeded = true;
+    }
+
+    // Random cell in the inclusive bounds, the same sequence every test run
+    pub fn random_cell(&mut self, from: (i8, i8), to: (i8, i8)) -> (i8, i8) {
+        if self.enabled || self.seeded {
+            (
+                self.rng.random_range(from.0..=to.0),
+                self.rng.random_range(from.1..=to.1),
+            )
+        } else {
+            let mut rng = rand::rng();
+            (
+                rng.random_range(from.0..=to.0),
+                rng.random_range(from.1..=to.1),
+            )
+        }
+    }
+
+    pub fn delta_secs(&self, time: &Time) -> f32 {
+        if self.enabled {
+            FIXED_TICK
+        } else {
+            time.delta_secs()
+        }
+    }
+
+    // Test runs keep away from the player's config, settings and save files,
+    // so they neither depend on what's in them nor change them
+    pub fn uses_user_files(&self) -> bool {
+        !self.enabled
+    }
+}
+
+impl Default for TestMode {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+// Whether a plugin should load the player's files, for plugins added without
+// the game plugin too
+pub fn uses_user_files(app: &App) -> bool {
+    app.world()
+        .get_resource::<TestMode>()
+        .is_none_or(TestMode::uses_user_files)
+}
+
+// Run condition for systems that only save to the player's files
+pub fn saves_user_files(test_mode: Option<Res<TestMode>>) -> bool {
+    test_mode.is_none_or(|test_mode| test_mode.uses_user_files())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_keeps_away_from_user_files() {
+        let mut app = App::new();
+        assert!(uses_user_files(&app), "plugins added on their own");
+        app.insert_resource(TestMode::new(false));
+        assert!(uses_user_files(&app));
+        app.insert_resource(TestMode::new(true));
+        assert!(!uses_user_files(&app));
+    }
+}
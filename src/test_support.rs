@@ -0,0 +1,137 @@
+// Drives the whole game headless, for the unit tests here and, with the
+// test_mode feature, the end-to-end tests in tests/
+
+use std::time::Duration;
+
+use bevy::{
+    audio::AudioPlugin,
+    log::LogPlugin,
+    prelude::*,
+    render::{
+        RenderPlugin,
+        settings::{RenderCreation, WgpuSettings},
+    },
+    winit::WinitPlugin,
+};
+
+use crate::{
+    Food, GameState, Position, SnakeGamePlugin,
+    controls::{InvertVertical, KeyBindings},
+    snake::Head,
+};
+
+// Frames to wait for a state change before a test gives up. Loading the
+// spritesheets happens on another thread, so it may take a few.
+const MAX_FRAMES: usize = 500;
+
+// The whole game in test mode, without a window, GPU, audio or logging.
+// Scripted like a --test-mode run: seeded placement and a fixed timer step
+// every frame.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .build()
+            .disable::<WinitPlugin>()
+            .disable::<AudioPlugin>()
+            .disable::<LogPlugin>()
+            .set(RenderPlugin {
+                render_creation: RenderCreation::Automatic(WgpuSettings {
+                    backends: None,
+                    ..default()
+                }),
+                ..default()
+            })
+            .set(ImagePlugin::default_nearest()),
+    );
+    app.add_plugins(SnakeGamePlugin {
+        test_mode: true,
+        demo_gallery: false,
+    });
+    // What App::run would do before the first frame, registers the image loader
+    app.finish();
+    app.cleanup();
+    wait_for_state(&mut app, GameState::MainMenu);
+    app
+}
+
+pub(crate) fn wait_for_state(app: &mut App, state: GameState) {
+    for _ in 0..MAX_FRAMES {
+        if *app.world().resource::<State<GameState>>() == state {
+            return;
+        }
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    panic!(
+        "never reached {:?}, still in {:?}",
+        state,
+        app.world().resource::<State<GameState>>().get()
+    );
+}
+
+// Starts a new run the way the menu does and waits for it to begin
+pub fn start_game(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::StartGame);
+    app.update();
+    wait_for_state(app, GameState::InGame);
+}
+
+pub fn in_game(app: &App) -> bool {
+    *app.world().resource::<State<GameState>>() == GameState::InGame
+}
+
+pub fn head_cell(app: &mut App) -> Option<(i8, i8)> {
+    app.world_mut()
+        .query_filtered::<&Position, With<Head>>()
+        .iter(app.world())
+        .next()
+        .map(|pos| (pos.x, pos.y))
+}
+
+// Holds the key that steers the head one step closer to `cell`, whichever
+// keys the game has bound
+pub fn steer_towards(app: &mut App, cell: (i8, i8)) {
+    let Some(head) = head_cell(app) else {
+        return;
+    };
+    let key_bindings = *app.world().resource::<KeyBindings>();
+    let (up, down) = app
+        .world()
+        .resource::<InvertVertical>()
+        .vertical_keys(&key_bindings);
+    let key = if head.0 < cell.0 {
+        key_bindings.right
+    } else if head.0 > cell.0 {
+        key_bindings.left
+    } else if head.1 < cell.1 {
+        up
+    } else {
+        down
+    };
+    let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    input.release_all();
+    input.press(key);
+}
+
+// Cells of every food on the board, sorted
+pub fn food_cells(app: &mut App) -> Vec<(i8, i8)> {
+    let mut cells: Vec<_> = app
+        .world_mut()
+        .query_filtered::<&Position, With<Food>>()
+        .iter(app.world())
+        .map(|pos| (pos.x, pos.y))
+        .collect();
+    cells.sort();
+    cells
+}
+
+#[cfg(test)]
+pub(crate) fn count<F: bevy::ecs::query::QueryFilter>(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), F>()
+        .iter(app.world())
+        .count()
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+use crate::{
+    BorderThickness, GameMode, GameOverEvent, GameState, GridConfig, TILE_SIZE,
+    camera::CameraAnchored,
+    strings::{MessageId, Strings},
+    test_mode::TestMode,
+};
+
+const DEFAULT_DURATION: f32 = 60.0;
+
+pub struct TimedModePlugin;
+
+impl Plugin for TimedModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimedModeConfig>();
+        app.init_resource::<TimedMode>();
+        app.add_systems(Startup, init_timer_text);
+        app.add_systems(OnEnter(GameState::StartGame), reset_countdown);
+        app.add_systems(
+            Update,
+            tick_countdown
+                .run_if(in_state(GameState::InGame))
+                .run_if(resource_equals(GameMode::Timed)),
+        );
+    }
+}
+
+// Round length in seconds for the timed mode
+#[derive(Resource)]
+pub struct TimedModeConfig {
+    pub duration: f32,
+}
+
+impl Default for TimedModeConfig {
+    fn default() -> Self {
+        Self {
+            duration: DEFAULT_DURATION,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TimedMode {
+    pub remaining: f32,
+}
+
+#[derive(Component)]
+struct TimerText;
+
+fn init_timer_text(
+    mut commands: Commands,
+    grid: Res<GridConfig>,
+    border_thickness: Res<BorderThickness>,
+) {
+    let world_pos = Vec3::new(
+        grid.to.0 as f32 * TILE_SIZE - TILE_SIZE,
+        grid.to.1 as f32 * TILE_SIZE + TILE_SIZE * 2.0 + border_thickness.outset(),
+        1.0,
+    );
+
+    commands.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 8.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        Transform::from_translation(world_pos),
+        CameraAnchored(world_pos),
+        Visibility::Hidden,
+        TimerText,
+    ));
+}
+
+fn reset_countdown(
+    game_mode: Res<GameMode>,
+    config: Res<TimedModeConfig>,
+    strings: Res<Strings>,
+    mut timed_mode: ResMut<TimedMode>,
+    mut query: Query<(&mut Visibility, &mut Text2d), With<TimerText>>,
+) {
+    timed_mode.remaining = config.duration;
+
+    if let Ok((mut visibility, mut text)) = query.single_mut() {
+        if *game_mode == GameMode::Timed {
+            *visibility = Visibility::Visible;
+            text.0 = format_remaining(timed_mode.remaining, &strings);
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn tick_countdown(
+    time: Res<Time>,
+    test_mode: Res<TestMode>,
+    strings: Res<Strings>,
+    mut timed_mode: ResMut<TimedMode>,
+    mut query: Query<&mut Text2d, With<TimerText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut game_over_events: EventWriter<GameOverEvent>,
+) {
+    // Counts down in the same fixed steps as the snake's timer in test mode
+    timed_mode.remaining = (timed_mode.remaining - test_mode.delta_secs(&time)).max(0.0);
+
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = format_remaining(timed_mode.remaining, &strings);
+    }
+
+    if timed_mode.remaining <= 0.0 {
+        info!("Time is up");
+        game_over_events.write(GameOverEvent::TimeUp);
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn format_remaining(remaining: f32, strings: &Strings) -> String {
+    strings.format(MessageId::Time, &[("time", &remaining.ceil())])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn test_mode_counts_down_by_the_fixed_tick() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        // A slow frame, which test mode doesn't count
+        time.advance_by(Duration::from_secs(1));
+        world.insert_resource(time);
+        world.insert_resource(TestMode::new(true));
+        world.init_resource::<Strings>();
+        world.insert_resource(TimedMode { remaining: 10.0 });
+        world.init_resource::<NextState<GameState>>();
+        world.init_resource::<Events<GameOverEvent>>();
+
+        world.run_system_once(tick_countdown).unwrap();
+
+        let remaining = world.resource::<TimedMode>().remaining;
+        let expected = 10.0 - TestMode::new(true).delta_secs(&Time::default());
+        assert!((remaining - expected).abs() < 1e-6, "{remaining}");
+    }
+}
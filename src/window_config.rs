@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use bevy::{
+    asset::LoadState, prelude::*, render::render_resource::TextureFormat, window::PrimaryWindow,
+    winit::WinitWindows,
+};
+use winit::window::Icon;
+
+use crate::{
+    GameState, Score,
+    settings::Settings,
+    strings::{MessageId, Strings},
+};
+
+const DEFAULT_TITLE: &str = "Snake";
+
+pub struct WindowConfigPlugin;
+
+impl Plugin for WindowConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScoreInTitle>();
+        app.init_resource::<HideCursor>();
+        app.add_systems(Startup, load_window_icon);
+        app.add_systems(
+            Update,
+            (
+                apply_score_in_title.run_if(resource_changed::<Settings>),
+                update_window_title.run_if(
+                    resource_changed::<ScoreInTitle>
+                        .or(resource_changed::<Score>)
+                        .or(state_changed::<GameState>),
+                ),
+            )
+                .chain(),
+        );
+        app.add_systems(
+            Update,
+            apply_window_icon.run_if(resource_exists::<PendingWindowIcon>),
+        );
+        app.add_systems(
+            Update,
+            apply_hide_cursor.run_if(resource_changed::<Settings>),
+        );
+        app.add_systems(
+            OnEnter(GameState::InGame),
+            hide_cursor.run_if(resource_equals(HideCursor(true))),
+        );
+        app.add_systems(OnExit(GameState::InGame), show_cursor);
+        // Never leave the cursor hidden behind
+        app.add_systems(Last, show_cursor.run_if(on_event::<AppExit>));
+    }
+}
+
+// Title and optional icon (a path relative to `assets/`) for the game window
+#[derive(Resource, Clone)]
+pub struct WindowConfig {
+    pub title: String,
+    pub icon: Option<String>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: DEFAULT_TITLE.to_string(),
+            icon: None,
+        }
+    }
+}
+
+impl WindowConfig {
+    pub fn window(&self) -> Window {
+        Window {
+            title: self.title.clone(),
+            resolution: (800.0, 600.0).into(),
+
+            ..default()
+        }
+    }
+}
+
+// Show the running score in the window title, for setups without a visible HUD
+#[derive(Resource, Default)]
+pub struct ScoreInTitle(pub bool);
+
+fn apply_score_in_title(settings: Res<Settings>, mut score_in_title: ResMut<ScoreInTitle>) {
+    if score_in_title.0 != settings.score_in_title {
+        score_in_title.0 = settings.score_in_title;
+    }
+}
+
+// Hide the OS cursor while the snake is moving, menus and pause still show it
+#[derive(Resource, Default, PartialEq)]
+pub struct HideCursor(pub bool);
+
+fn apply_hide_cursor(settings: Res<Settings>, mut hide: ResMut<HideCursor>) {
+    hide.set_if_neq(HideCursor(settings.hide_cursor));
+}
+
+fn set_cursor_visible(window_query: &mut Query<&mut Window, With<PrimaryWindow>>, visible: bool) {
+    if let Ok(mut window) = window_query.single_mut()
+        && window.cursor_options.visible != visible
+    {
+        window.cursor_options.visible = visible;
+    }
+}
+
+fn hide_cursor(mut window_query: Query<&mut Window, With<PrimaryWindow>>) {
+    set_cursor_visible(&mut window_query, false);
+}
+
+fn show_cursor(mut window_query: Query<&mut Window, With<PrimaryWindow>>) {
+    set_cursor_visible(&mut window_query, true);
+}
+
+// Runs only when the score, the state or the option changes. The plain title
+// comes back outside of a run and when the option is turned off.
+fn update_window_title(
+    score_in_title: Res<ScoreInTitle>,
+    score: Res<Score>,
+    game_state: Res<State<GameState>>,
+    window_config: Res<WindowConfig>,
+    strings: Res<Strings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let in_run = matches!(game_state.get(), GameState::InGame | GameState::Paused);
+    let title = if score_in_title.0 && in_run {
+        strings.format(
+            MessageId::WindowTitleScore,
+            &[("title", &window_config.title), ("score", &score.0)],
+        )
+    } else {
+        window_config.title.clone()
+    };
+
+    if let Ok(mut window) = window_query.single_mut()
+        && window.title != title
+    {
+        window.title = title;
+    }
+}
+
+// The icon image is loaded through the asset server and handed to winit once ready
+#[derive(Resource)]
+struct PendingWindowIcon(Handle<Image>);
+
+fn load_window_icon(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    window_config: Res<WindowConfig>,
+) {
+    let Some(icon_path) = &window_config.icon else {
+        return;
+    };
+
+    if !Path::new("assets").join(icon_path).exists() {
+        println!("Window icon {} not found, keeping the default", icon_path);
+        return;
+    }
+
+    commands.insert_resource(PendingWindowIcon(asset_server.load(icon_path.clone())));
+}
+
+fn apply_window_icon(
+    mut commands: Commands,
+    pending_icon: Res<PendingWindowIcon>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    winit_windows: NonSend<WinitWindows>,
+) {
+    if let LoadState::Failed(err) = asset_server.load_state(&pending_icon.0) {
+        println!("Could not load window icon: {}", err);
+        commands.remove_resource::<PendingWindowIcon>();
+        return;
+    }
+
+    // Wait for both the image and the OS window to exist
+    let Some(image) = images.get(&pending_icon.0) else {
+        return;
+    };
+    if winit_windows.windows.is_empty() {
+        return;
+    }
+
+    let icon = image
+        .convert(TextureFormat::Rgba8UnormSrgb)
+        .and_then(|image| {
+            let (width, height) = (image.width(), image.height());
+            Icon::from_rgba(image.data?, width, height).ok()
+        });
+
+    match icon {
+        Some(icon) => {
+            for window in winit_windows.windows.values() {
+                window.set_window_icon(Some(icon.clone()));
+            }
+        }
+        None => println!("Window icon has an unsupported format"),
+    }
+
+    commands.remove_resource::<PendingWindowIcon>();
+}
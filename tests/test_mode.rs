@@ -0,0 +1,56 @@
+// End-to-end runs of the whole game in test mode, headless:
+//
+//     cargo test --features test_mode
+use bevy::prelude::*;
+use bv_demo_project::{
+    RunStats,
+    test_support::{food_cells, headless_app, in_game, start_game, steer_towards},
+};
+
+fn moves(app: &App) -> usize {
+    app.world().resource::<RunStats>().moves
+}
+
+fn steer_towards_food(app: &mut App) {
+    if let Some(&food) = food_cells(app).first() {
+        steer_towards(app, food);
+    }
+}
+
+// Plays `frames` frames chasing the food. Returns where each food appeared
+// and the steps the snake took.
+fn chase_food(frames: usize) -> (Vec<(i8, i8)>, usize) {
+    let mut app = headless_app();
+    start_game(&mut app);
+
+    let mut placed = food_cells(&mut app);
+    for _ in 0..frames {
+        steer_towards_food(&mut app);
+        app.update();
+        assert!(in_game(&app), "the run ended early");
+        let food = food_cells(&mut app);
+        if food != placed[placed.len() - food.len()..] {
+            placed.extend(food);
+        }
+    }
+    (placed, moves(&app))
+}
+
+// Eight seconds at the fixed 1/60 s step
+const FRAMES: usize = 480;
+
+#[test]
+fn seeded_run_places_food_the_same_way() {
+    let (placed, _) = chase_food(FRAMES);
+    // The first food sits at a fixed cell, the next comes from the seed
+    assert_eq!(placed, [(3, 3), (-5, 2)]);
+    assert_eq!(chase_food(FRAMES).0, placed, "a second run differs");
+}
+
+#[test]
+fn fixed_tick_takes_the_same_steps_every_run() {
+    // Frame times don't matter in test mode, only the number of frames: a
+    // step every 49 frames at the start, faster once the first food is eaten
+    assert_eq!(chase_food(FRAMES / 2).1, 4);
+    assert_eq!(chase_food(FRAMES).1, 10);
+}